@@ -0,0 +1,542 @@
+pub mod snapshot;
+
+use crate::model::classes::{Class, ClassCache, Origin};
+use snapshot::CampaignSnapshot;
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::runtime::Runtime;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tracing::{error, info, warn};
+
+use std::convert::Infallible;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::{Arc, RwLock};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * Actions that an authenticated client may ask a campaign to perform
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ApiAction {
+    UsePrimary { name: String },
+    UseSecondary { name: String },
+    UseSpecial { name: String },
+    UseInspiration,
+    NextTurn,
+    NextBattle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ApiActionError {
+    #[error("No open campaign with id {0}")]
+    UnknownCampaign(usize),
+    #[error("The character has no action named \"{0}\"")]
+    UnknownAction(String),
+    #[error("The action \"{0}\" can not be used right now")]
+    ActionUnavailable(String),
+    #[error("The app stopped before the action could be performed")]
+    Dropped,
+}
+
+impl IntoResponse for ApiActionError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiActionError::UnknownCampaign(_) | ApiActionError::UnknownAction(_) => {
+                StatusCode::NOT_FOUND
+            }
+            ApiActionError::ActionUnavailable(_) => StatusCode::CONFLICT,
+            ApiActionError::Dropped => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/**
+ * An action requested over the API, waiting for the GUI to perform it
+ */
+#[derive(Debug)]
+pub struct ApiRequest {
+    campaign: usize,
+    action: ApiAction,
+    reply: oneshot::Sender<Result<CampaignSnapshot, ApiActionError>>,
+}
+
+impl ApiRequest {
+    pub fn get_campaign(&self) -> usize {
+        self.campaign
+    }
+
+    pub fn get_action(&self) -> &ApiAction {
+        &self.action
+    }
+
+    pub fn respond(self, result: Result<CampaignSnapshot, ApiActionError>) {
+        if self.reply.send(result).is_err() {
+            warn!("API client went away before the action was answered");
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ApiShared {
+    campaigns: RwLock<Vec<CampaignSnapshot>>,
+    class_cache: RwLock<ClassCache>,
+    updates: broadcast::Sender<Vec<CampaignSnapshot>>,
+    requests: mpsc::UnboundedSender<ApiRequest>,
+    token: String,
+    repaint_ctx: egui::Context,
+}
+
+/**
+ * Handle to the embedded HTTP server, owned by the GUI thread
+ */
+#[derive(Debug)]
+pub struct ApiServer {
+    shared: Arc<ApiShared>,
+    requests: mpsc::UnboundedReceiver<ApiRequest>,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: JoinHandle<()>,
+    address: SocketAddr,
+}
+
+impl ApiServer {
+    /**
+     * Bind to the given port on localhost and start serving on the given runtime
+     */
+    pub fn start(
+        runtime: &Runtime,
+        port: u16,
+        token: impl Into<String>,
+        class_cache: ClassCache,
+        repaint_ctx: egui::Context,
+    ) -> std::io::Result<ApiServer> {
+        let token = token.into();
+        if token.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the API token is empty",
+            ));
+        }
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let (request_sender, requests) = mpsc::unbounded_channel();
+        let (updates, _) = broadcast::channel(16);
+        let shared = Arc::new(ApiShared {
+            campaigns: RwLock::new(vec![]),
+            class_cache: RwLock::new(class_cache),
+            updates,
+            requests: request_sender,
+            token,
+            repaint_ctx,
+        });
+
+        let (shutdown, shutdown_receiver) = oneshot::channel::<()>();
+        let router = router(shared.clone());
+        let handle = runtime.spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    error!("Unable to hand API listener to tokio: {err}");
+                    return;
+                }
+            };
+            if let Err(err) = axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_receiver.await;
+                })
+                .await
+            {
+                error!("API server stopped with an error: {err}");
+            }
+        });
+        info!("API server listening on http://{address}");
+
+        Ok(ApiServer {
+            shared,
+            requests,
+            shutdown: Some(shutdown),
+            handle,
+            address,
+        })
+    }
+
+    pub fn get_address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.handle.is_finished()
+    }
+
+    /**
+     * Replace the published campaigns, notifying event listeners if anything changed
+     */
+    pub fn publish_campaigns(&self, campaigns: Vec<CampaignSnapshot>) {
+        let mut current = self.shared.campaigns.write().unwrap();
+        if *current != campaigns {
+            current.clone_from(&campaigns);
+            // No receivers just means nobody is listening for events yet
+            let _ = self.shared.updates.send(campaigns);
+        }
+    }
+
+    pub fn publish_class_cache(&self, class_cache: ClassCache) {
+        *self.shared.class_cache.write().unwrap() = class_cache;
+    }
+
+    /**
+     * Take the next action requested by a client, if any are waiting
+     */
+    pub fn try_next_request(&mut self) -> Option<ApiRequest> {
+        self.requests.try_recv().ok()
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            info!("Shutting down API server on {}", self.address);
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn router(shared: Arc<ApiShared>) -> Router {
+    Router::new()
+        .route("/api/campaigns", get(list_campaigns))
+        .route("/api/campaigns/:id", get(get_campaign))
+        .route("/api/campaigns/:id/actions", post(perform_action))
+        .route("/api/origins", get(list_origins))
+        .route("/api/origins/:name", get(get_origin))
+        .route("/api/classes", get(list_classes))
+        .route("/api/classes/:name", get(get_class))
+        .route("/api/events", get(campaign_events))
+        .with_state(shared)
+}
+
+async fn list_campaigns(State(shared): State<Arc<ApiShared>>) -> Json<Vec<CampaignSnapshot>> {
+    Json(shared.campaigns.read().unwrap().clone())
+}
+
+async fn get_campaign(
+    State(shared): State<Arc<ApiShared>>,
+    Path(id): Path<usize>,
+) -> Result<Json<CampaignSnapshot>, ApiActionError> {
+    shared
+        .campaigns
+        .read()
+        .unwrap()
+        .iter()
+        .find(|campaign| campaign.id == id)
+        .cloned()
+        .map(Json)
+        .ok_or(ApiActionError::UnknownCampaign(id))
+}
+
+async fn list_origins(State(shared): State<Arc<ApiShared>>) -> Json<Vec<Origin>> {
+    Json(
+        shared
+            .class_cache
+            .read()
+            .unwrap()
+            .get_origins()
+            .into_iter()
+            .cloned()
+            .collect(),
+    )
+}
+
+async fn get_origin(
+    State(shared): State<Arc<ApiShared>>,
+    Path(name): Path<String>,
+) -> Result<Json<Origin>, StatusCode> {
+    shared
+        .class_cache
+        .read()
+        .unwrap()
+        .find_origin(&name)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn list_classes(State(shared): State<Arc<ApiShared>>) -> Json<Vec<Class>> {
+    Json(
+        shared
+            .class_cache
+            .read()
+            .unwrap()
+            .get_classes()
+            .into_iter()
+            .cloned()
+            .collect(),
+    )
+}
+
+async fn get_class(
+    State(shared): State<Arc<ApiShared>>,
+    Path(name): Path<String>,
+) -> Result<Json<Class>, StatusCode> {
+    shared
+        .class_cache
+        .read()
+        .unwrap()
+        .find_class(&name)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn campaign_events(
+    State(shared): State<Arc<ApiShared>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let current = shared.campaigns.read().unwrap().clone();
+    let updates = BroadcastStream::new(shared.updates.subscribe())
+        .filter_map(Result::ok)
+        .map(|campaigns| campaigns_event(&campaigns));
+    let events = tokio_stream::once(campaigns_event(&current))
+        .chain(updates)
+        .map(Ok);
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn campaigns_event(campaigns: &[CampaignSnapshot]) -> Event {
+    Event::default()
+        .event("campaigns")
+        .json_data(campaigns)
+        .unwrap_or_default()
+}
+
+async fn perform_action(
+    State(shared): State<Arc<ApiShared>>,
+    Path(id): Path<usize>,
+    headers: HeaderMap,
+    Json(action): Json<ApiAction>,
+) -> Response {
+    if !is_authorized(&headers, &shared.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let (reply, response) = oneshot::channel();
+    if shared
+        .requests
+        .send(ApiRequest {
+            campaign: id,
+            action,
+            reply,
+        })
+        .is_err()
+    {
+        return ApiActionError::Dropped.into_response();
+    }
+    shared.repaint_ctx.request_repaint();
+
+    match response.await {
+        Ok(Ok(campaign)) => Json(campaign).into_response(),
+        Ok(Err(err)) => err.into_response(),
+        Err(_) => ApiActionError::Dropped.into_response(),
+    }
+}
+
+/**
+ * Check for a matching `Authorization: Bearer` header, an empty token never matches
+ */
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    !token.is_empty()
+        && headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|provided| tokens_match(provided.trim(), token))
+}
+
+/**
+ * Compare every byte regardless of where the first difference is, so the time taken doesn't
+ * tell a client how much of the token it guessed
+ */
+fn tokens_match(provided: &str, token: &str) -> bool {
+    provided.len() == token.len()
+        && provided
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (provided, expected)| {
+                difference | (provided ^ expected)
+            })
+            == 0
+}
+
+/**
+ * Generate a new random token for authenticating API clients
+ */
+pub fn generate_token() -> String {
+    std::iter::repeat_with(fastrand::alphanumeric)
+        .take(32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_action_from_json() {
+        let action: ApiAction =
+            serde_json::from_str(r#"{"action": "use_special", "name": "Action Surge"}"#).unwrap();
+        assert_eq!(
+            action,
+            ApiAction::UseSpecial {
+                name: "Action Surge".to_owned()
+            }
+        );
+
+        let action: ApiAction = serde_json::from_str(r#"{"action": "next_turn"}"#).unwrap();
+        assert_eq!(action, ApiAction::NextTurn);
+    }
+
+    #[test]
+    fn test_authorization() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer nope"),
+        );
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secreT"),
+        );
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        assert!(is_authorized(&headers, "secret"));
+    }
+
+    #[test]
+    fn test_empty_token_never_authorizes() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer "));
+        assert!(!is_authorized(&headers, ""));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let started = ApiServer::start(
+            &runtime,
+            0,
+            "",
+            ClassCache::default(),
+            egui::Context::default(),
+        );
+        assert_eq!(
+            started.unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_server_round_trip() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut server = ApiServer::start(
+            &runtime,
+            0,
+            "secret",
+            ClassCache::default(),
+            egui::Context::default(),
+        )
+        .unwrap();
+        let base = format!("http://{}/api", server.get_address());
+        let client = reqwest::Client::new();
+
+        let classes: Vec<Class> = runtime
+            .block_on(async {
+                client
+                    .get(format!("{base}/classes"))
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            })
+            .unwrap();
+        assert!(classes.is_empty());
+
+        let unauthorized = runtime
+            .block_on(
+                client
+                    .post(format!("{base}/campaigns/0/actions"))
+                    .json(&ApiAction::NextTurn)
+                    .send(),
+            )
+            .unwrap();
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let pending = runtime.spawn(
+            client
+                .post(format!("{base}/campaigns/3/actions"))
+                .bearer_auth("secret")
+                .json(&ApiAction::NextTurn)
+                .send(),
+        );
+        let request = loop {
+            if let Some(request) = server.try_next_request() {
+                break request;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(request.get_campaign(), 3);
+        assert_eq!(request.get_action(), &ApiAction::NextTurn);
+        request.respond(Err(ApiActionError::UnknownCampaign(3)));
+
+        let response = runtime.block_on(pending).unwrap().unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(char::is_alphanumeric));
+        assert_ne!(token, generate_token());
+    }
+}
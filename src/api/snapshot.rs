@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * Point in time view of an open campaign, as exposed over the API
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CampaignSnapshot {
+    pub id: usize,
+    pub name: String,
+    pub path: Option<String>,
+    pub level: usize,
+    pub origin: Option<String>,
+    pub classes: Vec<String>,
+    pub game_state: GameStateSnapshot,
+}
+
+/**
+ * Serializable view of the `GameState` of a campaign
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameStateSnapshot {
+    pub battle: u16,
+    pub round: u8,
+    pub turn: String,
+    pub power: i8,
+    pub defense: i8,
+    pub primary_actions_remaining: u8,
+    pub secondary_actions_remaining: u8,
+    pub inspiration_usable: bool,
+    pub any_special_usable: bool,
+    pub primary_actions: Vec<String>,
+    pub secondary_actions: Vec<String>,
+    pub special_actions: Vec<SpecialSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecialSnapshot {
+    pub name: String,
+    pub usable: bool,
+}
@@ -1,9 +1,191 @@
+use crate::export::sheet::CharacterSheet;
+use crate::export::summary::{render_summary, SummaryDetail, SummaryMarkup};
+use crate::export::{export_sheet, ExportError, SheetFormat};
+use crate::google::lint;
+use crate::google::profile::{RulesProfileError, RulesProfiles};
+use crate::google::{self, GetOriginsAndClassesError, RulesSource};
+use crate::model::classes::ClassCache;
+use crate::model::save::{Save, SaveFromFileError};
+use crate::util::LevelNumerals;
+
+use std::collections::HashMap;
 use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use thiserror::Error;
+use tracing::{info, warn};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, args_conflicts_with_subcommands = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     pub campaigns: Vec<OsString>,
 }
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Export a printable character sheet for a campaign
+    Sheet {
+        /// Campaign save file to export
+        campaign: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = SheetFormat::Html)]
+        format: SheetFormat,
+        /// Where to write the sheet, defaults to the campaign path with the format's extension
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Numerals class levels are written in
+        #[arg(long, value_enum, default_value_t = LevelsArg::Arabic)]
+        levels: LevelsArg,
+    },
+    /// Print a summary of one or more campaigns for pasting into chat
+    Summary {
+        /// Campaign save files to summarize, more than one makes a party summary
+        #[arg(required = true)]
+        campaigns: Vec<PathBuf>,
+        #[arg(short, long, value_enum, default_value_t = SummaryDetail::Compact)]
+        detail: SummaryDetail,
+        #[arg(short, long, value_enum, default_value_t = SummaryMarkup::Markdown)]
+        markup: SummaryMarkup,
+        /// Numerals class levels are written in
+        #[arg(long, value_enum, default_value_t = LevelsArg::Arabic)]
+        levels: LevelsArg,
+    },
+    /// Check the rules document for anything the parser would trip over
+    Lint {
+        /// Rules file to check instead of the active profile's document
+        file: Option<PathBuf>,
+    },
+}
+
+/**
+ * Command line spelling of `LevelNumerals`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LevelsArg {
+    Roman,
+    Arabic,
+}
+
+impl From<LevelsArg> for LevelNumerals {
+    fn from(levels: LevelsArg) -> LevelNumerals {
+        match levels {
+            LevelsArg::Roman => LevelNumerals::Roman,
+            LevelsArg::Arabic => LevelNumerals::Arabic,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("Unable to load campaign: {0}")]
+    Load(#[from] SaveFromFileError),
+    #[error("Unable to get the rules: {0}")]
+    Rules(#[from] GetOriginsAndClassesError),
+    #[error("Unable to use the rule set: {0}")]
+    RulesProfile(#[from] RulesProfileError),
+    #[error("Unable to export: {0}")]
+    Export(#[from] ExportError),
+    #[error("Unable to start the async runtime: {0}")]
+    Runtime(#[from] std::io::Error),
+    #[error("Found {0} issues in the rules")]
+    Lint(usize),
+}
+
+pub fn run(command: Command) -> Result<(), CliError> {
+    match command {
+        Command::Sheet {
+            campaign,
+            format,
+            output,
+            levels,
+        } => {
+            let class_cache = load_class_cache()?;
+            let save = load_save(&campaign, &class_cache)?;
+            let output = output.unwrap_or_else(|| campaign.with_extension(format.extension()));
+            let sheet = CharacterSheet::new(&save, &class_cache).with_level_numerals(levels.into());
+            export_sheet(&sheet, format, &output)?;
+            println!("Wrote sheet to {}", output.display());
+            Ok(())
+        }
+        Command::Summary {
+            campaigns,
+            detail,
+            markup,
+            levels,
+        } => {
+            let class_cache = load_class_cache()?;
+            let sheets = campaigns
+                .iter()
+                .map(|campaign| {
+                    load_save(campaign, &class_cache).map(|save| {
+                        CharacterSheet::new(&save, &class_cache).with_level_numerals(levels.into())
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            print!("{}", render_summary(&sheets, detail, markup));
+            Ok(())
+        }
+        Command::Lint { file } => {
+            let source = match file {
+                Some(file) => RulesSource::local(file),
+                None => RulesProfiles::load().get_active().get_source()?,
+            };
+            let issues = runtime()?.block_on(lint::lint(source))?;
+            for issue in &issues {
+                println!("{issue}");
+                if !issue.get_text().is_empty() {
+                    println!("    {}", issue.get_text());
+                }
+            }
+            if issues.is_empty() {
+                println!("No issues found");
+                Ok(())
+            } else {
+                Err(CliError::Lint(issues.len()))
+            }
+        }
+    }
+}
+
+fn runtime() -> Result<tokio::runtime::Runtime, CliError> {
+    Ok(tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?)
+}
+
+/**
+ * Use the rules cached by the GUI when available, fetching them otherwise
+ */
+fn load_class_cache() -> Result<ClassCache, CliError> {
+    if let Some(class_cache) = stored_class_cache() {
+        info!("Using the stored rules cache");
+        return Ok(class_cache);
+    }
+
+    let profiles = RulesProfiles::load();
+    let profile = profiles.get_active();
+    warn!(
+        "No stored rules cache, fetching the {} rules",
+        profile.get_name()
+    );
+    let source = profile.get_source()?;
+    Ok(runtime()?.block_on(google::get_origins_and_classes(source))?)
+}
+
+/**
+ * Read a campaign, moved over to the current names of anything the rules renamed
+ */
+fn load_save(campaign: &Path, class_cache: &ClassCache) -> Result<Save, SaveFromFileError> {
+    let mut save = Save::from_file(campaign)?;
+    save.follow_renames(class_cache);
+    Ok(save)
+}
+
+fn stored_class_cache() -> Option<ClassCache> {
+    let storage_path = eframe::storage_dir("Greed Console")?.join("app.ron");
+    let contents = std::fs::read_to_string(storage_path).ok()?;
+    let storage: HashMap<String, String> = ron::from_str(&contents).ok()?;
+    ron::from_str(storage.get("class_cache")?).ok()
+}
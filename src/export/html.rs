@@ -0,0 +1,122 @@
+use super::sheet::{class_label, CharacterSheet};
+
+use std::fmt::Write;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+static SHEET_STYLE: &str = "
+body { font-family: Helvetica, Arial, sans-serif; max-width: 50em; margin: 2em auto; color: #111; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 2px solid #444; padding-bottom: 0.1em; margin-top: 1.5em; }
+.summary { color: #444; }
+.entry { break-inside: avoid; margin-bottom: 0.8em; }
+.entry h3 { margin: 0; font-size: 1.05em; }
+.source { font-weight: normal; color: #666; font-size: 0.9em; }
+.entry p, .notes p { margin: 0.2em 0; }
+@media print { body { margin: 0; } }
+";
+
+/**
+ * Render a sheet as a self contained HTML page, styles included
+ */
+pub fn render_html(sheet: &CharacterSheet) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{SHEET_STYLE}</style>\n</head>\n<body>\n",
+        escape(&sheet.campaign_name)
+    );
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&sheet.campaign_name));
+    let _ = writeln!(
+        html,
+        "<p class=\"summary\">Level {} &middot; Origin: {}</p>",
+        sheet.level,
+        escape(sheet.origin.as_deref().unwrap_or("None"))
+    );
+
+    html.push_str("<h2>Classes</h2>\n<ul>\n");
+    for (name, level) in &sheet.classes {
+        let _ = writeln!(
+            html,
+            "<li>{}</li>",
+            escape(&class_label(name, *level, sheet.level_numerals))
+        );
+    }
+    html.push_str("</ul>\n");
+
+    for (title, entries) in sheet.sections() {
+        if entries.is_empty() {
+            continue;
+        }
+        let _ = writeln!(html, "<h2>{title}</h2>");
+        for entry in entries {
+            let _ = writeln!(
+                html,
+                "<div class=\"entry\">\n<h3>{} <span class=\"source\">({})</span></h3>",
+                escape(&entry.name),
+                escape(&entry.source)
+            );
+            push_paragraphs(&mut html, &entry.description);
+            html.push_str("</div>\n");
+        }
+    }
+
+    if !sheet.notes.trim().is_empty() {
+        html.push_str("<h2>Notes</h2>\n<div class=\"notes\">\n");
+        push_paragraphs(&mut html, &sheet.notes);
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn push_paragraphs(html: &mut String, text: &str) {
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let _ = writeln!(html, "<p>{}</p>", escape(line));
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<b>Fire & \"Ice\"</b>"),
+            "&lt;b&gt;Fire &amp; &quot;Ice&quot;&lt;/b&gt;"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+pub mod html;
+pub mod pdf;
+pub mod share_code;
+pub mod sheet;
+pub mod summary;
+
+use sheet::CharacterSheet;
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Error when writing export: {0}")]
+    WriteError(#[from] std::io::Error),
+    #[error("Error when rendering PDF: {0}")]
+    PdfError(#[from] printpdf::Error),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SheetFormat {
+    Html,
+    Pdf,
+}
+
+impl SheetFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SheetFormat::Html => "html",
+            SheetFormat::Pdf => "pdf",
+        }
+    }
+
+    pub fn render(self, sheet: &CharacterSheet) -> Result<Vec<u8>, ExportError> {
+        match self {
+            SheetFormat::Html => Ok(html::render_html(sheet).into_bytes()),
+            SheetFormat::Pdf => pdf::render_pdf(sheet),
+        }
+    }
+}
+
+/**
+ * Render the sheet in the given format and write it out to the path
+ */
+pub fn export_sheet(
+    sheet: &CharacterSheet,
+    format: SheetFormat,
+    path: impl AsRef<Path>,
+) -> Result<(), ExportError> {
+    let contents = format.render(sheet)?;
+    let mut file = File::create(path)?;
+    file.write_all(&contents)?;
+    file.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
+    use crate::model::classes::{Class, ClassCache, ClassPassive, ClassUtility};
+    use crate::model::ids::ClassId;
+    use crate::model::save::Save;
+
+    fn test_sheet() -> CharacterSheet {
+        let class_cache = ClassCache::new(
+            vec![],
+            vec![Class::new(
+                "Fighter",
+                Some(1),
+                vec![ClassUtility::new("Athletics", "Climb & jump")],
+                vec![ClassPassive::new("Tough", "More defense")],
+                PrimaryAction::new("Strike", "Hit something"),
+                SecondaryAction::new("Shove", "Push something"),
+                SpecialAction::new("Action Surge", "Two more primaries"),
+                None,
+            )],
+            None,
+        );
+        let mut save = Save::new("Sheet Test");
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Fighter"));
+        *save.get_notebook_mut().page_mut(0).unwrap().body_mut() =
+            "Owes the innkeeper <3> gold".to_owned();
+        CharacterSheet::new(&save, &class_cache)
+    }
+
+    #[test]
+    fn test_sheet_collects_abilities() {
+        let sheet = test_sheet();
+
+        assert_eq!(sheet.level, 1);
+        assert_eq!(sheet.classes, vec![("Fighter".to_owned(), Some(1))]);
+        assert_eq!(sheet.utilities[0].name, "Athletics");
+        assert_eq!(sheet.utilities[0].source, "Fighter");
+        assert_eq!(sheet.special_actions[0].description, "Two more primaries");
+    }
+
+    #[test]
+    fn test_html_export() {
+        let html = String::from_utf8(SheetFormat::Html.render(&test_sheet()).unwrap()).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Fighter (Level 1)"));
+        assert!(html.contains("Climb &amp; jump"));
+        assert!(html.contains("Owes the innkeeper &lt;3&gt; gold"));
+
+        let sheet = test_sheet().with_level_numerals(crate::util::LevelNumerals::Roman);
+        let html = String::from_utf8(SheetFormat::Html.render(&sheet).unwrap()).unwrap();
+        assert!(html.contains("Fighter (Level I)"));
+    }
+
+    #[test]
+    fn test_pdf_export() {
+        let pdf = SheetFormat::Pdf.render(&test_sheet()).unwrap();
+
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
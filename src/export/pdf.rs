@@ -0,0 +1,204 @@
+use super::sheet::{class_label, CharacterSheet};
+use super::ExportError;
+
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const PT_TO_MM: f32 = 0.3528;
+/// Rough average glyph width of Helvetica, as a fraction of the font size
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+
+const TITLE_SIZE: f32 = 20.0;
+const HEADING_SIZE: f32 = 14.0;
+const ENTRY_SIZE: f32 = 11.0;
+const BODY_SIZE: f32 = 10.0;
+
+/**
+ * Render a sheet as an A4 PDF using only the builtin PDF fonts
+ */
+pub fn render_pdf(sheet: &CharacterSheet) -> Result<Vec<u8>, ExportError> {
+    let mut writer = PdfWriter::new(&sheet.campaign_name)?;
+
+    writer.wrapped(&sheet.campaign_name, TITLE_SIZE, true);
+    writer.wrapped(
+        &format!(
+            "Level {} - Origin: {}",
+            sheet.level,
+            sheet.origin.as_deref().unwrap_or("None")
+        ),
+        BODY_SIZE,
+        false,
+    );
+
+    writer.heading("Classes");
+    for (name, level) in &sheet.classes {
+        writer.wrapped(
+            &format!("- {}", class_label(name, *level, sheet.level_numerals)),
+            BODY_SIZE,
+            false,
+        );
+    }
+
+    for (title, entries) in sheet.sections() {
+        if entries.is_empty() {
+            continue;
+        }
+        writer.heading(title);
+        for entry in entries {
+            writer.wrapped(
+                &format!("{} ({})", entry.name, entry.source),
+                ENTRY_SIZE,
+                true,
+            );
+            writer.paragraphs(&entry.description);
+            writer.gap(2.0);
+        }
+    }
+
+    if !sheet.notes.trim().is_empty() {
+        writer.heading("Notes");
+        writer.paragraphs(&sheet.notes);
+    }
+
+    Ok(writer.document.save_to_bytes()?)
+}
+
+struct PdfWriter {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    cursor: f32,
+}
+
+impl PdfWriter {
+    fn new(title: &str) -> Result<PdfWriter, ExportError> {
+        let (document, page, layer) =
+            PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Sheet");
+        let regular = document.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold = document.add_builtin_font(BuiltinFont::HelveticaBold)?;
+        let layer = document.get_page(page).get_layer(layer);
+        Ok(PdfWriter {
+            document,
+            layer,
+            regular,
+            bold,
+            cursor: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.gap(4.0);
+        self.wrapped(text, HEADING_SIZE, true);
+        self.gap(1.0);
+    }
+
+    fn paragraphs(&mut self, text: &str) {
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            self.wrapped(line, BODY_SIZE, false);
+        }
+    }
+
+    fn wrapped(&mut self, text: &str, size: f32, bold: bool) {
+        let line_height = size * PT_TO_MM * 1.3;
+        for line in wrap(text, max_chars_per_line(size)) {
+            self.ensure_space(line_height);
+            self.cursor -= line_height;
+            let font = if bold { &self.bold } else { &self.regular };
+            self.layer
+                .use_text(line, size, Mm(MARGIN), Mm(self.cursor), font);
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.cursor -= height;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.cursor - height < MARGIN {
+            let (page, layer) = self
+                .document
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Sheet");
+            self.layer = self.document.get_page(page).get_layer(layer);
+            self.cursor = PAGE_HEIGHT - MARGIN;
+        }
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn max_chars_per_line(size: f32) -> usize {
+    let usable_width = (PAGE_WIDTH - 2.0 * MARGIN) / PT_TO_MM;
+    (usable_width / (size * AVERAGE_GLYPH_WIDTH)) as usize
+}
+
+/**
+ * Greedy word wrap, splitting words that are longer than a whole line
+ */
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_owned();
+        while word.chars().count() > max_chars {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let split_at = word.char_indices().nth(max_chars).unwrap().0;
+            let rest = word.split_off(split_at);
+            lines.push(word);
+            word = rest;
+        }
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("one two three four", 9),
+            vec!["one two", "three", "four"]
+        );
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert!(wrap("   ", 10).is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::model::classes::ClassCache;
+use crate::model::ids::{ClassId, OriginId};
+use crate::model::sheets::Character;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use thiserror::Error;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+static SHARE_CODE_PREFIX: &str = "GC1-";
+const FLAG_FINGERPRINT: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShareCodeError {
+    #[error("Share codes start with \"{SHARE_CODE_PREFIX}\"")]
+    MissingPrefix,
+    #[error("The share code is not valid base64: {0}")]
+    Encoding(#[from] base64::DecodeError),
+    #[error("The share code is cut short")]
+    Truncated,
+    #[error("The share code does not contain valid names")]
+    InvalidNames,
+}
+
+/**
+ * A build decoded from a share code, not yet checked against any rules
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedBuild {
+    pub origin: Option<OriginId>,
+    pub classes: Vec<ClassId>,
+    pub fingerprint: Option<u64>,
+}
+
+/**
+ * How well a shared build fits the current rules
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedBuildReport {
+    pub rules_changed: bool,
+    pub missing_origin: Option<OriginId>,
+    pub missing_classes: Vec<ClassId>,
+}
+
+impl SharedBuildReport {
+    pub fn has_warnings(&self) -> bool {
+        self.rules_changed || self.missing_origin.is_some() || !self.missing_classes.is_empty()
+    }
+}
+
+impl SharedBuild {
+    pub fn from_character(character: &Character, fingerprint: Option<u64>) -> SharedBuild {
+        SharedBuild {
+            origin: character.get_origin().cloned(),
+            classes: character.get_classes().to_vec(),
+            fingerprint,
+        }
+    }
+
+    /**
+     * Encode as a URL safe code: a flag byte, the optional fingerprint, then
+     * the origin and class identifiers separated by newlines. Codes from before identifiers
+     * hold names, which decode to the same identifiers.
+     */
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![];
+        match self.fingerprint {
+            Some(fingerprint) => {
+                bytes.push(FLAG_FINGERPRINT);
+                bytes.extend_from_slice(&fingerprint.to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+        let names = std::iter::once(self.origin.as_ref().map_or("", OriginId::as_str))
+            .chain(self.classes.iter().map(ClassId::as_str))
+            .collect::<Vec<_>>()
+            .join("\n");
+        bytes.extend_from_slice(names.as_bytes());
+        format!("{SHARE_CODE_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    pub fn decode(code: &str) -> Result<SharedBuild, ShareCodeError> {
+        let encoded = code
+            .trim()
+            .strip_prefix(SHARE_CODE_PREFIX)
+            .ok_or(ShareCodeError::MissingPrefix)?;
+        let bytes = URL_SAFE_NO_PAD.decode(encoded)?;
+        let (flags, rest) = bytes.split_first().ok_or(ShareCodeError::Truncated)?;
+        let (fingerprint, rest) = if flags & FLAG_FINGERPRINT == 0 {
+            (None, rest)
+        } else {
+            if rest.len() < 8 {
+                return Err(ShareCodeError::Truncated);
+            }
+            let (fingerprint, rest) = rest.split_at(8);
+            (
+                Some(u64::from_le_bytes(fingerprint.try_into().unwrap())),
+                rest,
+            )
+        };
+        let names = std::str::from_utf8(rest).map_err(|_| ShareCodeError::InvalidNames)?;
+        let mut names = names.split('\n');
+        let origin = names.next().filter(|origin| !origin.is_empty());
+        Ok(SharedBuild {
+            origin: origin.map(OriginId::from_name),
+            classes: names
+                .filter(|class| !class.is_empty())
+                .map(ClassId::from_name)
+                .collect(),
+            fingerprint,
+        })
+    }
+
+    /**
+     * Compare against the current rules, listing anything that no longer resolves
+     */
+    pub fn check(&self, class_cache: &ClassCache) -> SharedBuildReport {
+        SharedBuildReport {
+            rules_changed: self
+                .fingerprint
+                .is_some_and(|fingerprint| fingerprint != class_cache.fingerprint()),
+            missing_origin: self
+                .origin
+                .clone()
+                .filter(|origin| class_cache.get_origin(origin).is_none()),
+            missing_classes: self
+                .classes
+                .iter()
+                .filter(|class| class_cache.get_class(class).is_none())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn to_character(&self) -> Character {
+        let mut character = Character::default();
+        character.replace_origin(self.origin.clone());
+        for class in &self.classes {
+            character.add_class(class.clone());
+        }
+        character
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::test_support::test_class;
+
+    fn test_cache(class_name: &str) -> ClassCache {
+        ClassCache::new(vec![], vec![test_class(class_name, Some(1), None)], None)
+    }
+
+    fn test_build(fingerprint: Option<u64>) -> SharedBuild {
+        SharedBuild {
+            origin: Some(OriginId::from_name("Elf")),
+            classes: vec![ClassId::from_name("Fighter"), ClassId::from_name("Knight")],
+            fingerprint,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for build in [test_build(None), test_build(Some(u64::MAX))] {
+            let code = build.encode();
+
+            assert!(code.starts_with(SHARE_CODE_PREFIX));
+            assert!(code
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+            assert_eq!(SharedBuild::decode(&code).unwrap(), build);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_without_origin() {
+        let build = SharedBuild {
+            origin: None,
+            classes: vec![],
+            fingerprint: None,
+        };
+
+        assert_eq!(SharedBuild::decode(&build.encode()).unwrap(), build);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            SharedBuild::decode("nope"),
+            Err(ShareCodeError::MissingPrefix)
+        );
+        assert_eq!(
+            SharedBuild::decode(SHARE_CODE_PREFIX),
+            Err(ShareCodeError::Truncated)
+        );
+        assert!(matches!(
+            SharedBuild::decode("GC1-!!!"),
+            Err(ShareCodeError::Encoding(_))
+        ));
+        assert_eq!(
+            SharedBuild::decode(&format!("{SHARE_CODE_PREFIX}AQID")),
+            Err(ShareCodeError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_check_against_changed_rules() {
+        let old_rules = test_cache("Fighter");
+        let new_rules = test_cache("Warrior");
+        let build = test_build(Some(old_rules.fingerprint()));
+
+        let report = build.check(&old_rules);
+        assert!(!report.rules_changed);
+        assert_eq!(report.missing_classes, vec![ClassId::from_name("Knight")]);
+
+        let report = build.check(&new_rules);
+        assert!(report.rules_changed);
+        assert_eq!(report.missing_origin, Some(OriginId::from_name("Elf")));
+        assert_eq!(
+            report.missing_classes,
+            vec![ClassId::from_name("Fighter"), ClassId::from_name("Knight")]
+        );
+    }
+
+    #[test]
+    fn test_codes_with_names_decode_to_ids() {
+        let bytes = [&[0][..], b"Elf\nFire Mage"].concat();
+        let code = format!("{SHARE_CODE_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes));
+
+        assert_eq!(
+            SharedBuild::decode(&code).unwrap(),
+            SharedBuild {
+                origin: Some(OriginId::from_name("elf")),
+                classes: vec![ClassId::from_name("fire-mage")],
+                fingerprint: None,
+            }
+        );
+    }
+}
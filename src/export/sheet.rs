@@ -0,0 +1,157 @@
+use crate::model::classes::{Class, ClassCache, Origin};
+use crate::model::save::Save;
+use crate::util::LevelNumerals;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * A single ability on a sheet, along with the origin or class granting it
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetEntry {
+    pub name: String,
+    pub description: String,
+    pub source: String,
+}
+
+/**
+ * Everything printed on a character sheet, resolved against the current rules
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharacterSheet {
+    pub campaign_name: String,
+    pub level: usize,
+    pub origin: Option<String>,
+    pub classes: Vec<(String, Option<usize>)>,
+    pub level_numerals: LevelNumerals,
+    pub utilities: Vec<SheetEntry>,
+    pub passives: Vec<SheetEntry>,
+    pub primary_actions: Vec<SheetEntry>,
+    pub secondary_actions: Vec<SheetEntry>,
+    pub special_actions: Vec<SheetEntry>,
+    pub notes: String,
+}
+
+impl CharacterSheet {
+    pub fn new(save: &Save, class_cache: &ClassCache) -> CharacterSheet {
+        let character = save.get_character();
+        let sources = character.get_ability_sources(class_cache);
+
+        let utilities = sources
+            .iter()
+            .flat_map(|class| {
+                class.get_utilities().iter().map(|utility| {
+                    SheetEntry::new(utility.get_name(), utility.get_description(), class)
+                })
+            })
+            .collect();
+        let passives = sources
+            .iter()
+            .flat_map(|class| {
+                class.get_passives().iter().map(|passive| {
+                    SheetEntry::new(passive.get_name(), passive.get_description(), class)
+                })
+            })
+            .collect();
+        let primary_actions = sources
+            .iter()
+            .map(|class| {
+                let action = class.get_primary_action();
+                SheetEntry::new(action.get_name(), action.get_description(), class)
+            })
+            .collect();
+        let secondary_actions = sources
+            .iter()
+            .map(|class| {
+                let action = class.get_secondary_action();
+                SheetEntry::new(action.get_name(), action.get_description(), class)
+            })
+            .collect();
+        let special_actions = sources
+            .iter()
+            .map(|class| {
+                let action = class.get_special_action();
+                SheetEntry::new(action.get_name(), action.get_description(), class)
+            })
+            .collect();
+
+        CharacterSheet {
+            campaign_name: save.get_campaign_name(),
+            level: character.get_level(class_cache),
+            origin: character.get_origin().map(|origin_id| {
+                class_cache
+                    .get_origin(origin_id)
+                    .map_or_else(|| origin_id.to_string(), Origin::get_name)
+            }),
+            classes: class_cache
+                .map_to_concrete_classes(character.get_classes())
+                .iter()
+                .map(|class| (class.get_name(), *class.get_level()))
+                .collect(),
+            level_numerals: LevelNumerals::default(),
+            utilities,
+            passives,
+            primary_actions,
+            secondary_actions,
+            special_actions,
+            notes: save.get_notebook().to_text(),
+        }
+    }
+
+    /**
+     * Write class levels in other numerals than the default Arabic ones
+     */
+    pub fn with_level_numerals(mut self, level_numerals: LevelNumerals) -> CharacterSheet {
+        self.level_numerals = level_numerals;
+        self
+    }
+
+    /**
+     * Ability sections in the order they are printed, empty sections included
+     */
+    pub fn sections(&self) -> [(&'static str, &[SheetEntry]); 5] {
+        [
+            ("Utilities", &self.utilities),
+            ("Passives", &self.passives),
+            ("Primary Actions", &self.primary_actions),
+            ("Secondary Actions", &self.secondary_actions),
+            ("Special Actions", &self.special_actions),
+        ]
+    }
+}
+
+impl SheetEntry {
+    fn new(name: String, description: String, source: &Class) -> SheetEntry {
+        SheetEntry {
+            name,
+            description,
+            source: source.get_name(),
+        }
+    }
+}
+
+/**
+ * Label for a class with its level, e.g. "Fighter (Level 2)" or "Fighter (Level II)"
+ */
+pub fn class_label(name: &str, level: Option<usize>, level_numerals: LevelNumerals) -> String {
+    match level {
+        Some(level) => format!("{name} (Level {})", level_numerals.format(level)),
+        None => name.to_owned(),
+    }
+}
@@ -0,0 +1,434 @@
+use super::sheet::{class_label, CharacterSheet};
+use crate::model::classes::{Class, ClassCache};
+use crate::model::ids::OriginId;
+use crate::model::sheets::Character;
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+use std::fmt::{self, Write};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * How much of the character to put in a summary
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, ValueEnum)]
+pub enum SummaryDetail {
+    /// Only the origin and class names
+    #[default]
+    Compact,
+    /// Every ability along with its description
+    Full,
+}
+
+/**
+ * Markup used when rendering a summary
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, ValueEnum)]
+pub enum SummaryMarkup {
+    #[default]
+    Markdown,
+    PlainText,
+}
+
+/**
+ * A character read back out of a summary
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedCharacter {
+    pub name: String,
+    pub character: Character,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ImportProblem {
+    #[error("No characters found, expected a line starting with \"## \" or \"== \"")]
+    NoCharacters,
+    #[error("{character}: unknown origin \"{origin}\"")]
+    UnknownOrigin { character: String, origin: String },
+    #[error("{character}: unknown class \"{class}\"")]
+    UnknownClass { character: String, class: String },
+    #[error("{character}: class \"{class}\" is listed more than once")]
+    DuplicateClass { character: String, class: String },
+    #[error("{character}: class \"{class}\" is not available, its prerequisites are not met")]
+    UnavailableClass { character: String, class: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct SummaryImportError {
+    pub problems: Vec<ImportProblem>,
+}
+
+impl fmt::Display for SummaryImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems = self
+            .problems
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{}", problems.join("\n"))
+    }
+}
+
+/**
+ * Render one character, or a party when given several sheets
+ */
+pub fn render_summary(
+    sheets: &[CharacterSheet],
+    detail: SummaryDetail,
+    markup: SummaryMarkup,
+) -> String {
+    let mut summary = String::new();
+    if sheets.len() > 1 {
+        match markup {
+            SummaryMarkup::Markdown => summary.push_str("# Party\n\n"),
+            SummaryMarkup::PlainText => summary.push_str("Party\n\n"),
+        }
+    }
+    for sheet in sheets {
+        render_character(&mut summary, sheet, detail, markup);
+        summary.push('\n');
+    }
+    summary.trim_end().to_owned() + "\n"
+}
+
+fn render_character(
+    summary: &mut String,
+    sheet: &CharacterSheet,
+    detail: SummaryDetail,
+    markup: SummaryMarkup,
+) {
+    let classes = sheet
+        .classes
+        .iter()
+        .map(|(name, level)| class_label(name, *level, sheet.level_numerals))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let origin = sheet.origin.as_deref().unwrap_or("None");
+    match markup {
+        SummaryMarkup::Markdown => {
+            let _ = writeln!(summary, "## {}", sheet.campaign_name);
+            let _ = writeln!(summary, "- **Origin:** {origin}");
+            let _ = writeln!(summary, "- **Level:** {}", sheet.level);
+            let _ = writeln!(summary, "- **Classes:** {classes}");
+        }
+        SummaryMarkup::PlainText => {
+            let _ = writeln!(summary, "== {} ==", sheet.campaign_name);
+            let _ = writeln!(summary, "Origin: {origin}");
+            let _ = writeln!(summary, "Level: {}", sheet.level);
+            let _ = writeln!(summary, "Classes: {classes}");
+        }
+    }
+
+    if detail == SummaryDetail::Compact {
+        return;
+    }
+
+    for (title, entries) in sheet.sections() {
+        if entries.is_empty() {
+            continue;
+        }
+        match markup {
+            SummaryMarkup::Markdown => {
+                let _ = writeln!(summary, "\n### {title}");
+            }
+            SummaryMarkup::PlainText => {
+                let _ = writeln!(summary, "\n-- {title} --");
+            }
+        }
+        for entry in entries {
+            let description = entry.description.lines().collect::<Vec<_>>().join(" ");
+            match markup {
+                SummaryMarkup::Markdown => {
+                    let _ = writeln!(
+                        summary,
+                        "- **{}** ({}): {description}",
+                        entry.name, entry.source
+                    );
+                }
+                SummaryMarkup::PlainText => {
+                    let _ = writeln!(
+                        summary,
+                        "* {} ({}): {description}",
+                        entry.name, entry.source
+                    );
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Parse characters back out of a summary in either markup, checking them against the rules
+ */
+pub fn parse_summary(
+    summary: &str,
+    class_cache: &ClassCache,
+) -> Result<Vec<ImportedCharacter>, SummaryImportError> {
+    let mut drafts: Vec<(String, Option<String>, Vec<String>)> = vec![];
+    for line in summary.lines().map(str::trim) {
+        if let Some(name) = character_heading(line) {
+            drafts.push((name.to_owned(), None, vec![]));
+            continue;
+        }
+        let Some((_, origin, classes)) = drafts.last_mut() else {
+            continue;
+        };
+        let field = line.trim_start_matches(['-', '*', ' ']).replace("**", "");
+        if let Some(value) = field.strip_prefix("Origin:") {
+            let value = value.trim();
+            *origin = (value != "None" && !value.is_empty()).then(|| value.to_owned());
+        } else if let Some(value) = field.strip_prefix("Classes:") {
+            *classes = value
+                .split(',')
+                .map(strip_level)
+                .filter(|class| !class.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+    }
+
+    if drafts.is_empty() {
+        return Err(SummaryImportError {
+            problems: vec![ImportProblem::NoCharacters],
+        });
+    }
+
+    let mut problems = vec![];
+    let characters = drafts
+        .into_iter()
+        .map(|(name, origin, classes)| {
+            let character = build_character(&name, origin, &classes, class_cache, &mut problems);
+            ImportedCharacter { name, character }
+        })
+        .collect();
+
+    if problems.is_empty() {
+        Ok(characters)
+    } else {
+        Err(SummaryImportError { problems })
+    }
+}
+
+fn character_heading(line: &str) -> Option<&str> {
+    if let Some(name) = line.strip_prefix("## ") {
+        Some(name.trim())
+    } else {
+        line.strip_prefix("== ")
+            .map(|name| name.trim_end_matches('=').trim())
+    }
+}
+
+/**
+ * Strip a trailing "(Level N)" from a class label
+ */
+fn strip_level(label: &str) -> &str {
+    let label = label.trim();
+    match label.rfind(" (Level ") {
+        Some(index) if label.ends_with(')') => label[..index].trim(),
+        _ => label,
+    }
+}
+
+fn build_character(
+    name: &str,
+    origin: Option<String>,
+    classes: &[String],
+    class_cache: &ClassCache,
+    problems: &mut Vec<ImportProblem>,
+) -> Character {
+    let mut character = Character::default();
+    if let Some(origin) = origin {
+        if class_cache.find_origin(&origin).is_none() {
+            problems.push(ImportProblem::UnknownOrigin {
+                character: name.to_owned(),
+                origin: origin.clone(),
+            });
+        }
+        character.replace_origin(Some(OriginId::from_name(&origin)));
+    }
+
+    let mut pending: Vec<Class> = vec![];
+    for class_name in classes {
+        match class_cache.find_class(class_name) {
+            Some(found) if pending.iter().any(|class| class.get_id() == found.get_id()) => {
+                problems.push(ImportProblem::DuplicateClass {
+                    character: name.to_owned(),
+                    class: class_name.clone(),
+                });
+            }
+            Some(class) => pending.push(class.clone()),
+            None => problems.push(ImportProblem::UnknownClass {
+                character: name.to_owned(),
+                class: class_name.clone(),
+            }),
+        }
+    }
+
+    // Classes may be listed in any order, so keep adding whatever has its prerequisites met
+    let mut taken: Vec<Class> = vec![];
+    while let Some(position) = pending
+        .iter()
+        .position(|class| class.get_class_available(&taken))
+    {
+        taken.push(pending.remove(position));
+    }
+    for class in pending {
+        problems.push(ImportProblem::UnavailableClass {
+            character: name.to_owned(),
+            class: class.get_name(),
+        });
+    }
+
+    for class in &taken {
+        character.add_class(class.get_id());
+    }
+    character
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::classes::{Origin, OriginRules};
+    use crate::model::ids::ClassId;
+    use crate::model::save::Save;
+    use crate::model::test_support::test_class;
+
+    fn test_cache() -> ClassCache {
+        ClassCache::new(
+            vec![Origin::without_abilities("Elf", OriginRules::default())],
+            vec![
+                test_class("Fighter", Some(1), None),
+                test_class("Knight", Some(2), Some("Fighter")),
+            ],
+            None,
+        )
+    }
+
+    fn test_sheet(cache: &ClassCache) -> CharacterSheet {
+        let mut save = Save::new("Aria");
+        save.get_character_mut()
+            .replace_origin(Some(OriginId::from_name("Elf")));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Fighter"));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Knight"));
+        CharacterSheet::new(&save, cache)
+    }
+
+    #[test]
+    fn test_compact_markdown() {
+        let cache = test_cache();
+        let summary = render_summary(
+            &[test_sheet(&cache)],
+            SummaryDetail::Compact,
+            SummaryMarkup::Markdown,
+        );
+
+        assert_eq!(
+            summary,
+            "## Aria\n- **Origin:** Elf\n- **Level:** 2\n- **Classes:** Fighter (Level 1), Knight (Level 2)\n"
+        );
+    }
+
+    #[test]
+    fn test_full_includes_descriptions() {
+        let cache = test_cache();
+        let summary = render_summary(
+            &[test_sheet(&cache)],
+            SummaryDetail::Full,
+            SummaryMarkup::PlainText,
+        );
+
+        assert!(summary.contains("-- Special Actions --"));
+        assert!(summary.contains("* Knight Special (Knight): Does a big thing"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cache = test_cache();
+        let sheets = [test_sheet(&cache), test_sheet(&cache)];
+        for markup in [SummaryMarkup::Markdown, SummaryMarkup::PlainText] {
+            for detail in [SummaryDetail::Compact, SummaryDetail::Full] {
+                let summary = render_summary(&sheets, detail, markup);
+                let imported = parse_summary(&summary, &cache).unwrap();
+
+                assert_eq!(imported.len(), 2);
+                assert_eq!(imported[0].name, "Aria");
+                assert_eq!(
+                    imported[0].character.get_origin(),
+                    Some(&OriginId::from_name("Elf"))
+                );
+                assert_eq!(
+                    imported[0].character.get_classes(),
+                    [ClassId::from_name("Fighter"), ClassId::from_name("Knight")]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_any_order() {
+        let imported = parse_summary("## Bo\nClasses: Knight, Fighter", &test_cache()).unwrap();
+
+        assert_eq!(
+            imported[0].character.get_classes(),
+            [ClassId::from_name("Fighter"), ClassId::from_name("Knight")]
+        );
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let error = parse_summary(
+            "## Bo\nOrigin: Dwarf\nClasses: Knight, Wizard",
+            &test_cache(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![
+                ImportProblem::UnknownOrigin {
+                    character: "Bo".to_owned(),
+                    origin: "Dwarf".to_owned()
+                },
+                ImportProblem::UnknownClass {
+                    character: "Bo".to_owned(),
+                    class: "Wizard".to_owned()
+                },
+                ImportProblem::UnavailableClass {
+                    character: "Bo".to_owned(),
+                    class: "Knight".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_nothing() {
+        assert_eq!(
+            parse_summary("just some chat", &test_cache())
+                .unwrap_err()
+                .problems,
+            vec![ImportProblem::NoCharacters]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::{Cursor, Read};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use thiserror::Error;
+use zip::ZipArchive;
+
+use super::{Block, BlockKind, BlockText};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+static DOCUMENT_PATH: &str = "word/document.xml";
+
+#[derive(Debug, Error)]
+pub enum DocxError {
+    #[error("Not a DOCX archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Failed to read the document: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Malformed document XML: {0}")]
+    Xml(#[from] quick_xml::Error),
+}
+
+fn get_value(element: &BytesStart) -> Result<Option<String>, DocxError> {
+    for attribute in element.attributes().flatten() {
+        if attribute.key.local_name().as_ref() == b"val" {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/**
+ * Whether a toggle property like `<w:b/>` is on, as it can be turned off with `w:val="0"`
+ */
+fn is_on(element: &BytesStart) -> Result<bool, DocxError> {
+    Ok(get_value(element)?.is_none_or(|value| value != "0" && value != "false"))
+}
+
+/**
+ * The heading level of a paragraph style like `Heading2` or `Title`
+ */
+fn heading_level(style: &str) -> Option<usize> {
+    let style = style.to_lowercase().replace(' ', "");
+    if style == "title" {
+        return Some(1);
+    }
+    style
+        .strip_prefix("heading")?
+        .parse()
+        .ok()
+        .filter(|level| (1..=9).contains(level))
+}
+
+/**
+ * Split a Word document into paragraphs, taking headings from paragraph styles
+ */
+pub fn blocks(content: &[u8]) -> Result<Vec<Block>, DocxError> {
+    let mut xml = String::new();
+    ZipArchive::new(Cursor::new(content))?
+        .by_name(DOCUMENT_PATH)?
+        .read_to_string(&mut xml)?;
+    let mut reader = Reader::from_str(&xml);
+
+    let mut blocks = vec![];
+    let mut text = BlockText::default();
+    let mut kind = BlockKind::Paragraph;
+    let (mut bold, mut italic) = (false, false);
+    let mut in_text = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) if element.local_name().as_ref() == b"t" => in_text = true,
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"p" => kind = BlockKind::Paragraph,
+                b"pStyle" => {
+                    if let Some(level) = get_value(&element)?.as_deref().and_then(heading_level) {
+                        kind = BlockKind::Heading(level);
+                    }
+                }
+                b"numPr" if kind == BlockKind::Paragraph => kind = BlockKind::ListItem,
+                b"r" => (bold, italic) = (false, false),
+                b"b" => bold = is_on(&element)?,
+                b"i" => italic = is_on(&element)?,
+                b"tab" | b"br" => text.push(" ", bold, italic),
+                _ => {}
+            },
+            Event::Text(content) if in_text => text.push(&content.unescape()?, bold, italic),
+            Event::End(element) => match element.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let block = text.finish(kind);
+                    if !block.text.is_empty() {
+                        blocks.push(block);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(blocks)
+}
@@ -0,0 +1,354 @@
+use std::collections::HashSet;
+
+use super::{Block, BlockKind, BlockText};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Elements that end the paragraph before them and start a new one
+static BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "li",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "div",
+    "ul",
+    "ol",
+    "table",
+    "tr",
+    "td",
+    "th",
+    "blockquote",
+    "body",
+];
+/// Elements that never have a closing tag
+static VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "hr", "img", "input", "link", "meta", "wbr",
+];
+/// Elements whose contents are never shown
+static RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "title"];
+
+#[derive(Debug)]
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attributes: Vec<(String, &'a str)>,
+}
+
+impl<'a> Tag<'a> {
+    fn parse(source: &'a str) -> Tag<'a> {
+        let closing = source.starts_with('/');
+        let self_closing = source.ends_with('/');
+        let source = source.trim_start_matches('/').trim_end_matches('/');
+        let name_end = source
+            .find(|character: char| character.is_whitespace())
+            .unwrap_or(source.len());
+        let name = source[..name_end].to_lowercase();
+
+        let mut attributes = vec![];
+        let mut rest = source[name_end..].trim_start();
+        while !rest.is_empty() {
+            let key_end = rest
+                .find(|character: char| character == '=' || character.is_whitespace())
+                .unwrap_or(rest.len());
+            let key = rest[..key_end].to_lowercase();
+            rest = rest[key_end..].trim_start();
+            let value = if let Some(value) = rest.strip_prefix('=') {
+                let value = value.trim_start();
+                let (value, remaining) = if let Some(quote @ ('"' | '\'')) = value.chars().next() {
+                    let value = &value[1..];
+                    let end = value.find(quote).unwrap_or(value.len());
+                    (&value[..end], value.get(end + 1..).unwrap_or_default())
+                } else {
+                    let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                    value.split_at(end)
+                };
+                rest = remaining.trim_start();
+                value
+            } else {
+                ""
+            };
+            attributes.push((key, value));
+        }
+
+        Tag {
+            name,
+            closing,
+            self_closing,
+            attributes,
+        }
+    }
+
+    fn get_attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/**
+ * The CSS classes that make text bold or italic, as Google Docs styles its exports with them
+ */
+#[derive(Debug, Default)]
+struct Styles {
+    bold: HashSet<String>,
+    italic: HashSet<String>,
+}
+
+impl Styles {
+    fn add_stylesheet(&mut self, css: &str) {
+        for rule in css.split('}') {
+            let Some((selectors, declarations)) = rule.split_once('{') else {
+                continue;
+            };
+            let classes = selectors
+                .split(',')
+                .filter_map(|selector| selector.trim().strip_prefix('.'))
+                .filter(|class| {
+                    class
+                        .chars()
+                        .all(|character| character.is_alphanumeric() || "-_".contains(character))
+                });
+            let bold = is_bold(declarations);
+            let italic = is_italic(declarations);
+            for class in classes {
+                if bold {
+                    self.bold.insert(class.to_owned());
+                }
+                if italic {
+                    self.italic.insert(class.to_owned());
+                }
+            }
+        }
+    }
+
+    fn emphasis(&self, tag: &Tag) -> (bool, bool) {
+        let classes = tag
+            .get_attribute("class")
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>();
+        let style = tag.get_attribute("style").unwrap_or_default();
+        let bold = matches!(tag.name.as_str(), "b" | "strong")
+            || classes.iter().any(|class| self.bold.contains(*class))
+            || is_bold(style);
+        let italic = matches!(tag.name.as_str(), "i" | "em")
+            || classes.iter().any(|class| self.italic.contains(*class))
+            || is_italic(style);
+        (bold, italic)
+    }
+}
+
+fn get_declaration(declarations: &str, property: &str) -> Option<String> {
+    declarations.split(';').find_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        (name.trim().eq_ignore_ascii_case(property)).then(|| value.trim().to_lowercase())
+    })
+}
+
+fn is_bold(declarations: &str) -> bool {
+    get_declaration(declarations, "font-weight").is_some_and(|weight| {
+        weight == "bold"
+            || weight == "bolder"
+            || weight.parse::<u32>().is_ok_and(|weight| weight >= 600)
+    })
+}
+
+fn is_italic(declarations: &str) -> bool {
+    get_declaration(declarations, "font-style")
+        .is_some_and(|style| style == "italic" || style == "oblique")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..=end]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        });
+        if let (Some(entity), Some(character)) = (entity, character) {
+            decoded.push(character);
+            rest = &rest[entity.len() + 2..];
+        } else {
+            decoded.push('&');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/**
+ * Find the end of a raw text element's contents, ignoring case in its closing tag
+ */
+fn find_closing_tag(html: &str, name: &str) -> usize {
+    let closing = format!("</{name}");
+    html.to_ascii_lowercase()
+        .find(&closing)
+        .unwrap_or(html.len())
+}
+
+/**
+ * Split an HTML export into paragraphs. Tags are read leniently, as exports
+ * aren't always well formed.
+ */
+pub fn blocks(html: &str) -> Vec<Block> {
+    let mut styles = Styles::default();
+    let mut blocks = vec![];
+    let mut text = BlockText::default();
+    let mut kind = BlockKind::Paragraph;
+    // Open elements with the emphasis they give their contents
+    let mut open: Vec<(String, bool, bool)> = vec![];
+    let mut finish = |text: &mut BlockText, kind: BlockKind| {
+        let block = text.finish(kind);
+        if !block.text.is_empty() {
+            blocks.push(block);
+        }
+    };
+
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let bold = open.iter().any(|(_, bold, _)| *bold);
+        let italic = open.iter().any(|(_, _, italic)| *italic);
+        text.push(&decode_entities(&rest[..start]), bold, italic);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment
+                .find("-->")
+                .map_or("", |end| &comment[end + "-->".len()..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = Tag::parse(rest[1..end].trim());
+        rest = &rest[end + 1..];
+        if tag.name.starts_with('!') {
+            // A doctype or other declaration
+            continue;
+        }
+
+        if RAW_TEXT_ELEMENTS.contains(&tag.name.as_str()) {
+            if tag.closing {
+                continue;
+            }
+            let end = find_closing_tag(rest, &tag.name);
+            if tag.name == "style" {
+                styles.add_stylesheet(&rest[..end]);
+            }
+            rest = &rest[end..];
+            continue;
+        }
+        if tag.name == "br" {
+            text.push(" ", false, false);
+            continue;
+        }
+        if BLOCK_ELEMENTS.contains(&tag.name.as_str()) {
+            finish(&mut text, kind);
+            kind = if tag.closing {
+                BlockKind::Paragraph
+            } else if tag.name == "li" {
+                BlockKind::ListItem
+            } else if let Some(level) = tag
+                .name
+                .strip_prefix('h')
+                .and_then(|level| level.parse().ok())
+            {
+                BlockKind::Heading(level)
+            } else {
+                kind
+            };
+        }
+        if tag.closing {
+            if let Some(index) = open.iter().rposition(|(name, _, _)| *name == tag.name) {
+                open.truncate(index);
+            }
+        } else if !tag.self_closing && !VOID_ELEMENTS.contains(&tag.name.as_str()) {
+            let (bold, italic) = styles.emphasis(&tag);
+            open.push((tag.name, bold, italic));
+        }
+    }
+    text.push(&decode_entities(rest), false, false);
+    finish(&mut text, kind);
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let html = r#"<!DOCTYPE html><html><head><meta charset="utf-8">
+            <style>.c1{font-weight:700}.c2{font-style:italic;color:#000}</style></head>
+            <body><h2 class="c0">Fighter &amp; Friends</h2>
+            <p><span class="c1">Primary</span></p>
+            <ul><li><span>Strike</span></li></ul>
+            <p><span>Hit something</span> <span style="font-weight: bold">hard</span>.</p>
+            <p class=c2>Flavour&nbsp;text</p></body></html>"#;
+
+        assert_eq!(
+            blocks(html)
+                .into_iter()
+                .map(|block| (block.kind, block.text, block.bold, block.italic))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    BlockKind::Heading(2),
+                    "Fighter & Friends".to_owned(),
+                    false,
+                    false
+                ),
+                (BlockKind::Paragraph, "Primary".to_owned(), true, false),
+                (BlockKind::ListItem, "Strike".to_owned(), false, false),
+                (
+                    BlockKind::Paragraph,
+                    "Hit something hard.".to_owned(),
+                    false,
+                    false
+                ),
+                (BlockKind::Paragraph, "Flavour text".to_owned(), false, true),
+            ]
+        );
+    }
+}
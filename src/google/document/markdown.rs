@@ -0,0 +1,194 @@
+use super::{Block, BlockKind, BlockText};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line
+        .chars()
+        .take_while(|character| *character == '#')
+        .count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6)
+        .contains(&level)
+        .then(|| (level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if let Some(text) = ["* ", "- ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+    {
+        return Some(text);
+    }
+    let number_end = line.find(|character: char| !character.is_ascii_digit())?;
+    (number_end > 0)
+        .then(|| line[number_end..].strip_prefix(". "))
+        .flatten()
+}
+
+/**
+ * A line of only `-`, `=`, `*` or `_`, which is either a rule or underlines a heading
+ */
+fn rule(line: &str) -> Option<char> {
+    let line = line.trim();
+    let character = line.chars().next()?;
+    ("-=*_".contains(character)
+        && line
+            .chars()
+            .all(|next| next == character || next.is_whitespace()))
+    .then_some(character)
+}
+
+/**
+ * Add a line of text, dropping the markers around bold and italic text
+ */
+fn push_inline(text: &mut BlockText, line: &str, bold: &mut bool, italic: &mut bool) {
+    let characters = line.chars().collect::<Vec<_>>();
+    let mut run = String::new();
+    let mut index = 0;
+    while let Some(&character) = characters.get(index) {
+        let previous = index.checked_sub(1).and_then(|index| characters.get(index));
+        if character == '\\'
+            && characters
+                .get(index + 1)
+                .is_some_and(char::is_ascii_punctuation)
+        {
+            run.push(characters[index + 1]);
+            index += 2;
+            continue;
+        }
+        if character == '*' || character == '_' {
+            let length = characters[index..]
+                .iter()
+                .take_while(|next| **next == character)
+                .count();
+            let next = characters.get(index + length);
+            let is_spaced = |neighbour: Option<&char>| neighbour.is_none_or(|c| c.is_whitespace());
+            let is_word = |neighbour: Option<&char>| neighbour.is_some_and(|c| c.is_alphanumeric());
+            // A lone `*` between spaces or `_` inside a word is just text
+            let is_text = (is_spaced(previous) && is_spaced(next))
+                || (character == '_' && is_word(previous) && is_word(next));
+            if !is_text && length <= 3 {
+                text.push(&run, *bold, *italic);
+                run.clear();
+                if length >= 2 {
+                    *bold = !*bold;
+                }
+                if length != 2 {
+                    *italic = !*italic;
+                }
+                index += length;
+                continue;
+            }
+        }
+        run.push(character);
+        index += 1;
+    }
+    text.push(&run, *bold, *italic);
+    text.push(" ", *bold, *italic);
+}
+
+/**
+ * Split a Markdown export into paragraphs
+ */
+pub fn blocks(markdown: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut text = BlockText::default();
+    let mut kind = BlockKind::Paragraph;
+    let mut lines = 0;
+    let (mut bold, mut italic) = (false, false);
+    let mut finish = |text: &mut BlockText, kind: BlockKind, lines: &mut usize| {
+        let block = text.finish(kind);
+        if !block.text.is_empty() {
+            blocks.push(block);
+        }
+        *lines = 0;
+    };
+
+    for line in markdown.lines() {
+        if line.trim().is_empty() {
+            finish(&mut text, kind, &mut lines);
+            kind = BlockKind::Paragraph;
+        } else if let Some((level, heading)) = heading(line) {
+            finish(&mut text, kind, &mut lines);
+            push_inline(&mut text, heading, &mut bold, &mut italic);
+            finish(&mut text, BlockKind::Heading(level), &mut lines);
+            kind = BlockKind::Paragraph;
+        } else if let Some(underline) = rule(line) {
+            // A paragraph underlined with `=` or `-` is a heading
+            let kind = match underline {
+                '=' if lines > 0 => BlockKind::Heading(1),
+                '-' if lines > 0 && kind == BlockKind::Paragraph => BlockKind::Heading(2),
+                _ => kind,
+            };
+            finish(&mut text, kind, &mut lines);
+        } else if let Some(item) = list_item(line) {
+            finish(&mut text, kind, &mut lines);
+            kind = BlockKind::ListItem;
+            push_inline(&mut text, item, &mut bold, &mut italic);
+            lines += 1;
+        } else {
+            push_inline(&mut text, line.trim(), &mut bold, &mut italic);
+            lines += 1;
+        }
+        if lines == 0 {
+            // Emphasis left open doesn't carry over into the next paragraph
+            (bold, italic) = (false, false);
+        }
+    }
+    finish(&mut text, kind, &mut lines);
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let markdown = "# Fighter (I)\n\n**Primary**\n\n- Strike\n\nHit some\\_thing *very*\nhard.\n\n_Flavour text_\n\nPassives\n---\n1. snake_case\n";
+
+        assert_eq!(
+            blocks(markdown)
+                .into_iter()
+                .map(|block| (block.kind, block.text, block.bold, block.italic))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    BlockKind::Heading(1),
+                    "Fighter (I)".to_owned(),
+                    false,
+                    false
+                ),
+                (BlockKind::Paragraph, "Primary".to_owned(), true, false),
+                (BlockKind::ListItem, "Strike".to_owned(), false, false),
+                (
+                    BlockKind::Paragraph,
+                    "Hit some_thing very hard.".to_owned(),
+                    false,
+                    false
+                ),
+                (BlockKind::Paragraph, "Flavour text".to_owned(), false, true),
+                (BlockKind::Heading(2), "Passives".to_owned(), false, false),
+                (BlockKind::ListItem, "snake_case".to_owned(), false, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,429 @@
+pub mod docx;
+pub mod html;
+pub mod markdown;
+
+use std::path::Path;
+
+use super::{
+    draft_class, origin_without_abilities, parse_class_heading, parse_subclasses,
+    GetOriginsAndClassesError, ParsedRules,
+};
+use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
+use crate::model::classes::{Class, ClassPassive, ClassUtility, Origin, OriginActions};
+use crate::model::glossary::Glossary;
+use crate::model::idea_bank::DraftClass;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * The formats a rules document can be read from
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulesFormat {
+    PlainText,
+    Html,
+    Markdown,
+    Docx,
+}
+
+impl RulesFormat {
+    pub const fn get_mime_type(self) -> &'static str {
+        match self {
+            RulesFormat::PlainText => "text/plain",
+            RulesFormat::Html => "text/html",
+            RulesFormat::Markdown => "text/markdown",
+            RulesFormat::Docx => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+        }
+    }
+
+    pub fn from_mime_type(mime_type: &str) -> Option<RulesFormat> {
+        [
+            RulesFormat::PlainText,
+            RulesFormat::Html,
+            RulesFormat::Markdown,
+            RulesFormat::Docx,
+        ]
+        .into_iter()
+        .find(|format| format.get_mime_type() == mime_type)
+    }
+
+    pub fn from_path(path: &Path) -> Option<RulesFormat> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        FILE_EXTENSIONS
+            .iter()
+            .find(|(known, _)| *known == extension)
+            .map(|(_, format)| *format)
+    }
+}
+
+/**
+ * File extensions rules can be read from, and the format each is read as
+ */
+pub static FILE_EXTENSIONS: &[(&str, RulesFormat)] = &[
+    ("txt", RulesFormat::PlainText),
+    ("html", RulesFormat::Html),
+    ("htm", RulesFormat::Html),
+    ("md", RulesFormat::Markdown),
+    ("markdown", RulesFormat::Markdown),
+    ("docx", RulesFormat::Docx),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Heading(usize),
+    Paragraph,
+    ListItem,
+}
+
+/**
+ * A paragraph of a formatted document, noting whether all of it is bold or italic
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl Block {
+    fn get_heading_level(&self) -> Option<usize> {
+        match self.kind {
+            BlockKind::Heading(level) => Some(level),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Collects the runs of text in a block, collapsing whitespace and tracking emphasis
+ */
+#[derive(Debug, Default)]
+pub struct BlockText {
+    text: String,
+    characters: usize,
+    bold_characters: usize,
+    italic_characters: usize,
+}
+
+impl BlockText {
+    pub fn push(&mut self, text: &str, bold: bool, italic: bool) {
+        for character in text.chars() {
+            if character.is_whitespace() {
+                if !self.text.is_empty() && !self.text.ends_with(' ') {
+                    self.text.push(' ');
+                }
+                continue;
+            }
+            self.text.push(character);
+            self.characters += 1;
+            self.bold_characters += usize::from(bold);
+            self.italic_characters += usize::from(italic);
+        }
+    }
+
+    /**
+     * Finish the block, leaving this empty for the next one
+     */
+    pub fn finish(&mut self, kind: BlockKind) -> Block {
+        let text = std::mem::take(self);
+        Block {
+            kind,
+            text: text.text.trim_end().to_owned(),
+            bold: text.characters > 0 && text.bold_characters == text.characters,
+            italic: text.characters > 0 && text.italic_characters == text.characters,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Utility,
+    Passive,
+    Primary,
+    Secondary,
+    Special,
+    Subclasses,
+}
+
+impl Section {
+    /**
+     * The section a heading or bold paragraph starts, like "Passive" or "Primary Action:"
+     */
+    fn from_block(block: &Block) -> Option<Section> {
+        if block.get_heading_level().is_none() && !block.bold {
+            return None;
+        }
+        let text = block.text.trim_end_matches(':');
+        if text.split_whitespace().count() > 2 {
+            return None;
+        }
+        match text.split_whitespace().next()? {
+            "Utility" | "Utilities" => Some(Section::Utility),
+            "Passive" | "Passives" => Some(Section::Passive),
+            "Primary" => Some(Section::Primary),
+            "Secondary" => Some(Section::Secondary),
+            "Special" => Some(Section::Special),
+            "Subclasses" => Some(Section::Subclasses),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Ability {
+    name: String,
+    description: Vec<String>,
+}
+
+/**
+ * The abilities under each section heading of an origin or class
+ */
+#[derive(Debug, Default)]
+struct Sections(Vec<(Section, Vec<Ability>)>);
+
+impl Sections {
+    fn new(blocks: &[&Block]) -> Sections {
+        let mut sections = Sections::default();
+        for block in blocks {
+            if let Some(section) = Section::from_block(block) {
+                sections.0.push((section, vec![]));
+                continue;
+            }
+            let Some((_, abilities)) = sections.0.last_mut() else {
+                continue;
+            };
+            let is_name = block.get_heading_level().is_some()
+                || block.kind == BlockKind::ListItem
+                || block.bold;
+            if is_name {
+                abilities.push(Ability {
+                    name: block.text.clone(),
+                    description: vec![],
+                });
+            } else if !block.italic {
+                // Italic paragraphs are flavour text rather than rules
+                if abilities.is_empty() {
+                    abilities.push(Ability::default());
+                }
+                if let Some(ability) = abilities.last_mut() {
+                    ability.description.push(block.text.clone());
+                }
+            }
+        }
+        sections
+    }
+
+    fn abilities(&self, section: Section) -> impl Iterator<Item = (String, String)> + '_ {
+        self.0
+            .iter()
+            .filter(move |(found, _)| *found == section)
+            .flat_map(|(_, abilities)| abilities)
+            .map(|ability| (ability.name.clone(), ability.description.join("\n")))
+    }
+
+    fn action(
+        &self,
+        section: Section,
+        error: fn() -> GetOriginsAndClassesError,
+    ) -> Result<(String, String), GetOriginsAndClassesError> {
+        self.abilities(section).next().ok_or_else(error)
+    }
+
+    fn actions(
+        &self,
+        error: fn() -> GetOriginsAndClassesError,
+    ) -> Result<OriginActions, GetOriginsAndClassesError> {
+        let (primary_name, primary_description) = self.action(Section::Primary, error)?;
+        let (secondary_name, secondary_description) = self.action(Section::Secondary, error)?;
+        let (special_name, special_description) = self.action(Section::Special, error)?;
+        Ok(OriginActions::new(
+            PrimaryAction::new(primary_name, primary_description),
+            SecondaryAction::new(secondary_name, secondary_description),
+            SpecialAction::new(special_name, special_description),
+        ))
+    }
+
+    fn utilities(&self) -> Vec<ClassUtility> {
+        self.abilities(Section::Utility)
+            .map(|(name, description)| ClassUtility::new(name, description))
+            .collect()
+    }
+
+    fn passives(&self) -> Vec<ClassPassive> {
+        self.abilities(Section::Passive)
+            .map(|(name, description)| ClassPassive::new(name, description))
+            .collect()
+    }
+
+    fn into_origin(self, name: String) -> Result<Origin, GetOriginsAndClassesError> {
+        let actions = self.actions(|| GetOriginsAndClassesError::OriginParse)?;
+        Ok(Origin::new(
+            name,
+            self.utilities(),
+            self.passives(),
+            actions,
+        ))
+    }
+
+    fn into_class(
+        self,
+        name: String,
+        level: Option<usize>,
+        requirements: Option<Box<dyn crate::model::classes::ClassRequirement>>,
+    ) -> Result<Class, GetOriginsAndClassesError> {
+        let (primary_name, primary_description) =
+            self.action(Section::Primary, || GetOriginsAndClassesError::ClassParse)?;
+        let (secondary_name, secondary_description) =
+            self.action(Section::Secondary, || GetOriginsAndClassesError::ClassParse)?;
+        let (special_name, special_description) =
+            self.action(Section::Special, || GetOriginsAndClassesError::ClassParse)?;
+        let subclasses = self
+            .0
+            .iter()
+            .filter(|(section, _)| *section == Section::Subclasses)
+            .flat_map(|(_, abilities)| abilities)
+            .flat_map(|ability| std::iter::once(&ability.name).chain(&ability.description))
+            .map(String::as_str);
+        let subclasses = parse_subclasses(subclasses);
+        let mut class = Class::new(
+            name,
+            level,
+            self.utilities(),
+            self.passives(),
+            PrimaryAction::new(primary_name, primary_description),
+            SecondaryAction::new(secondary_name, secondary_description),
+            SpecialAction::new(special_name, special_description),
+            requirements,
+        );
+        class.set_subclasses(subclasses);
+        Ok(class)
+    }
+}
+
+fn is_class_heading(text: &str) -> bool {
+    text.contains('(')
+}
+
+/**
+ * Headings before the origins, each with the paragraphs under it, for the glossary
+ */
+fn glossary(blocks: &[&Block]) -> Glossary {
+    let mut sections: Vec<(&str, Vec<&str>)> = vec![("", vec![])];
+    for block in blocks {
+        if block.get_heading_level().is_some() {
+            sections.push((&block.text, vec![]));
+        } else if let Some((_, paragraphs)) = sections.last_mut() {
+            paragraphs.push(&block.text);
+        }
+    }
+    Glossary::from_sections(sections)
+}
+
+/**
+ * The entries under the Idea Bank heading, each starting at a heading, list item or bold
+ * paragraph with any plain paragraphs after it as notes
+ */
+fn idea_bank(blocks: &[&Block]) -> Vec<DraftClass> {
+    let mut entries: Vec<(&str, Vec<&str>)> = vec![];
+    for block in blocks {
+        let is_title =
+            block.get_heading_level().is_some() || block.kind == BlockKind::ListItem || block.bold;
+        match entries.last_mut() {
+            Some((_, notes)) if !is_title => notes.push(&block.text),
+            _ => entries.push((&block.text, vec![])),
+        }
+    }
+    entries
+        .into_iter()
+        .map(|(title, notes)| draft_class(title, &notes))
+        .collect()
+}
+
+/**
+ * Read rules from a document with real headings. Origins are the headings under "Origins",
+ * classes are headings like `Fighter (I)`, and each has section headings or bold paragraphs
+ * for its utilities, passives and actions, with ability names as list items, bold paragraphs
+ * or headings.
+ */
+pub fn parse(blocks: &[Block]) -> Result<ParsedRules, GetOriginsAndClassesError> {
+    let blocks = blocks
+        .iter()
+        .filter(|block| !block.text.is_empty())
+        .collect::<Vec<_>>();
+    let origins_start = blocks
+        .iter()
+        .position(|block| block.get_heading_level().is_some() && block.text.starts_with("Origins"))
+        .ok_or(GetOriginsAndClassesError::FormatChange)?;
+    let origins_level = blocks[origins_start]
+        .get_heading_level()
+        .ok_or(GetOriginsAndClassesError::FormatChange)?;
+    let glossary = glossary(&blocks[..origins_start]);
+
+    let mut origins = vec![];
+    let mut classes = vec![];
+    let mut idea_bank_entries = vec![];
+    let mut index = origins_start + 1;
+    while let Some(block) = blocks.get(index) {
+        let Some(level) = block.get_heading_level() else {
+            index += 1;
+            continue;
+        };
+        let end = blocks[index + 1..]
+            .iter()
+            .position(|next| next.get_heading_level().is_some_and(|next| next <= level))
+            .map_or(blocks.len(), |position| index + 1 + position);
+        if block.text.contains("Idea Bank") {
+            idea_bank_entries = idea_bank(&blocks[index + 1..end]);
+            break;
+        }
+        let contents = &blocks[index + 1..end];
+        if is_class_heading(&block.text) {
+            let (name, level, requirements) = parse_class_heading(&block.text);
+            classes.push(Sections::new(contents).into_class(name, level, requirements)?);
+            index = end;
+        } else if classes.is_empty() && level > origins_level {
+            let name = block.text.trim().to_owned();
+            let sections = Sections::new(contents);
+            origins.push(if sections.0.is_empty() {
+                let description = contents
+                    .iter()
+                    .filter(|block| !block.italic)
+                    .map(|block| block.text.as_str())
+                    .collect::<Vec<_>>();
+                origin_without_abilities(name, &description.join("\n"))
+            } else {
+                sections.into_origin(name)?
+            });
+            index = end;
+        } else {
+            // Grouping headings like "Classes" are looked inside of
+            index += 1;
+        }
+    }
+
+    Ok(ParsedRules {
+        origins,
+        classes,
+        glossary,
+        idea_bank: idea_bank_entries,
+    })
+}
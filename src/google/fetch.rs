@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use super::RulesSource;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+static REST_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static RESPONSE_CACHE: OnceLock<Mutex<ResponseCache>> = OnceLock::new();
+
+/**
+ * How often and how patiently a failed request is tried again
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each one after
+    pub initial_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            initial_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// Nothing was asked conditionally, so there is no body to reuse
+    #[error("{0} answered \"304 Not Modified\" with nothing cached")]
+    UncachedNotModified(String),
+}
+
+/**
+ * A body kept for answering a later `304 Not Modified`
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedResponse {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+/**
+ * Responses remembered by URL, stored between launches so the first fetch after
+ * starting can already be answered with `304 Not Modified`
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseCache {
+    responses: HashMap<String, CachedResponse>,
+}
+
+/**
+ * Copy of the remembered responses, for storing
+ */
+pub fn get_response_cache() -> ResponseCache {
+    response_cache()
+        .lock()
+        .map(|cache| cache.clone())
+        .unwrap_or_default()
+}
+
+/**
+ * Remember responses stored by an earlier launch, keeping any fetched since
+ */
+pub fn restore_response_cache(stored: ResponseCache) {
+    if let Ok(mut cache) = response_cache().lock() {
+        for (url, response) in stored.responses {
+            cache.responses.entry(url).or_insert(response);
+        }
+    }
+}
+
+fn client() -> &'static reqwest::Client {
+    REST_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+fn response_cache() -> &'static Mutex<ResponseCache> {
+    RESPONSE_CACHE.get_or_init(Mutex::default)
+}
+
+/**
+ * The URL without the API key, so keys are never written out with the cache
+ */
+fn cache_key(url: &Url) -> String {
+    let mut url = url.clone();
+    let query = url
+        .query_pairs()
+        .filter(|(name, _)| name != "key")
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    url.query_pairs_mut().clear().extend_pairs(query);
+    url.to_string()
+}
+
+/**
+ * Failures that might not happen again: timeouts, dropped connections and server side errors
+ */
+fn is_transient(err: &reqwest::Error) -> bool {
+    err.is_timeout()
+        || err.is_connect()
+        || err.is_body()
+        || err.status().is_some_and(|status| {
+            status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+        })
+}
+
+/**
+ * GET a text body, retrying transient failures with exponential backoff.
+ * Bodies sent with an `ETag` or `Last-Modified` are remembered, so asking again
+ * can be answered with `304 Not Modified` instead of the whole document.
+ */
+pub async fn get_text(
+    source: &RulesSource,
+    url: &str,
+    query: &[(&str, &str)],
+) -> Result<String, FetchError> {
+    let mut delay = source.retry.initial_delay;
+    let mut attempt = 1;
+    loop {
+        match get_text_once(source, url, query).await {
+            Err(FetchError::Reqwest(err))
+                if attempt < source.retry.attempts && is_transient(&err) =>
+            {
+                warn!("Attempt {attempt} to fetch {url} failed, retrying in {delay:?}: {err}");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn get_text_once(
+    source: &RulesSource,
+    url: &str,
+    query: &[(&str, &str)],
+) -> Result<String, FetchError> {
+    let request = client()
+        .get(url)
+        .query(query)
+        .timeout(source.timeout)
+        .build()?;
+    let cache_key = cache_key(request.url());
+    let cached = response_cache()
+        .lock()
+        .ok()
+        .and_then(|cache| cache.responses.get(&cache_key).cloned());
+
+    let mut request = reqwest::RequestBuilder::from_parts(client().clone(), request);
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send().await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        let Some(cached) = cached else {
+            return Err(FetchError::UncachedNotModified(cache_key));
+        };
+        info!("{url} is unchanged, using the cached response");
+        return Ok(cached.body);
+    }
+    let response = response.error_for_status()?;
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    let body = response.text().await?;
+
+    if etag.is_some() || last_modified.is_some() {
+        if let Ok(mut cache) = response_cache().lock() {
+            cache.responses.insert(
+                cache_key,
+                CachedResponse {
+                    etag,
+                    last_modified,
+                    body: body.clone(),
+                },
+            );
+        }
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_leaves_out_api_key() {
+        let url =
+            Url::parse("https://example.com/files/doc?key=secret&fields=modifiedTime").unwrap();
+        assert_eq!(
+            cache_key(&url),
+            "https://example.com/files/doc?fields=modifiedTime"
+        );
+    }
+}
@@ -0,0 +1,785 @@
+use super::document::{self, Block, BlockKind, RulesFormat};
+use super::{
+    get_rules_content, marked_bonus_classes, parse_rules, GetOriginsAndClassesError, RulesSource,
+    BONUS_CLASSES,
+};
+use crate::util::{from_roman, LevelNumerals};
+
+use std::collections::HashMap;
+use std::fmt;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Sections every origin and class needs, in the order the plain text parser reads them
+static REQUIRED_SECTIONS: [&str; 5] = ["Utility", "Passive", "Primary", "Secondary", "Special"];
+/// Sections whose first line is the action's name even without a `*`
+static ACTION_SECTIONS: [&str; 3] = ["Primary", "Secondary", "Special"];
+static SUBCLASSES: &str = "Subclasses";
+
+/**
+ * Where in the rules document an issue is, lines for plain text and paragraphs otherwise
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintPosition {
+    Line(usize),
+    Paragraph(usize),
+}
+
+impl fmt::Display for LintPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintPosition::Line(line) => write!(f, "Line {line}"),
+            LintPosition::Paragraph(paragraph) => write!(f, "Paragraph {paragraph}"),
+        }
+    }
+}
+
+/**
+ * A way the rules document strays from the format the parser expects
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintProblem {
+    /// The parser itself gave up, the other issues likely say why
+    Unparsable(String),
+    MissingOrigins,
+    /// An origin with no abilities that doesn't say how many classes it gives
+    MissingBonusClasses {
+        unit: String,
+    },
+    MissingSection {
+        unit: String,
+        section: &'static str,
+    },
+    MisorderedSection {
+        unit: String,
+        section: &'static str,
+        after: &'static str,
+    },
+    EmptySection {
+        unit: String,
+        section: &'static str,
+    },
+    UnnamedAbility {
+        unit: String,
+        section: &'static str,
+    },
+    MissingDescription {
+        unit: String,
+        ability: String,
+    },
+    DuplicateName {
+        name: String,
+        first: LintPosition,
+    },
+    InvalidLevel {
+        class: String,
+        level: String,
+    },
+    UnknownRequirement {
+        class: String,
+        requirement: String,
+    },
+    RequirementLevel {
+        class: String,
+        level: usize,
+        requirement: String,
+        requirement_level: usize,
+    },
+}
+
+impl fmt::Display for LintProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let roman = |level| LevelNumerals::Roman.format(level);
+        match self {
+            LintProblem::Unparsable(error) => write!(f, "The rules don't parse: {error}"),
+            LintProblem::MissingOrigins => write!(f, "There is no \"Origins\" heading"),
+            LintProblem::MissingBonusClasses { unit } => write!(
+                f,
+                "\"{unit}\" has no abilities or \"{BONUS_CLASSES}\" line, so it gives one class"
+            ),
+            LintProblem::MissingSection { unit, section } => {
+                write!(f, "\"{unit}\" has no {section} section")
+            }
+            LintProblem::MisorderedSection {
+                unit,
+                section,
+                after,
+            } => write!(f, "\"{unit}\" has its {section} section after {after}"),
+            LintProblem::EmptySection { unit, section } => {
+                write!(f, "The {section} section of \"{unit}\" has nothing in it")
+            }
+            LintProblem::UnnamedAbility { unit, section } => write!(
+                f,
+                "Text in the {section} section of \"{unit}\" isn't under an ability name"
+            ),
+            LintProblem::MissingDescription { unit, ability } => {
+                write!(f, "\"{ability}\" in \"{unit}\" has no description")
+            }
+            LintProblem::DuplicateName { name, first } => {
+                write!(f, "\"{name}\" is already used at {first}")
+            }
+            LintProblem::InvalidLevel { class, level } => {
+                write!(
+                    f,
+                    "\"{class}\" has level \"{level}\", which isn't a Roman numeral"
+                )
+            }
+            LintProblem::UnknownRequirement { class, requirement } => write!(
+                f,
+                "\"{class}\" requires \"{requirement}\", but there is no such class"
+            ),
+            LintProblem::RequirementLevel {
+                class,
+                level,
+                requirement,
+                requirement_level,
+            } => write!(
+                f,
+                "\"{class}\" is level {} but requires \"{requirement}\" at level {}",
+                roman(*level),
+                roman(*requirement_level)
+            ),
+        }
+    }
+}
+
+/**
+ * A problem in the rules document along with the text it was found in
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    position: Option<LintPosition>,
+    text: String,
+    problem: LintProblem,
+}
+
+impl LintIssue {
+    fn new(line: &Line, problem: LintProblem) -> LintIssue {
+        LintIssue {
+            position: Some(line.position),
+            text: line.text.clone(),
+            problem,
+        }
+    }
+
+    pub fn get_position(&self) -> Option<LintPosition> {
+        self.position
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_problem(&self) -> &LintProblem {
+        &self.problem
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{position}: {}", self.problem),
+            None => write!(f, "{}", self.problem),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    /// Starts an origin or class, or a heading inside one, with the heading level if known
+    Heading(Option<usize>),
+    Section(&'static str),
+    /// An ability name, a `*` line, list item or bold paragraph
+    Name,
+    Text,
+    /// Italic text that isn't part of the rules
+    Flavour,
+}
+
+/**
+ * A non-empty line or paragraph, classified the same way whatever the document's format
+ */
+#[derive(Debug, Clone)]
+struct Line {
+    position: LintPosition,
+    text: String,
+    kind: LineKind,
+}
+
+/**
+ * The section a line starts, like "Passive" or "Primary Action:"
+ */
+fn section_name(text: &str) -> Option<&'static str> {
+    let text = text.trim().trim_end_matches(':');
+    if text.split_whitespace().count() > 2 {
+        return None;
+    }
+    match text.split_whitespace().next()? {
+        "Utility" | "Utilities" => Some("Utility"),
+        "Passive" | "Passives" => Some("Passive"),
+        "Primary" => Some("Primary"),
+        "Secondary" => Some("Secondary"),
+        "Special" => Some("Special"),
+        "Subclasses" => Some(SUBCLASSES),
+        _ => None,
+    }
+}
+
+/**
+ * Origins and classes in the plain text export start after a blank line, and once the first
+ * class is found only lines with a level like `(II)` start one
+ */
+fn plain_text_lines(text: &str) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut after_break = true;
+    let mut seen_class = false;
+    for (index, line) in text.lines().enumerate() {
+        let text = line.trim();
+        if text.is_empty() {
+            after_break = true;
+            continue;
+        }
+        let (text, kind) = if text.len() > 1 && text.starts_with('_') && text.ends_with('_') {
+            (text, LineKind::Flavour)
+        } else if let Some(section) = section_name(text) {
+            (text, LineKind::Section(section))
+        } else if let Some(name) = text.strip_prefix('*') {
+            (name.trim(), LineKind::Name)
+        } else if after_break && (!seen_class || text.contains('(')) {
+            seen_class |= text.contains('(');
+            (text, LineKind::Heading(None))
+        } else {
+            (text, LineKind::Text)
+        };
+        lines.push(Line {
+            position: LintPosition::Line(index + 1),
+            text: text.to_owned(),
+            kind,
+        });
+        after_break = false;
+    }
+    lines
+}
+
+fn document_lines(blocks: &[Block]) -> Vec<Line> {
+    blocks
+        .iter()
+        .filter(|block| !block.text.is_empty())
+        .enumerate()
+        .map(|(index, block)| {
+            let heading = match block.kind {
+                BlockKind::Heading(level) => Some(level),
+                _ => None,
+            };
+            let section = section_name(&block.text).filter(|_| heading.is_some() || block.bold);
+            let kind = match (section, heading) {
+                (Some(section), _) => LineKind::Section(section),
+                (None, Some(level)) => LineKind::Heading(Some(level)),
+                _ if block.kind == BlockKind::ListItem || block.bold => LineKind::Name,
+                _ if block.italic => LineKind::Flavour,
+                _ => LineKind::Text,
+            };
+            Line {
+                position: LintPosition::Paragraph(index + 1),
+                text: block.text.clone(),
+                kind,
+            }
+        })
+        .collect()
+}
+
+/**
+ * An origin or class heading with the lines under it
+ */
+struct Unit<'a> {
+    heading: &'a Line,
+    level: Option<usize>,
+    lines: Vec<&'a Line>,
+}
+
+impl Unit<'_> {
+    fn is_class(&self) -> bool {
+        self.heading.text.contains('(')
+    }
+
+    fn name(&self) -> &str {
+        self.heading
+            .text
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .trim()
+    }
+
+    /**
+     * The text between the parentheses of a class heading
+     */
+    fn level_text(&self) -> Option<&str> {
+        self.heading.text.split_once('(')?.1.split(')').next()
+    }
+
+    fn requirements(&self) -> impl Iterator<Item = &str> {
+        self.heading
+            .text
+            .split_once("Req:")
+            .map(|(_, requirements)| requirements)
+            .into_iter()
+            .flat_map(|requirements| requirements.split(','))
+            .map(str::trim)
+            .filter(|requirement| !requirement.is_empty())
+    }
+}
+
+/**
+ * Group the lines between "Origins" and the Idea Bank by origin and class. A heading deeper
+ * than the one it is under names an ability, unless nothing came before it, which makes the
+ * heading above a grouping one like "Classes".
+ */
+fn units(lines: &[Line]) -> Vec<Unit<'_>> {
+    let mut units: Vec<Unit> = vec![];
+    for line in lines {
+        if let LineKind::Heading(level) = line.kind {
+            let starts_unit = units.last().is_none_or(|unit| {
+                unit.lines.is_empty()
+                    || level.is_none()
+                    || unit
+                        .level
+                        .zip(level)
+                        .is_some_and(|(unit, level)| level <= unit)
+            });
+            if starts_unit {
+                units.push(Unit {
+                    heading: line,
+                    level,
+                    lines: vec![],
+                });
+                continue;
+            }
+        }
+        if let Some(unit) = units.last_mut() {
+            unit.lines.push(line);
+        }
+    }
+    // Empty grouping headings aren't origins, but an empty class is still worth a mention
+    units.retain(|unit| !unit.lines.is_empty() || unit.is_class());
+    units
+}
+
+#[derive(Debug)]
+struct Ability<'a> {
+    line: &'a Line,
+    described: bool,
+}
+
+/// A section's name, the line starting it and the abilities under it
+type Section<'a> = (&'static str, &'a Line, Vec<Ability<'a>>);
+
+/**
+ * Check that an origin or class has each of its sections in order with named and described
+ * abilities under them
+ */
+fn lint_sections(unit: &Unit, issues: &mut Vec<LintIssue>) {
+    let name = unit.name();
+    let mut sections: Vec<Section> = vec![];
+    let mut ability_names = HashMap::new();
+    for line in &unit.lines {
+        let kind = match line.kind {
+            LineKind::Heading(_) => LineKind::Name,
+            kind => kind,
+        };
+        match kind {
+            LineKind::Section(section) => {
+                let order =
+                    |section: &str| REQUIRED_SECTIONS.iter().position(|next| *next == section);
+                let after = sections
+                    .iter()
+                    .map(|(found, _, _)| *found)
+                    .rev()
+                    .find(|found| order(found) > order(section));
+                if let (Some(after), Some(_)) = (after, order(section)) {
+                    issues.push(LintIssue::new(
+                        line,
+                        LintProblem::MisorderedSection {
+                            unit: name.to_owned(),
+                            section,
+                            after,
+                        },
+                    ));
+                }
+                sections.push((section, line, vec![]));
+            }
+            LineKind::Name | LineKind::Text => {
+                let Some((section, _, abilities)) = sections.last_mut() else {
+                    continue;
+                };
+                if *section == SUBCLASSES {
+                    continue;
+                }
+                let is_name = kind == LineKind::Name
+                    || (abilities.is_empty() && ACTION_SECTIONS.contains(section));
+                if is_name {
+                    if let Some(first) = ability_names.insert(line.text.as_str(), line.position) {
+                        issues.push(LintIssue::new(
+                            line,
+                            LintProblem::DuplicateName {
+                                name: line.text.clone(),
+                                first,
+                            },
+                        ));
+                    }
+                    abilities.push(Ability {
+                        line,
+                        described: false,
+                    });
+                } else if let Some(ability) = abilities.last_mut() {
+                    ability.described = true;
+                } else {
+                    issues.push(LintIssue::new(
+                        line,
+                        LintProblem::UnnamedAbility {
+                            unit: name.to_owned(),
+                            section,
+                        },
+                    ));
+                    // Reported once, with the rest of the text going along with it
+                    abilities.push(Ability {
+                        line,
+                        described: true,
+                    });
+                }
+            }
+            LineKind::Heading(_) | LineKind::Flavour => {}
+        }
+    }
+    report_sections(unit, &sections, issues);
+}
+
+/**
+ * Report sections that are missing or empty, and abilities without descriptions
+ */
+fn report_sections(unit: &Unit, sections: &[Section], issues: &mut Vec<LintIssue>) {
+    let name = unit.name();
+    for required in REQUIRED_SECTIONS {
+        if !sections.iter().any(|(section, _, _)| *section == required) {
+            issues.push(LintIssue::new(
+                unit.heading,
+                LintProblem::MissingSection {
+                    unit: name.to_owned(),
+                    section: required,
+                },
+            ));
+        }
+    }
+    for (section, line, abilities) in sections {
+        if *section != SUBCLASSES && abilities.is_empty() {
+            issues.push(LintIssue::new(
+                line,
+                LintProblem::EmptySection {
+                    unit: name.to_owned(),
+                    section,
+                },
+            ));
+        }
+        for ability in abilities.iter().filter(|ability| !ability.described) {
+            issues.push(LintIssue::new(
+                ability.line,
+                LintProblem::MissingDescription {
+                    unit: name.to_owned(),
+                    ability: ability.line.text.clone(),
+                },
+            ));
+        }
+    }
+}
+
+/**
+ * Check a class heading's level and that what it requires exists at a lower level
+ */
+fn lint_class_heading(unit: &Unit, levels: &HashMap<&str, Option<usize>>) -> Vec<LintIssue> {
+    let name = unit.name();
+    let mut issues = vec![];
+    let level_text = unit.level_text().unwrap_or_default().trim();
+    let level = from_roman(level_text);
+    if level.is_none() {
+        issues.push(LintIssue::new(
+            unit.heading,
+            LintProblem::InvalidLevel {
+                class: name.to_owned(),
+                level: level_text.to_owned(),
+            },
+        ));
+    }
+
+    for requirement in unit.requirements() {
+        let requirement_level = if let Some((_, info)) = requirement.split_once("Any Level") {
+            // Like `Any Level IX "Kn"`, a class of that level whose name starts with "Kn"
+            let required_level = info.split_whitespace().next().and_then(from_roman);
+            if required_level.is_none() {
+                issues.push(LintIssue::new(
+                    unit.heading,
+                    LintProblem::InvalidLevel {
+                        class: name.to_owned(),
+                        level: requirement.to_owned(),
+                    },
+                ));
+            }
+            let prefix = info.split('"').nth(1).unwrap_or_default();
+            if !levels.keys().any(|class| class.starts_with(prefix)) {
+                issues.push(LintIssue::new(
+                    unit.heading,
+                    LintProblem::UnknownRequirement {
+                        class: name.to_owned(),
+                        requirement: requirement.to_owned(),
+                    },
+                ));
+            }
+            required_level
+        } else if let Some(required_level) = levels.get(requirement) {
+            *required_level
+        } else {
+            issues.push(LintIssue::new(
+                unit.heading,
+                LintProblem::UnknownRequirement {
+                    class: name.to_owned(),
+                    requirement: requirement.to_owned(),
+                },
+            ));
+            None
+        };
+        if let Some((level, requirement_level)) = level.zip(requirement_level) {
+            if requirement_level >= level {
+                issues.push(LintIssue::new(
+                    unit.heading,
+                    LintProblem::RequirementLevel {
+                        class: name.to_owned(),
+                        level,
+                        requirement: requirement.to_owned(),
+                        requirement_level,
+                    },
+                ));
+            }
+        }
+    }
+    issues
+}
+
+fn lint_lines(lines: &[Line]) -> Vec<LintIssue> {
+    let is_heading = |line: &&Line| matches!(line.kind, LineKind::Heading(_));
+    let Some(origins_start) = lines
+        .iter()
+        .position(|line| is_heading(&line) && line.text.starts_with("Origins"))
+    else {
+        return vec![LintIssue {
+            position: None,
+            text: String::new(),
+            problem: LintProblem::MissingOrigins,
+        }];
+    };
+    let rules_end = lines[origins_start..]
+        .iter()
+        // Only classes start after a blank line in plain text, so any line can end them
+        .position(|line| line.kind != LineKind::Name && line.text.starts_with("Idea Bank"))
+        .map_or(lines.len(), |position| origins_start + position);
+    let units = units(&lines[origins_start + 1..rules_end]);
+
+    let mut issues = vec![];
+    let mut names = HashMap::new();
+    for unit in &units {
+        if let Some(first) = names.insert(unit.name(), unit.heading.position) {
+            issues.push(LintIssue::new(
+                unit.heading,
+                LintProblem::DuplicateName {
+                    name: unit.name().to_owned(),
+                    first,
+                },
+            ));
+        }
+    }
+    let levels = units
+        .iter()
+        .filter(|unit| unit.is_class())
+        .map(|unit| (unit.name(), unit.level_text().and_then(from_roman)))
+        .collect::<HashMap<_, _>>();
+    for unit in &units {
+        // Origins without any sections give bonus classes instead, and should say how many
+        let has_sections = unit
+            .lines
+            .iter()
+            .any(|line| matches!(line.kind, LineKind::Section(_)));
+        if unit.is_class() || has_sections {
+            lint_sections(unit, &mut issues);
+        } else if !unit
+            .lines
+            .iter()
+            .any(|line| marked_bonus_classes(&line.text).is_some())
+        {
+            issues.push(LintIssue::new(
+                unit.heading,
+                LintProblem::MissingBonusClasses {
+                    unit: unit.name().to_owned(),
+                },
+            ));
+        }
+        if unit.is_class() {
+            issues.extend(lint_class_heading(unit, &levels));
+        }
+    }
+    issues.sort_by_key(LintIssue::get_position);
+    issues
+}
+
+/**
+ * Check a rules document for everything that would trip up the parser or confuse players,
+ * with the parser's own error first if it fails
+ */
+pub fn lint_rules(
+    content: &[u8],
+    format: RulesFormat,
+) -> Result<Vec<LintIssue>, GetOriginsAndClassesError> {
+    let text = || String::from_utf8_lossy(content);
+    let lines = match format {
+        RulesFormat::PlainText => plain_text_lines(&text()),
+        RulesFormat::Html => document_lines(&document::html::blocks(&text())),
+        RulesFormat::Markdown => document_lines(&document::markdown::blocks(&text())),
+        RulesFormat::Docx => document_lines(&document::docx::blocks(content)?),
+    };
+    let mut issues = vec![];
+    if let Err(err) = parse_rules(content, format) {
+        issues.push(LintIssue {
+            position: None,
+            text: String::new(),
+            problem: LintProblem::Unparsable(err.to_string()),
+        });
+    }
+    issues.extend(lint_lines(&lines));
+    Ok(issues)
+}
+
+/**
+ * Fetch or read the rules the same way as for play and check them
+ */
+pub async fn lint(source: RulesSource) -> Result<Vec<LintIssue>, GetOriginsAndClassesError> {
+    let (content, format) = get_rules_content(&source).await?;
+    lint_rules(&content, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static RULES_EXPORT: &str = include_str!("fixtures/rules.txt");
+    static RULES_MARKDOWN: &str = include_str!("fixtures/rules.md");
+    static RULES_HTML: &str = include_str!("fixtures/rules.html");
+    static RULES_DOCX: &[u8] = include_bytes!("fixtures/rules.docx");
+
+    fn problems(rules: &str, format: RulesFormat) -> Vec<(Option<LintPosition>, String)> {
+        lint_rules(rules.as_bytes(), format)
+            .unwrap()
+            .into_iter()
+            .map(|issue| (issue.get_position(), issue.get_problem().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_fixtures_are_clean() {
+        assert_eq!(problems(RULES_EXPORT, RulesFormat::PlainText), []);
+        assert_eq!(problems(RULES_MARKDOWN, RulesFormat::Markdown), []);
+        assert_eq!(problems(RULES_HTML, RulesFormat::Html), []);
+        assert_eq!(lint_rules(RULES_DOCX, RulesFormat::Docx).unwrap(), []);
+    }
+
+    #[test]
+    fn test_plain_text_issues() {
+        let rules = RULES_EXPORT
+            .replace("Secondary\n* Guard\n", "Secondary\n")
+            .replace("* Tough\nGain 1 Defense.\n", "* Tough\n")
+            .replace(
+                "Knight (II) Req: Fighter",
+                "Knight (II) Req: Fihgter, Any Level V \"F\"",
+            )
+            .replace("Elf\n", "Fighter (I)\n");
+
+        assert_eq!(
+            problems(&rules, RulesFormat::PlainText),
+            [
+                (
+                    Some(LintPosition::Line(36)),
+                    "\"Fighter\" is already used at Line 17".to_owned()
+                ),
+                (
+                    Some(LintPosition::Line(41)),
+                    "\"Tough\" in \"Fighter\" has no description".to_owned()
+                ),
+                (
+                    Some(LintPosition::Line(54)),
+                    "\"Knight\" requires \"Fihgter\", but there is no such class".to_owned()
+                ),
+                (
+                    Some(LintPosition::Line(54)),
+                    "\"Knight\" is level II but requires \"Any Level V \"F\"\" at level V"
+                        .to_owned()
+                ),
+                (
+                    Some(LintPosition::Line(65)),
+                    "\"Protect an ally from the next hit.\" in \"Knight\" has no description"
+                        .to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_origins_without_bonus_classes() {
+        let rules = RULES_MARKDOWN.replace("Bonus Classes: 1\n\n", "");
+
+        assert_eq!(
+            problems(&rules, RulesFormat::Markdown),
+            [(
+                Some(LintPosition::Paragraph(8)),
+                "\"Human\" has no abilities or \"Bonus Classes:\" line, so it gives one class"
+                    .to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_document_issues() {
+        let rules = RULES_MARKDOWN
+            .replace("**Secondary**\n\n* Shove", "* Shove")
+            .replace("## Fighter (I)", "## Fighter (1)");
+
+        assert_eq!(
+            problems(&rules, RulesFormat::Markdown),
+            [
+                (
+                    None,
+                    "The rules don't parse: Failed to parse class".to_owned()
+                ),
+                (
+                    Some(LintPosition::Paragraph(29)),
+                    "\"Fighter\" has no Secondary section".to_owned()
+                ),
+                (
+                    Some(LintPosition::Paragraph(29)),
+                    "\"Fighter\" has level \"1\", which isn't a Roman numeral".to_owned()
+                ),
+            ]
+        );
+    }
+}
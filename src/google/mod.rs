@@ -1,16 +1,27 @@
-use std::sync::OnceLock;
+mod document;
+pub mod fetch;
+pub mod lint;
+pub mod profile;
+
+use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
 use crate::model::classes::{
     AndClassRequirement, Class, ClassCache, ClassPassive, ClassRequirement, ClassUtility,
-    LevelPrefixRequirement, SuperClassRequirement,
+    LevelPrefixRequirement, Origin, OriginActions, OriginRules, SuperClassRequirement,
 };
+use crate::model::glossary::Glossary;
+use crate::model::idea_bank::DraftClass;
 use crate::util::from_roman;
+use document::docx::DocxError;
+use document::RulesFormat;
+use fetch::{FetchError, RetryPolicy};
 
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use thiserror::Error;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
 /*
  * A console and digital character sheet for campaigns under the greed ruleset.
@@ -30,17 +41,93 @@ use tracing::{info, instrument};
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-static API_KEY: &str = env!("API_KEY");
+/// Key used by profiles that don't set their own, when the build provides one
+static API_KEY: Option<&str> = option_env!("API_KEY");
+/**
+ * Whether profiles can leave their API key empty and use the build's
+ */
+pub fn has_built_in_api_key() -> bool {
+    API_KEY.is_some()
+}
+
+static GOOGLE_API_BASE_URL: &str = "https://www.googleapis.com";
 static GREED_RULES_DOC_ID: &str = "1154Ep1n8AuiG5iQVxNmahIzjb69BQD28C3QmLfta1n4";
-static RULES_EXPORT_FORMAT: &str = "text/plain";
-static REST_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
-#[allow(let_underscore_drop, clippy::too_many_lines)]
-#[instrument]
-fn get_class(
+/**
+ * Where a rules document is fetched from, built from a `RulesProfile`
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RulesSource {
+    base_url: String,
+    document_id: String,
+    api_key: String,
+    export_format: String,
+    /// Read instead of fetching the document when set
+    local_file: Option<PathBuf>,
+    timeout: Duration,
+    retry: RetryPolicy,
+}
+
+impl RulesSource {
+    pub fn new(
+        base_url: &str,
+        document_id: &str,
+        api_key: &str,
+        export_format: &str,
+    ) -> RulesSource {
+        RulesSource {
+            base_url: base_url.trim().trim_end_matches('/').to_owned(),
+            document_id: document_id.trim().to_owned(),
+            api_key: api_key.trim().to_owned(),
+            export_format: export_format.to_owned(),
+            local_file: None,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /**
+     * A source reading rules from a file on disk, in a format picked by its extension
+     */
+    pub fn local(path: impl Into<PathBuf>) -> RulesSource {
+        RulesSource {
+            local_file: Some(path.into()),
+            ..RulesSource::new("", "", "", "")
+        }
+    }
+
+    fn file_url(&self) -> String {
+        format!("{}/drive/v3/files/{}", self.base_url, self.document_id)
+    }
+}
+
+/**
+ * Origins, classes and keywords read from a rules document, whatever its format
+ */
+#[derive(Debug)]
+struct ParsedRules {
+    origins: Vec<Origin>,
+    classes: Vec<Class>,
+    glossary: Glossary,
+    idea_bank: Vec<DraftClass>,
+}
+
+impl ParsedRules {
+    fn into_class_cache(self, update_time: Option<i64>) -> ClassCache {
+        let mut class_cache = ClassCache::new(self.origins, self.classes, update_time);
+        class_cache.set_glossary(self.glossary);
+        class_cache.set_idea_bank(self.idea_bank);
+        class_cache
+    }
+}
+
+/**
+ * Split a class heading like `Knight (II) Req: Fighter` into its name, level and requirements
+ */
+fn parse_class_heading(
     first_line: &str,
-    mut paragraphs: impl Iterator<Item = String> + std::fmt::Debug,
-) -> Result<Class, GetOriginsAndClassesError> {
+) -> (String, Option<usize>, Option<Box<dyn ClassRequirement>>) {
     let class_components = first_line.split('(').collect::<Vec<&str>>();
     let class_name = class_components[0].trim().to_owned();
     let class_level = if class_components.len() > 1 {
@@ -56,6 +143,76 @@ fn get_class(
     } else {
         None
     };
+    (class_name, class_level, class_requirements)
+}
+
+/**
+ * Class names from a Subclasses section, which can list several to a line or say "None"
+ */
+fn parse_subclasses<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(str::trim)
+        // Italic flavour text rather than a class
+        .filter(|line| !(line.len() > 1 && line.starts_with('_') && line.ends_with('_')))
+        .flat_map(|line| line.trim_start_matches('*').split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.eq_ignore_ascii_case("None"))
+        .map(String::from)
+        .collect()
+}
+
+/**
+ * An Idea Bank entry, its first line read like a class heading when it is one
+ */
+fn draft_class(title: &str, notes: &[&str]) -> DraftClass {
+    let (heading, requirements) = match title.split_once("Req:") {
+        Some((heading, requirements)) => (heading, Some(requirements.trim().to_owned())),
+        None => (title, None),
+    };
+    let (name, level) = match heading.split_once('(') {
+        Some((name, level)) => (name, level.split(')').next().and_then(from_roman)),
+        None => (heading, None),
+    };
+    DraftClass::new(name.trim(), level, requirements, notes.join("\n"))
+}
+
+/// Line an origin without abilities of its own uses to say how many classes it gives instead
+static BONUS_CLASSES: &str = "Bonus Classes:";
+
+/**
+ * The count on an origin's "Bonus Classes:" line, if it has one that parses
+ */
+fn marked_bonus_classes(description: &str) -> Option<usize> {
+    description
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(BONUS_CLASSES))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+/// What an origin without abilities gives when it doesn't say, as rules from before the
+/// "Bonus Classes:" line only had such origins take a class at first level
+const DEFAULT_BONUS_CLASSES: usize = 1;
+
+/**
+ * An origin with no sections of its own, which says what it gives instead on a
+ * "Bonus Classes:" line
+ */
+fn origin_without_abilities(name: String, description: &str) -> Origin {
+    let bonus_classes = marked_bonus_classes(description).unwrap_or_else(|| {
+        warn!("\"{name}\" has no \"{BONUS_CLASSES}\" line, it gives {DEFAULT_BONUS_CLASSES}");
+        DEFAULT_BONUS_CLASSES
+    });
+    Origin::without_abilities(name, OriginRules::new(bonus_classes))
+}
+
+#[allow(let_underscore_drop, clippy::too_many_lines)]
+#[instrument]
+fn get_class(
+    first_line: &str,
+    mut paragraphs: impl Iterator<Item = String> + std::fmt::Debug,
+) -> Result<Class, GetOriginsAndClassesError> {
+    let (class_name, class_level, class_requirements) = parse_class_heading(first_line);
 
     paragraphs
         .next()
@@ -179,7 +336,10 @@ fn determine_class_requirements(line: &str) -> Box<dyn ClassRequirement> {
         ))
     } else if let Some(index) = line.find("Any Level") {
         let info = line.get((index + "Any Level".len())..).unwrap().trim();
-        let level = from_roman(info.split_whitespace().collect::<Vec<&str>>()[0]);
+        let level = info.split_whitespace().next().and_then(from_roman);
+        if level.is_none() {
+            warn!("\"{line}\" has no Roman numeral level, any level will meet it");
+        }
         let start_name = info.split('"').collect::<Vec<&str>>()[1];
         Box::new(LevelPrefixRequirement::new(level.unwrap_or(0), start_name))
     } else {
@@ -191,23 +351,12 @@ fn determine_class_requirements(line: &str) -> Box<dyn ClassRequirement> {
 fn get_origin(
     first_line: &str,
     mut paragraphs: impl Iterator<Item = String>,
-) -> Result<Class, GetOriginsAndClassesError> {
+) -> Result<Origin, GetOriginsAndClassesError> {
     let origin_name = first_line.to_owned();
-    if origin_name == "Human" {
-        Ok(Class::new(
-            String::from("Human"),
-            None,
-            vec![ClassUtility::new(String::new(), String::new())],
-            vec![ClassPassive::new(String::new(), String::new())],
-            PrimaryAction::new(String::new(), String::new()),
-            SecondaryAction::new(String::new(), String::new()),
-            SpecialAction::new(String::new(), String::new()),
-            None,
-        ))
-    } else {
-        paragraphs
-            .next()
-            .ok_or(GetOriginsAndClassesError::OriginParse)?;
+    let first_paragraph = paragraphs
+        .next()
+        .ok_or(GetOriginsAndClassesError::OriginParse)?;
+    if first_paragraph.starts_with("Utility") || first_paragraph.starts_with("Utilities") {
         let utility_data = paragraphs
             .by_ref()
             .take_while(|line| !line.starts_with("Passive"))
@@ -304,47 +453,107 @@ fn get_origin(
             .join("\n")
             .trim_end()
             .to_owned();
-        Ok(Class::new(
+        Ok(Origin::new(
             origin_name,
-            None,
             utilities.collect(),
             passives.collect(),
-            PrimaryAction::new(primary_name, primary_description),
-            SecondaryAction::new(secondary_name, secondary_description),
-            SpecialAction::new(special_name, special_description),
-            None,
+            OriginActions::new(
+                PrimaryAction::new(primary_name, primary_description),
+                SecondaryAction::new(secondary_name, secondary_description),
+                SpecialAction::new(special_name, special_description),
+            ),
         ))
+    } else {
+        // No sections, just a description running to the next blank line
+        let description = if first_paragraph.trim().is_empty() {
+            String::new()
+        } else {
+            std::iter::once(first_paragraph)
+                .chain(
+                    paragraphs
+                        .by_ref()
+                        .take_while(|line| !line.trim().is_empty()),
+                )
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        Ok(origin_without_abilities(origin_name, &description))
     }
 }
 
-async fn get_rules() -> Result<Vec<String>, GetOriginsAndClassesError> {
-    let content = REST_CLIENT
-        .get_or_init(reqwest::Client::new)
-        .get(format!(
-            "https://www.googleapis.com/drive/v3/files/{GREED_RULES_DOC_ID}/export"
-        ))
-        .query(&[("key", API_KEY), ("mimeType", RULES_EXPORT_FORMAT)])
-        .send()
-        .await?
-        .text()
-        .await?;
+async fn get_rules(source: &RulesSource) -> Result<String, GetOriginsAndClassesError> {
+    let content = fetch::get_text(
+        source,
+        &format!("{}/export", source.file_url()),
+        &[
+            ("key", source.api_key.as_str()),
+            ("mimeType", source.export_format.as_str()),
+        ],
+    )
+    .await?;
 
     info!("Rules Document: {content}");
 
-    Ok(content
-        .lines()
-        .skip_while(|paragraph| !paragraph.starts_with("Origins"))
-        .skip(1)
-        .map(String::from)
-        .collect())
+    Ok(content)
+}
+
+/**
+ * The rules document and the format it is in, read from disk or fetched from Drive
+ */
+async fn get_rules_content(
+    source: &RulesSource,
+) -> Result<(Vec<u8>, RulesFormat), GetOriginsAndClassesError> {
+    if let Some(path) = &source.local_file {
+        let format = RulesFormat::from_path(path).ok_or_else(|| {
+            GetOriginsAndClassesError::UnsupportedFormat(path.display().to_string())
+        })?;
+        Ok((tokio::fs::read(path).await?, format))
+    } else {
+        // Exports are fetched as text, so DOCX is only read from local files
+        let format = RulesFormat::from_mime_type(&source.export_format)
+            .filter(|format| *format != RulesFormat::Docx)
+            .ok_or_else(|| {
+                GetOriginsAndClassesError::UnsupportedFormat(source.export_format.clone())
+            })?;
+        Ok((get_rules(source).await?.into_bytes(), format))
+    }
 }
 
+pub async fn get_origins_and_classes(
+    source: RulesSource,
+) -> Result<ClassCache, GetOriginsAndClassesError> {
+    let (content, format) = get_rules_content(&source).await?;
+    let rules = parse_rules(&content, format)?;
+    Ok(rules.into_class_cache(Some(get_update_time(source).await?)))
+}
+
+fn parse_rules(
+    content: &[u8],
+    format: RulesFormat,
+) -> Result<ParsedRules, GetOriginsAndClassesError> {
+    let text = || String::from_utf8_lossy(content);
+    match format {
+        RulesFormat::PlainText => parse_plain_text(text().lines().map(String::from).collect()),
+        RulesFormat::Html => document::parse(&document::html::blocks(&text())),
+        RulesFormat::Markdown => document::parse(&document::markdown::blocks(&text())),
+        RulesFormat::Docx => document::parse(&document::docx::blocks(content)?),
+    }
+}
+
+/**
+ * Read the plain text export, where only line prefixes and keywords give away its structure
+ */
 #[allow(clippy::skip_while_next)]
-pub async fn get_origins_and_classes() -> Result<ClassCache, GetOriginsAndClassesError> {
-    let lines = get_rules().await?;
+fn parse_plain_text(mut lines: Vec<String>) -> Result<ParsedRules, GetOriginsAndClassesError> {
+    let origins_start = lines
+        .iter()
+        .position(|paragraph| paragraph.starts_with("Origins"))
+        .ok_or(GetOriginsAndClassesError::FormatChange)?;
+    let glossary = Glossary::from_rules(&lines[..origins_start]);
+    let lines = lines.split_off(origins_start + 1);
     let mut origin_lines = lines.clone().into_iter();
 
-    let mut origins = Vec::<Class>::new();
+    let mut origins = Vec::<Origin>::new();
     let mut line = origin_lines.next();
     while !line
         .as_ref()
@@ -355,22 +564,12 @@ pub async fn get_origins_and_classes() -> Result<ClassCache, GetOriginsAndClasse
             &line.ok_or(GetOriginsAndClassesError::OriginParse)?,
             origin_lines.by_ref(),
         )?;
-        if origin.clone().get_name() == "Human" {
-            line = origin_lines
-                .by_ref()
-                .skip_while(|paragraph| !paragraph.trim().is_empty())
-                .skip_while(|paragraph| paragraph.trim().is_empty())
-                .skip_while(|paragraph| paragraph.starts_with('_'))
-                .skip_while(|paragraph| paragraph.trim().is_empty())
-                .next();
-        } else {
-            line = origin_lines
-                .by_ref()
-                .skip_while(|paragraph| paragraph.trim().is_empty())
-                .skip_while(|paragraph| paragraph.starts_with('_'))
-                .skip_while(|paragraph| paragraph.trim().is_empty())
-                .next();
-        }
+        line = origin_lines
+            .by_ref()
+            .skip_while(|paragraph| paragraph.trim().is_empty())
+            .skip_while(|paragraph| paragraph.starts_with('_'))
+            .skip_while(|paragraph| paragraph.trim().is_empty())
+            .next();
         origins.push(origin);
     }
 
@@ -384,44 +583,68 @@ pub async fn get_origins_and_classes() -> Result<ClassCache, GetOriginsAndClasse
         .as_ref()
         .is_some_and(|line| !line.contains("Idea Bank"))
     {
-        let class = get_class(
+        let mut class = get_class(
             &line.ok_or(GetOriginsAndClassesError::FormatChange)?,
             class_lines.by_ref(),
         )?;
+        // Everything up to the next class is what its Subclasses section lists
+        let mut subclass_lines = vec![];
+        line = loop {
+            match class_lines.next() {
+                Some(next) if !next.contains('(') && !next.contains("Idea Bank") => {
+                    subclass_lines.push(next);
+                }
+                next => break next,
+            }
+        };
+        class.set_subclasses(parse_subclasses(subclass_lines.iter().map(String::as_str)));
         classes.push(class);
-        line = class_lines
-            .by_ref()
-            .skip_while(|line| !line.contains('(') && !line.contains("Idea Bank"))
-            .next();
     }
-    Ok(ClassCache::new(
+
+    let idea_lines = class_lines.collect::<Vec<_>>();
+    let idea_bank = idea_lines
+        .split(|line| line.trim().is_empty())
+        .filter_map(|entry| {
+            let (title, notes) = entry.split_first()?;
+            let notes = notes.iter().map(String::as_str).collect::<Vec<_>>();
+            Some(draft_class(title, &notes))
+        })
+        .collect();
+    Ok(ParsedRules {
         origins,
         classes,
-        Some(get_update_time().await?),
-    ))
+        glossary,
+        idea_bank,
+    })
 }
 
-pub async fn get_update_time() -> Result<i64, GetUpdateTimeError> {
-    let timestamp = REST_CLIENT
-        .get_or_init(reqwest::Client::new)
-        .get(format!(
-            "https://www.googleapis.com/drive/v3/files/{GREED_RULES_DOC_ID}"
-        ))
-        .query(&[("key", API_KEY), ("fields", "modifiedTime")])
-        .send()
-        .await?
-        .json::<GetUpdateTimeResponse>()
-        .await?
+pub async fn get_update_time(source: RulesSource) -> Result<i64, GetUpdateTimeError> {
+    if let Some(path) = &source.local_file {
+        let modified = tokio::fs::metadata(path).await?.modified()?;
+        return Ok(DateTime::<Utc>::from(modified).timestamp());
+    }
+    let response = fetch::get_text(
+        &source,
+        &source.file_url(),
+        &[("key", source.api_key.as_str()), ("fields", "modifiedTime")],
+    )
+    .await?;
+    let timestamp = serde_json::from_str::<GetUpdateTimeResponse>(&response)?
         .modified_time
         .timestamp();
     info!("New timestamp: {timestamp:?}");
     Ok(timestamp)
 }
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug, Error)]
 pub enum GetOriginsAndClassesError {
     #[error("Error in the Reqwest Client: {0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Unexpected response from Google Drive: {0}")]
+    UncachedNotModified(String),
     #[error("The format of the rules doc has changed, please fix parsing")]
     FormatChange,
     #[error("Failed to parse class")]
@@ -430,12 +653,46 @@ pub enum GetOriginsAndClassesError {
     OriginParse,
     #[error("Failed to get Update Time: {0}")]
     UpdateTimeError(#[from] GetUpdateTimeError),
+    #[error("Rules can't be read from \"{0}\"")]
+    UnsupportedFormat(String),
+    #[error("Failed to read the rules file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to read the DOCX file: {0}")]
+    Docx(#[from] DocxError),
 }
 
 #[derive(Debug, Error)]
 pub enum GetUpdateTimeError {
     #[error("Error in the Reqwest Client: {0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Unexpected response from Google Drive: {0}")]
+    UncachedNotModified(String),
+    #[error("Unexpected response from Google Drive: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to read the rules file's modified time: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<FetchError> for GetOriginsAndClassesError {
+    fn from(err: FetchError) -> Self {
+        match err {
+            FetchError::Reqwest(err) => GetOriginsAndClassesError::Reqwest(err),
+            err @ FetchError::UncachedNotModified(_) => {
+                GetOriginsAndClassesError::UncachedNotModified(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<FetchError> for GetUpdateTimeError {
+    fn from(err: FetchError) -> Self {
+        match err {
+            FetchError::Reqwest(err) => GetUpdateTimeError::Reqwest(err),
+            err @ FetchError::UncachedNotModified(_) => {
+                GetUpdateTimeError::UncachedNotModified(err.to_string())
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Copy, Clone)]
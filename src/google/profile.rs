@@ -0,0 +1,492 @@
+use std::path::{Path, PathBuf};
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::document::{RulesFormat, FILE_EXTENSIONS};
+use super::{RulesSource, API_KEY, GOOGLE_API_BASE_URL, GREED_RULES_DOC_ID};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+static DEFAULT_PROFILE_NAME: &str = "Greed";
+static CONFIG_FILE_NAME: &str = "rules.ron";
+
+/**
+ * Export formats the rules parser understands
+ */
+pub static SUPPORTED_EXPORT_FORMATS: &[&str] = &[
+    RulesFormat::PlainText.get_mime_type(),
+    RulesFormat::Html.get_mime_type(),
+    RulesFormat::Markdown.get_mime_type(),
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RulesProfileError {
+    #[error("The profile needs a name")]
+    EmptyName,
+    #[error("There is already a profile named \"{0}\"")]
+    DuplicateName(String),
+    #[error("\"{0}\" is not a Google Drive document ID")]
+    InvalidDocumentId(String),
+    #[error("The rules can't be read from \"{0}\" exports")]
+    UnsupportedExportFormat(String),
+    #[error("\"{0}\" is not an http or https URL")]
+    InvalidBaseUrl(String),
+    #[error("The rules can't be read from \"{0}\" files")]
+    UnsupportedFile(String),
+    #[error("\"{0}\" needs an API key, this build doesn't have one built in")]
+    MissingApiKey(String),
+}
+
+#[derive(Debug, Error)]
+pub enum RulesConfigError {
+    #[error("Error when reading the rules configuration: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+    #[error("Error when writing the rules configuration: {0}")]
+    Serialize(#[from] ron::error::Error),
+    #[error("Error when accessing the rules configuration file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/**
+ * One named rules document and the credentials used to fetch it
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulesProfile {
+    name: String,
+    document_id: String,
+    /// Left empty to use the key the app was built with
+    #[serde(default)]
+    api_key: String,
+    #[serde(default = "default_export_format")]
+    export_format: String,
+    #[serde(default = "default_base_url")]
+    base_url: String,
+    /// Read instead of the Google Drive document when set
+    #[serde(default)]
+    local_file: Option<PathBuf>,
+}
+
+fn default_export_format() -> String {
+    RulesFormat::PlainText.get_mime_type().to_owned()
+}
+
+fn default_base_url() -> String {
+    GOOGLE_API_BASE_URL.to_owned()
+}
+
+impl Default for RulesProfile {
+    fn default() -> Self {
+        RulesProfile {
+            name: DEFAULT_PROFILE_NAME.to_owned(),
+            document_id: GREED_RULES_DOC_ID.to_owned(),
+            api_key: String::new(),
+            export_format: default_export_format(),
+            base_url: default_base_url(),
+            local_file: None,
+        }
+    }
+}
+
+impl RulesProfile {
+    /**
+     * A profile for another document, fetched the same way as the official rules
+     */
+    pub fn new<N: Into<String>, D: Into<String>>(name: N, document_id: D) -> RulesProfile {
+        RulesProfile {
+            name: name.into(),
+            document_id: document_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn document_id_mut(&mut self) -> &mut String {
+        &mut self.document_id
+    }
+
+    pub fn api_key_mut(&mut self) -> &mut String {
+        &mut self.api_key
+    }
+
+    pub fn get_export_format(&self) -> &str {
+        &self.export_format
+    }
+
+    pub fn set_export_format(&mut self, export_format: impl Into<String>) {
+        self.export_format = export_format.into();
+    }
+
+    pub fn base_url_mut(&mut self) -> &mut String {
+        &mut self.base_url
+    }
+
+    pub fn get_local_file(&self) -> Option<&Path> {
+        self.local_file.as_deref()
+    }
+
+    pub fn set_local_file(&mut self, local_file: Option<PathBuf>) {
+        self.local_file = local_file;
+    }
+
+    /**
+     * Link for reading the document in a browser
+     */
+    pub fn get_document_url(&self) -> String {
+        format!(
+            "https://docs.google.com/document/d/{}/edit?usp=sharing",
+            self.document_id.trim()
+        )
+    }
+
+    /**
+     * Where to fetch the rules from, failing when neither the profile nor the build has an
+     * API key for Google Drive
+     */
+    pub fn get_source(&self) -> Result<RulesSource, RulesProfileError> {
+        if let Some(local_file) = &self.local_file {
+            return Ok(RulesSource::local(local_file));
+        }
+        let api_key = if self.api_key.trim().is_empty() {
+            API_KEY.ok_or_else(|| RulesProfileError::MissingApiKey(self.name.clone()))?
+        } else {
+            &self.api_key
+        };
+        Ok(RulesSource::new(
+            &self.base_url,
+            &self.document_id,
+            api_key,
+            &self.export_format,
+        ))
+    }
+
+    /**
+     * Catch mistakes that would make every fetch fail, before trying one
+     */
+    pub fn validate(&self) -> Result<(), RulesProfileError> {
+        if self.name.trim().is_empty() {
+            return Err(RulesProfileError::EmptyName);
+        }
+        if let Some(local_file) = &self.local_file {
+            return match RulesFormat::from_path(local_file) {
+                Some(_) => Ok(()),
+                None => Err(RulesProfileError::UnsupportedFile(
+                    local_file.display().to_string(),
+                )),
+            };
+        }
+        let document_id = self.document_id.trim();
+        if document_id.is_empty()
+            || !document_id.chars().all(|character| {
+                character.is_ascii_alphanumeric() || matches!(character, '-' | '_')
+            })
+        {
+            return Err(RulesProfileError::InvalidDocumentId(document_id.to_owned()));
+        }
+        if !SUPPORTED_EXPORT_FORMATS.contains(&self.export_format.as_str()) {
+            return Err(RulesProfileError::UnsupportedExportFormat(
+                self.export_format.clone(),
+            ));
+        }
+        let base_url = self.base_url.trim();
+        let host = base_url
+            .strip_prefix("https://")
+            .or_else(|| base_url.strip_prefix("http://"));
+        if host.is_none_or(|host| host.trim_matches('/').is_empty()) {
+            return Err(RulesProfileError::InvalidBaseUrl(base_url.to_owned()));
+        }
+        Ok(())
+    }
+}
+
+/**
+ * File extensions a profile's local file can have
+ */
+pub fn supported_file_extensions() -> Vec<&'static str> {
+    FILE_EXTENSIONS
+        .iter()
+        .map(|(extension, _)| *extension)
+        .collect()
+}
+
+/**
+ * Every configured rule set and which one is in use, kept in its own file so it can be
+ * shared between the app and the command line or edited by hand
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulesProfiles {
+    active: String,
+    profiles: Vec<RulesProfile>,
+}
+
+impl Default for RulesProfiles {
+    fn default() -> Self {
+        RulesProfiles {
+            active: DEFAULT_PROFILE_NAME.to_owned(),
+            profiles: vec![RulesProfile::default()],
+        }
+    }
+}
+
+impl RulesProfiles {
+    /**
+     * Where the configuration lives alongside the app's other storage
+     */
+    pub fn default_path() -> Option<PathBuf> {
+        eframe::storage_dir("Greed Console").map(|storage_dir| storage_dir.join(CONFIG_FILE_NAME))
+    }
+
+    /**
+     * Read the configuration, a missing file gives just the official rules
+     */
+    pub fn from_file(path: impl AsRef<Path>) -> Result<RulesProfiles, RulesConfigError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                let mut profiles: RulesProfiles = ron::from_str(&contents)?;
+                if profiles.profiles.is_empty() {
+                    profiles = RulesProfiles::default();
+                }
+                Ok(profiles)
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(RulesProfiles::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /**
+     * Read the configuration from its default path, falling back to the official rules
+     */
+    pub fn load() -> RulesProfiles {
+        RulesProfiles::default_path()
+            .map(RulesProfiles::from_file)
+            .transpose()
+            .unwrap_or_else(|err| {
+                tracing::error!("{err}");
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), RulesConfigError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(
+            path,
+            ron::ser::to_string_pretty(self, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_profiles(&self) -> &[RulesProfile] {
+        &self.profiles
+    }
+
+    pub fn profile_mut(&mut self, index: usize) -> Option<&mut RulesProfile> {
+        self.profiles.get_mut(index)
+    }
+
+    /**
+     * The profile rules are fetched with, the first one if the active profile was removed
+     */
+    pub fn get_active(&self) -> &RulesProfile {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == self.active)
+            .or_else(|| self.profiles.first())
+            .expect("There is always at least one rules profile")
+    }
+
+    pub fn is_active(&self, index: usize) -> bool {
+        self.profiles
+            .get(index)
+            .is_some_and(|profile| profile == self.get_active())
+    }
+
+    /**
+     * Switch to another profile, returning whether it wasn't already active
+     */
+    pub fn set_active(&mut self, index: usize) -> bool {
+        match self.profiles.get(index) {
+            Some(profile) if !self.is_active(index) => {
+                self.active = profile.name.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /**
+     * Check a profile on its own and against the names of the others
+     */
+    pub fn validate(&self, index: usize) -> Result<(), RulesProfileError> {
+        let Some(profile) = self.profiles.get(index) else {
+            return Ok(());
+        };
+        profile.validate()?;
+        let name = profile.name.trim();
+        if self
+            .profiles
+            .iter()
+            .enumerate()
+            .any(|(other, profile)| other != index && profile.name.trim() == name)
+        {
+            return Err(RulesProfileError::DuplicateName(name.to_owned()));
+        }
+        Ok(())
+    }
+
+    /**
+     * Add a profile with an unused name, returning its index
+     */
+    pub fn add(&mut self, mut profile: RulesProfile) -> usize {
+        let base_name = profile.name.clone();
+        let mut number = 1;
+        while self
+            .profiles
+            .iter()
+            .any(|existing| existing.name == profile.name)
+        {
+            number += 1;
+            profile.name = format!("{base_name} {number}");
+        }
+        self.profiles.push(profile);
+        self.profiles.len() - 1
+    }
+
+    /**
+     * Remove a profile, the last one is always kept
+     */
+    pub fn remove(&mut self, index: usize) -> Option<RulesProfile> {
+        if self.profiles.len() <= 1 || index >= self.profiles.len() {
+            return None;
+        }
+        let was_active = self.is_active(index);
+        let profile = self.profiles.remove(index);
+        if was_active {
+            self.active = self.profiles[0].name.clone();
+        }
+        Some(profile)
+    }
+
+    /**
+     * Rename a profile, keeping it active if it was
+     */
+    pub fn rename(&mut self, index: usize, name: impl Into<String>) {
+        let was_active = self.is_active(index);
+        if let Some(profile) = self.profiles.get_mut(index) {
+            profile.name = name.into();
+            if was_active {
+                self.active = profile.name.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_profile() {
+        assert_eq!(RulesProfile::default().validate(), Ok(()));
+
+        let mut profile = RulesProfile::new("Homebrew", "not a/document");
+        assert_eq!(
+            profile.validate(),
+            Err(RulesProfileError::InvalidDocumentId(
+                "not a/document".to_owned()
+            ))
+        );
+
+        *profile.document_id_mut() = "abc-123_XYZ".to_owned();
+        *profile.base_url_mut() = "ftp://example.com".to_owned();
+        assert!(matches!(
+            profile.validate(),
+            Err(RulesProfileError::InvalidBaseUrl(_))
+        ));
+
+        *profile.base_url_mut() = "http://localhost:8080/".to_owned();
+        profile.set_export_format("application/zip");
+        assert!(matches!(
+            profile.validate(),
+            Err(RulesProfileError::UnsupportedExportFormat(_))
+        ));
+
+        profile.set_local_file(Some(PathBuf::from("rules.pdf")));
+        assert_eq!(
+            profile.validate(),
+            Err(RulesProfileError::UnsupportedFile("rules.pdf".to_owned()))
+        );
+        profile.set_local_file(Some(PathBuf::from("Rules.DOCX")));
+        assert_eq!(profile.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_profiles_round_trip() {
+        let path = std::env::temp_dir().join(format!("greed_rules_{}.ron", std::process::id()));
+        let mut profiles = RulesProfiles::from_file(&path).unwrap();
+        assert_eq!(profiles, RulesProfiles::default());
+
+        let homebrew = profiles.add(RulesProfile::new("Greed", "homebrew"));
+        assert_eq!(profiles.get_profiles()[homebrew].get_name(), "Greed 2");
+        assert!(profiles.set_active(homebrew));
+        assert!(!profiles.set_active(homebrew));
+        profiles.to_file(&path).unwrap();
+
+        let loaded = RulesProfiles::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get_active().document_id, "homebrew");
+
+        let mut loaded = loaded;
+        loaded.rename(0, "Greed 2");
+        assert_eq!(
+            loaded.validate(0),
+            Err(RulesProfileError::DuplicateName("Greed 2".to_owned()))
+        );
+        assert!(loaded.remove(homebrew).is_some());
+        assert!(loaded.remove(0).is_none());
+        assert_eq!(loaded.get_active().document_id, GREED_RULES_DOC_ID);
+    }
+
+    #[test]
+    fn test_api_key_falls_back_to_built_in() {
+        let mut profile = RulesProfile::default();
+        match API_KEY {
+            Some(api_key) => assert_eq!(profile.get_source().unwrap().api_key, api_key),
+            None => assert_eq!(
+                profile.get_source(),
+                Err(RulesProfileError::MissingApiKey(
+                    profile.get_name().to_owned()
+                ))
+            ),
+        }
+
+        *profile.api_key_mut() = " my-key ".to_owned();
+        assert_eq!(profile.get_source().unwrap().api_key, "my-key");
+
+        profile.set_local_file(Some(PathBuf::from("rules.md")));
+        assert!(profile.get_source().is_ok());
+    }
+}
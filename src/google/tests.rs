@@ -0,0 +1,620 @@
+use super::*;
+use crate::model::ids::{ClassId, OriginId};
+use crate::model::sheets::Character;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::header::{ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::routing::get;
+use axum::Router;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+static RULES_EXPORT: &str = include_str!("fixtures/rules.txt");
+static RULES_HTML: &str = include_str!("fixtures/rules.html");
+static RULES_MARKDOWN: &str = include_str!("fixtures/rules.md");
+static RULES_DOCX: &[u8] = include_bytes!("fixtures/rules.docx");
+static MODIFIED_TIME: &str = include_str!("fixtures/modified_time.json");
+static MODIFIED_TIMESTAMP: i64 = 1_710_408_413;
+static TEST_KEY: &str = "test-key";
+static FORBIDDEN_BODY: &str =
+    r#"{"error": {"code": 403, "message": "The request is missing a valid API key."}}"#;
+
+#[derive(Debug, Clone)]
+struct MockResponse {
+    status: StatusCode,
+    body: String,
+    delay: Duration,
+    /// Number of requests answered with `503 Service Unavailable` first
+    failures: usize,
+    request_count: Arc<AtomicUsize>,
+    etag: Option<&'static str>,
+    not_modified_count: Arc<AtomicUsize>,
+}
+
+impl MockResponse {
+    fn ok(body: &str) -> MockResponse {
+        MockResponse {
+            status: StatusCode::OK,
+            body: body.to_owned(),
+            delay: Duration::ZERO,
+            failures: 0,
+            request_count: Arc::default(),
+            etag: None,
+            not_modified_count: Arc::default(),
+        }
+    }
+
+    fn status(status: StatusCode, body: &str) -> MockResponse {
+        MockResponse {
+            status,
+            ..MockResponse::ok(body)
+        }
+    }
+
+    fn delayed(mut self, delay: Duration) -> MockResponse {
+        self.delay = delay;
+        self
+    }
+
+    fn failing_first(self, failures: usize) -> MockResponse {
+        MockResponse { failures, ..self }
+    }
+
+    fn with_etag(self, etag: &'static str) -> MockResponse {
+        MockResponse {
+            etag: Some(etag),
+            ..self
+        }
+    }
+}
+
+/**
+ * Stand-in for the two Google Drive endpoints the rules are fetched from
+ */
+#[derive(Debug, Clone)]
+struct MockDrive {
+    export: MockResponse,
+    export_format: RulesFormat,
+    metadata: MockResponse,
+}
+
+impl Default for MockDrive {
+    fn default() -> Self {
+        MockDrive {
+            export: MockResponse::ok(RULES_EXPORT),
+            export_format: RulesFormat::PlainText,
+            metadata: MockResponse::ok(MODIFIED_TIME),
+        }
+    }
+}
+
+async fn respond(
+    response: &MockResponse,
+    id: &str,
+    query: &HashMap<String, String>,
+    request_headers: &HeaderMap,
+) -> (StatusCode, HeaderMap, String) {
+    let mut headers = HeaderMap::new();
+    if query.get("key").map(String::as_str) != Some(TEST_KEY) {
+        return (StatusCode::FORBIDDEN, headers, FORBIDDEN_BODY.to_owned());
+    }
+    if id != GREED_RULES_DOC_ID {
+        return (StatusCode::NOT_FOUND, headers, String::new());
+    }
+    if response.request_count.fetch_add(1, Ordering::SeqCst) < response.failures {
+        return (StatusCode::SERVICE_UNAVAILABLE, headers, String::new());
+    }
+    tokio::time::sleep(response.delay).await;
+    if let Some(etag) = response.etag {
+        headers.insert(ETAG, HeaderValue::from_static(etag));
+        if request_headers
+            .get(IF_NONE_MATCH)
+            .is_some_and(|value| value == etag)
+        {
+            response.not_modified_count.fetch_add(1, Ordering::SeqCst);
+            return (StatusCode::NOT_MODIFIED, headers, String::new());
+        }
+    }
+    (response.status, headers, response.body.clone())
+}
+
+/**
+ * Serve the mock on a free local port, returning a source pointing at it
+ */
+async fn serve(drive: MockDrive) -> RulesSource {
+    let router = Router::new()
+        .route(
+            "/drive/v3/files/:id/export",
+            get(
+                |State(drive): State<MockDrive>,
+                 Path(id): Path<String>,
+                 Query(query): Query<HashMap<String, String>>,
+                 headers: HeaderMap| async move {
+                    assert_eq!(
+                        query.get("mimeType").map(String::as_str),
+                        Some(drive.export_format.get_mime_type())
+                    );
+                    respond(&drive.export, &id, &query, &headers).await
+                },
+            ),
+        )
+        .route(
+            "/drive/v3/files/:id",
+            get(
+                |State(drive): State<MockDrive>,
+                 Path(id): Path<String>,
+                 Query(query): Query<HashMap<String, String>>,
+                 headers: HeaderMap| async move {
+                    respond(&drive.metadata, &id, &query, &headers).await
+                },
+            ),
+        );
+    let export_format = drive.export_format.get_mime_type();
+    let router = router.with_state(drive);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    RulesSource {
+        retry: RetryPolicy {
+            attempts: 3,
+            initial_delay: Duration::from_millis(10),
+        },
+        ..RulesSource::new(
+            &format!("http://{address}/"),
+            GREED_RULES_DOC_ID,
+            TEST_KEY,
+            export_format,
+        )
+    }
+}
+
+fn expect_status(error: &GetOriginsAndClassesError, status: StatusCode) {
+    match error {
+        GetOriginsAndClassesError::Reqwest(error)
+        | GetOriginsAndClassesError::UpdateTimeError(GetUpdateTimeError::Reqwest(error)) => {
+            assert_eq!(
+                error.status().map(|status| status.as_u16()),
+                Some(status.as_u16())
+            );
+        }
+        error => panic!("Expected a {status} response, got {error:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_and_parse_rules() {
+    let source = serve(MockDrive::default()).await;
+
+    let class_cache = get_origins_and_classes(source.clone()).await.unwrap();
+
+    assert_eq!(
+        class_cache
+            .get_origins()
+            .iter()
+            .map(|origin| origin.get_name())
+            .collect::<Vec<_>>(),
+        ["Human", "Elf"]
+    );
+    let elf = class_cache.find_origin("Elf").unwrap();
+    assert_eq!(elf.get_utilities()[0].get_name(), "Keen Senses");
+    assert_eq!(
+        elf.get_passives()[0].get_description(),
+        "Gain 1 Defense while dodging."
+    );
+    assert_eq!(elf.get_special_action().unwrap().get_name(), "Starlight");
+
+    assert_eq!(
+        class_cache
+            .get_classes()
+            .iter()
+            .map(|class| class.get_name())
+            .collect::<Vec<_>>(),
+        ["Fighter", "Knight"]
+    );
+    let fighter = class_cache.find_class("Fighter").unwrap();
+    let knight = class_cache.find_class("Knight").unwrap();
+    assert_eq!(*fighter.get_level(), Some(1));
+    assert_eq!(*knight.get_level(), Some(2));
+    assert_eq!(
+        fighter.get_special_action().get_description(),
+        "Take another primary action."
+    );
+    assert!(!knight.get_class_available(&[]));
+    assert!(knight.get_class_available(std::slice::from_ref(fighter)));
+
+    assert_eq!(
+        class_cache.get_glossary().get_definition("Power"),
+        Some("Added to every attack roll.")
+    );
+    assert_eq!(
+        class_cache.get_cache_update_time(),
+        Some(MODIFIED_TIMESTAMP)
+    );
+    assert_eq!(get_update_time(source).await.unwrap(), MODIFIED_TIMESTAMP);
+}
+
+#[tokio::test]
+async fn test_wrong_api_key() {
+    let source = serve(MockDrive::default()).await;
+    let source = RulesSource {
+        api_key: "wrong-key".to_owned(),
+        ..source
+    };
+
+    let error = get_origins_and_classes(source).await.unwrap_err();
+
+    expect_status(&error, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_forbidden_export() {
+    let source = serve(MockDrive {
+        export: MockResponse::status(StatusCode::FORBIDDEN, FORBIDDEN_BODY),
+        ..Default::default()
+    })
+    .await;
+
+    let error = get_origins_and_classes(source).await.unwrap_err();
+
+    expect_status(&error, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_forbidden_update_time() {
+    let source = serve(MockDrive {
+        metadata: MockResponse::status(StatusCode::FORBIDDEN, FORBIDDEN_BODY),
+        ..Default::default()
+    })
+    .await;
+
+    let error = get_origins_and_classes(source).await.unwrap_err();
+
+    expect_status(&error, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_export_timeout() {
+    let source = serve(MockDrive {
+        export: MockResponse::ok(RULES_EXPORT).delayed(Duration::from_secs(5)),
+        ..Default::default()
+    })
+    .await;
+    let source = RulesSource {
+        timeout: Duration::from_millis(100),
+        ..source
+    };
+
+    let error = get_origins_and_classes(source).await.unwrap_err();
+
+    assert!(
+        matches!(&error, GetOriginsAndClassesError::Reqwest(error) if error.is_timeout()),
+        "Expected a timeout, got {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_update_time() {
+    let source = serve(MockDrive {
+        metadata: MockResponse::ok(r#"{"modifiedTime": "yesterday"#),
+        ..Default::default()
+    })
+    .await;
+
+    let error = get_origins_and_classes(source.clone()).await.unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            GetOriginsAndClassesError::UpdateTimeError(GetUpdateTimeError::Json(_))
+        ),
+        "Expected a decoding error, got {error:?}"
+    );
+    assert!(get_update_time(source).await.is_err());
+}
+
+#[tokio::test]
+async fn test_truncated_document() {
+    let cut_off_mid_class = RULES_EXPORT
+        .lines()
+        .take_while(|line| *line != "Hit something with a weapon.")
+        .chain(["Hit something with a weapon."])
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(cut_off_mid_class.contains("Origins"));
+    let source = serve(MockDrive {
+        export: MockResponse::ok(&cut_off_mid_class),
+        ..Default::default()
+    })
+    .await;
+    assert!(matches!(
+        get_origins_and_classes(source).await,
+        Err(GetOriginsAndClassesError::ClassParse)
+    ));
+
+    let cut_off_before_origins = &RULES_EXPORT[..RULES_EXPORT.find("Origins").unwrap()];
+    let source = serve(MockDrive {
+        export: MockResponse::ok(cut_off_before_origins),
+        ..Default::default()
+    })
+    .await;
+    assert!(matches!(
+        get_origins_and_classes(source).await,
+        Err(GetOriginsAndClassesError::FormatChange)
+    ));
+}
+
+#[tokio::test]
+async fn test_retries_transient_failures() {
+    let source = serve(MockDrive {
+        export: MockResponse::ok(RULES_EXPORT).failing_first(2),
+        ..Default::default()
+    })
+    .await;
+
+    let class_cache = get_origins_and_classes(source).await.unwrap();
+
+    assert_eq!(class_cache.get_classes().len(), 2);
+
+    let source = serve(MockDrive {
+        export: MockResponse::ok(RULES_EXPORT).failing_first(3),
+        ..Default::default()
+    })
+    .await;
+
+    let error = get_origins_and_classes(source).await.unwrap_err();
+
+    expect_status(&error, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_unchanged_response_is_reused() {
+    let metadata = MockResponse::ok(MODIFIED_TIME).with_etag("\"v1\"");
+    let not_modified_count = metadata.not_modified_count.clone();
+    let source = serve(MockDrive {
+        metadata,
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(
+        get_update_time(source.clone()).await.unwrap(),
+        MODIFIED_TIMESTAMP
+    );
+    assert_eq!(not_modified_count.load(Ordering::SeqCst), 0);
+    assert_eq!(get_update_time(source).await.unwrap(), MODIFIED_TIMESTAMP);
+    assert_eq!(not_modified_count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_not_modified_without_cache_is_an_error() {
+    let source = serve(MockDrive {
+        metadata: MockResponse::status(StatusCode::NOT_MODIFIED, ""),
+        ..Default::default()
+    })
+    .await;
+
+    let error = get_update_time(source).await.unwrap_err();
+
+    assert!(
+        matches!(error, GetUpdateTimeError::UncachedNotModified(_)),
+        "Expected an uncached 304, got {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_response_cache_is_stored_without_keys() {
+    let source = serve(MockDrive {
+        metadata: MockResponse::ok(MODIFIED_TIME).with_etag("\"stored\""),
+        ..Default::default()
+    })
+    .await;
+    get_update_time(source).await.unwrap();
+
+    let stored = ron::to_string(&fetch::get_response_cache()).unwrap();
+    assert!(stored.contains("stored"));
+    assert!(!stored.contains(TEST_KEY));
+    let restored: fetch::ResponseCache = ron::from_str(&stored).unwrap();
+    fetch::restore_response_cache(restored.clone());
+    assert_eq!(fetch::get_response_cache(), restored);
+}
+
+#[test]
+fn test_formats_parse_the_same() {
+    // Compared serialized, as class requirements are never equal as trait objects
+    let serialize = |class_cache: &ClassCache| ron::to_string(class_cache).unwrap();
+    let expected = serialize(
+        &parse_rules(RULES_EXPORT.as_bytes(), RulesFormat::PlainText)
+            .unwrap()
+            .into_class_cache(None),
+    );
+
+    for (content, format) in [
+        (RULES_HTML.as_bytes(), RulesFormat::Html),
+        (RULES_MARKDOWN.as_bytes(), RulesFormat::Markdown),
+        (RULES_DOCX, RulesFormat::Docx),
+    ] {
+        let class_cache = parse_rules(content, format).unwrap().into_class_cache(None);
+        assert_eq!(serialize(&class_cache), expected, "{format:?} rules differ");
+    }
+}
+
+#[test]
+fn test_document_without_actions() {
+    let without_special = RULES_MARKDOWN.replace("**Special**", "**Notes**");
+
+    assert!(matches!(
+        parse_rules(without_special.as_bytes(), RulesFormat::Markdown),
+        Err(GetOriginsAndClassesError::OriginParse)
+    ));
+    assert!(matches!(
+        parse_rules(b"not a zip archive", RulesFormat::Docx),
+        Err(GetOriginsAndClassesError::Docx(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_fetch_html_export() {
+    let source = serve(MockDrive {
+        export: MockResponse::ok(RULES_HTML),
+        export_format: RulesFormat::Html,
+        ..Default::default()
+    })
+    .await;
+
+    let class_cache = get_origins_and_classes(source).await.unwrap();
+
+    assert_eq!(class_cache.get_classes().len(), 2);
+    assert_eq!(
+        class_cache.get_cache_update_time(),
+        Some(MODIFIED_TIMESTAMP)
+    );
+}
+
+#[tokio::test]
+async fn test_read_local_file() {
+    let path = std::env::temp_dir().join(format!("greed_rules_{}.docx", std::process::id()));
+    std::fs::write(&path, RULES_DOCX).unwrap();
+
+    let class_cache = get_origins_and_classes(RulesSource::local(&path)).await;
+    std::fs::remove_file(&path).unwrap();
+
+    let class_cache = class_cache.unwrap();
+    assert_eq!(
+        class_cache
+            .get_classes()
+            .iter()
+            .map(|class| class.get_name())
+            .collect::<Vec<_>>(),
+        ["Fighter", "Knight"]
+    );
+    assert!(class_cache.get_cache_update_time().is_some());
+
+    let unsupported = get_origins_and_classes(RulesSource::local("rules.pdf")).await;
+    assert!(matches!(
+        unsupported,
+        Err(GetOriginsAndClassesError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn test_class_heading_beyond_level_five() {
+    let (name, level, requirements) = parse_class_heading("Champion (VI) Req: Any Level IX \"Kn\"");
+    assert_eq!(name, "Champion");
+    assert_eq!(level, Some(6));
+
+    let knight = |level| {
+        Class::new(
+            format!("Knight {level}"),
+            Some(level),
+            vec![],
+            vec![],
+            PrimaryAction::new(String::new(), String::new()),
+            SecondaryAction::new(String::new(), String::new()),
+            SpecialAction::new(String::new(), String::new()),
+            None,
+        )
+    };
+    let requirements = requirements.unwrap();
+    assert!(!requirements.meets_requirement(&[knight(8)]));
+    assert!(requirements.meets_requirement(&[knight(9)]));
+}
+
+#[test]
+fn test_subclasses_and_idea_bank() {
+    let class_cache = parse_rules(RULES_EXPORT.as_bytes(), RulesFormat::PlainText)
+        .unwrap()
+        .into_class_cache(None);
+    let subclasses = class_cache
+        .get_classes()
+        .iter()
+        .map(|class| (class.get_name(), class.get_subclasses().to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        subclasses,
+        [
+            ("Fighter".to_owned(), vec!["Knight".to_owned()]),
+            ("Knight".to_owned(), vec![])
+        ]
+    );
+    assert!(class_cache.get_subclass_report().is_empty());
+
+    let idea_bank = class_cache.get_idea_bank();
+    assert_eq!(idea_bank.len(), 2);
+    assert_eq!(idea_bank[0].get_name(), "Maybe a bard?");
+    assert_eq!(idea_bank[0].get_level(), None);
+    assert_eq!(idea_bank[1].get_name(), "Bard");
+    assert_eq!(idea_bank[1].get_level(), Some(2));
+    assert_eq!(idea_bank[1].get_requirements(), Some("Fighter"));
+    assert_eq!(
+        idea_bank[1].get_notes(),
+        "Inspires allies with songs of greed.\nNeeds a special action."
+    );
+    assert!(idea_bank[1].matches_search("SONGS"));
+}
+
+#[test]
+fn test_origin_rules_come_from_the_rules() {
+    let rules = RULES_EXPORT.replace(
+        "Elf\nUtility",
+        "Changeling\nChangelings take two classes instead of an origin ability.\nBonus Classes: 2\n\nElf\nUtility",
+    );
+    let class_cache = parse_rules(rules.as_bytes(), RulesFormat::PlainText)
+        .unwrap()
+        .into_class_cache(None);
+    let origin = |name: &str| class_cache.find_origin(name).unwrap();
+    assert_eq!(origin("Human").get_rules(), OriginRules::new(1));
+    assert_eq!(origin("Changeling").get_rules(), OriginRules::new(2));
+    assert_eq!(origin("Elf").get_rules(), OriginRules::default());
+    assert!(!origin("Human").has_abilities());
+    assert!(!origin("Changeling").has_abilities());
+    assert!(origin("Elf").has_abilities());
+
+    let mut character = Character::default();
+    character.replace_origin(Some(OriginId::from_name("Changeling")));
+    for class in ["Fighter", "Knight"] {
+        character.add_class(ClassId::from_name(class));
+    }
+    assert_eq!(character.get_level(&class_cache), 0);
+    assert_eq!(character.get_ability_sources(&class_cache).len(), 2);
+}
+
+#[test]
+fn test_origins_without_bonus_classes_take_one() {
+    let rules = RULES_EXPORT.replace("Bonus Classes: 1\n", "");
+    let class_cache = parse_rules(rules.as_bytes(), RulesFormat::PlainText)
+        .unwrap()
+        .into_class_cache(None);
+    let human = class_cache.find_origin("Human").unwrap();
+    assert!(!human.has_abilities());
+    assert_eq!(human.get_rules(), OriginRules::new(1));
+
+    let markdown = RULES_MARKDOWN.replace("Bonus Classes: 1\n", "");
+    let class_cache = parse_rules(markdown.as_bytes(), RulesFormat::Markdown)
+        .unwrap()
+        .into_class_cache(None);
+    assert_eq!(
+        class_cache.find_origin("Human").unwrap().get_rules(),
+        OriginRules::new(1)
+    );
+}
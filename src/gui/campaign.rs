@@ -1,18 +1,38 @@
-use std::{cell::RefCell, ffi::OsString, path::Path, rc::Rc};
+use std::{
+    cell::RefCell,
+    ffi::OsString,
+    path::Path,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use tracing::info;
+use tracing::{info, warn};
 
+use crate::api::snapshot::{CampaignSnapshot, GameStateSnapshot, SpecialSnapshot};
+use crate::api::{ApiAction, ApiActionError};
 use crate::model::{
-    actions::{PrimaryAction, SecondaryAction},
-    classes::{Class, ClassCache, ClassPassive, ClassUtility},
+    actions::{PrimaryAction, SecondaryAction, SpecialAction},
+    classes::{Class, ClassCache, ClassPassive, ClassUtility, Origin},
+    comparison::AbilityCategory,
     game_state::GameState,
-    save::{Save, SaveToFileError, SaveWithPath},
+    glossary::TermIndex,
+    ids::ClassId,
+    planner::{reachable_builds, LevelPlan, PlanError, MAX_PLANNED_BUILDS},
+    save::{PlayState, Save, SaveToFileError, SaveWithPath},
+    validation::{validate_save, SaveIssue, SaveRepair},
 };
 
+use super::commands::AppCommand;
+use super::i18n::{self, t};
+use super::markdown;
+use super::theme::ColorScheme;
+use super::widgets::accessible::{ability_label, action_button, focusable_label, menu_section};
 use super::widgets::panels::StatsPanel;
+use crate::util::LevelNumerals;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CampaignGui {
+    campaign_id: usize,
     game_state: GameState,
     current_save: SaveWithPath,
     utilities: Vec<ClassUtility>,
@@ -20,18 +40,35 @@ pub struct CampaignGui {
     primary_actions: Vec<PrimaryAction>,
     secondary_actions: Vec<SecondaryAction>,
     character_classes: Vec<Class>,
-    character_origin: Option<Class>,
+    character_origin: Option<Origin>,
     class_cache: Rc<RefCell<ClassCache>>,
     description_hovering: bool,
     track_turns: bool,
+    save_issues: Vec<SaveIssue>,
+    planner_levels_ahead: usize,
+    planner_custom_target: Vec<ClassId>,
+    planner_error: Option<PlanError>,
+    undo_history: Vec<(GameState, PlayState)>,
+    notes_page: usize,
+    notes_editing: bool,
+    notes_search: String,
+    term_index: TermIndex,
 }
 
+/// How many steps back Undo can go per campaign
+const UNDO_LIMIT: usize = 50;
+
+/// Source of campaign ids, never reused while the app is running
+static NEXT_CAMPAIGN_ID: AtomicUsize = AtomicUsize::new(0);
+
 impl CampaignGui {
     pub fn new_refreshable(
         current_save: SaveWithPath,
         class_cache: Rc<RefCell<ClassCache>>,
     ) -> CampaignGui {
+        let term_index = TermIndex::new(&class_cache.borrow());
         CampaignGui {
+            campaign_id: NEXT_CAMPAIGN_ID.fetch_add(1, Ordering::Relaxed),
             game_state: GameState::default(),
             current_save,
             utilities: vec![],
@@ -43,40 +80,63 @@ impl CampaignGui {
             class_cache,
             description_hovering: true,
             track_turns: true,
+            save_issues: vec![],
+            planner_levels_ahead: 1,
+            planner_custom_target: vec![],
+            planner_error: None,
+            undo_history: vec![],
+            notes_page: 0,
+            notes_editing: false,
+            notes_search: String::new(),
+            term_index,
         }
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        colors: &ColorScheme,
+        accessible: bool,
+        level_numerals: LevelNumerals,
+    ) {
         ui.vertical(|ui| {
-            self.campaign_menu(ui);
+            self.campaign_menu(ui, accessible, level_numerals);
+
+            if !self.save_issues.is_empty() {
+                self.repair_panel(ui);
+            }
+
+            if self.current_save.get_save().get_plan().is_some() {
+                self.plan_panel(ui);
+            }
 
             egui::ScrollArea::both().show(ui, |ui| {
                 ui.horizontal(|ui| {
                     if !self.utilities.is_empty() {
                         ui.vertical(|ui| {
-                            self.utility_panel(ui);
+                            self.utility_panel(ui, colors, accessible);
                         });
                     }
                     if !self.passives.is_empty() {
                         ui.vertical(|ui| {
-                            self.passive_panel(ui);
+                            self.passive_panel(ui, colors, accessible);
                         });
                     }
                     if !self.primary_actions.is_empty() {
                         ui.vertical(|ui| {
-                            self.primary_panel(ui);
+                            self.primary_panel(ui, colors, accessible);
                         });
                     }
 
                     if !self.secondary_actions.is_empty() {
                         ui.vertical(|ui| {
-                            self.secondary_panel(ui);
+                            self.secondary_panel(ui, colors, accessible);
                         });
                     }
 
                     if !(self.game_state.get_special_actions().is_empty()) {
                         ui.vertical(|ui| {
-                            self.special_panel(ui);
+                            self.special_panel(ui, colors, accessible);
                         });
                     }
                 });
@@ -84,22 +144,22 @@ impl CampaignGui {
                 if !(self.primary_actions.is_empty()
                     && self.secondary_actions.is_empty()
                     && self.game_state.get_special_actions().is_empty())
-                    && ui
-                        .add_enabled(
-                            self.game_state.get_inspiration_usable(),
-                            egui::Button::new("Use Inspiration"),
-                        )
-                        .clicked()
+                    && action_button(
+                        ui,
+                        accessible,
+                        self.description_hovering,
+                        egui::RichText::new(t!("campaign.use_inspiration")),
+                        &[],
+                        self.game_state.get_inspiration_usable(),
+                    )
                 {
+                    self.record_undo();
                     self.game_state.use_inspiration();
                 }
             });
 
-            ui.label("Notes:");
-            ui.add_sized(
-                ui.available_size(),
-                egui::TextEdit::multiline(self.current_save.get_save_mut().get_notes_mut()),
-            );
+            focusable_label(ui, accessible, t!("campaign.notes"));
+            self.notes_panel(ui, accessible);
         });
         self.current_save
             .get_save_mut()
@@ -109,226 +169,812 @@ impl CampaignGui {
             .set_battle_defense(self.game_state.get_battle_defense());
     }
 
-    pub fn get_level(&self) -> usize {
-        let class_count = self.character_classes.len();
-        if self
-            .character_origin
-            .as_ref()
-            .is_some_and(|origin| origin.get_name() == "Human")
-        {
-            if class_count < 1 {
-                0
-            } else {
-                class_count - 1
+    /**
+     * Walk through each problem with the save, offering fixes with likely renames first
+     */
+    fn repair_panel(&mut self, ui: &mut egui::Ui) {
+        let mut chosen_repairs = vec![];
+        ui.group(|ui| {
+            egui::CollapsingHeader::new(t!(
+                "repair.header",
+                count = i18n::number(self.save_issues.len())
+            ))
+            .id_source("repair_panel")
+            .default_open(true)
+            .show(ui, |ui| {
+                for issue in &self.save_issues {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(save_issue(issue));
+                        for repair in issue.repairs() {
+                            if ui.button(save_repair(&repair)).clicked() {
+                                chosen_repairs.push(repair);
+                            }
+                        }
+                    });
+                }
+                if ui
+                    .button(t!("repair.apply_suggested"))
+                    .on_hover_text(t!("repair.apply_suggested_hover"))
+                    .clicked()
+                {
+                    chosen_repairs.extend(
+                        self.save_issues
+                            .iter()
+                            .filter_map(|issue| issue.repairs().into_iter().next()),
+                    );
+                }
+            });
+        });
+        if !chosen_repairs.is_empty() {
+            self.apply_repairs(&chosen_repairs);
+        }
+    }
+
+    fn planner_menu(&mut self, ui: &mut egui::Ui) {
+        ui.set_min_width(250.0);
+        let character = self.current_save.get_save().get_character();
+        let class_cache_rc = self.class_cache.clone();
+        let class_cache = class_cache_rc.borrow();
+        let mut new_plan = None;
+
+        ui.add(
+            egui::Slider::new(&mut self.planner_levels_ahead, 1..=3)
+                .text(t!("planner.levels_ahead")),
+        );
+        let builds = reachable_builds(&character, &class_cache, self.planner_levels_ahead);
+        if builds.get_builds().is_empty() {
+            ui.label(t!("planner.no_classes"));
+        }
+        if builds.is_truncated() {
+            ui.label(t!(
+                "planner.truncated",
+                count = i18n::number(MAX_PLANNED_BUILDS)
+            ));
+        }
+        egui::ScrollArea::vertical()
+            .id_source("planner_builds")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for build in builds.get_builds() {
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("planner.pin")).clicked() {
+                            new_plan = Some(build.clone());
+                        }
+                        let names = build
+                            .iter()
+                            .map(|class_id| class_cache.get_class_name(class_id))
+                            .collect::<Vec<_>>();
+                        ui.label(names.join(&t!("planner.then")));
+                    });
+                }
+            });
+
+        ui.separator();
+        ui.label(t!("planner.custom_target"));
+        egui::ComboBox::from_id_source("planner_custom_class")
+            .selected_text(t!("planner.add_class"))
+            .show_ui(ui, |ui| {
+                for class in class_cache.get_classes() {
+                    let class_id = class.get_id();
+                    if !self.planner_custom_target.contains(&class_id)
+                        && !character.get_classes().contains(&class_id)
+                        && ui.selectable_label(false, class.get_name()).clicked()
+                    {
+                        self.planner_custom_target.push(class_id);
+                    }
+                }
+            });
+        self.planner_custom_target.retain(|class_id| {
+            ui.horizontal(|ui| {
+                ui.label(class_cache.get_class_name(class_id));
+                !ui.small_button(t!("planner.remove")).clicked()
+            })
+            .inner
+        });
+        if !self.planner_custom_target.is_empty() && ui.button(t!("planner.pin_custom")).clicked() {
+            new_plan = Some(self.planner_custom_target.clone());
+        }
+        if let Some(err) = &self.planner_error {
+            ui.colored_label(ui.visuals().error_fg_color, plan_error(err, &class_cache));
+        }
+
+        if let Some(target) = new_plan {
+            match LevelPlan::new(&character, &class_cache, target) {
+                Ok(plan) => {
+                    info!("Pinned level plan: {:?}", plan.get_steps());
+                    self.current_save.get_save_mut().set_plan(Some(plan));
+                    self.planner_custom_target.clear();
+                    self.planner_error = None;
+                    ui.close_menu();
+                }
+                Err(err) => self.planner_error = Some(err),
+            }
+        }
+    }
+
+    /**
+     * Page tabs, search and either the Markdown view or the editor for the open page
+     */
+    fn notes_panel(&mut self, ui: &mut egui::Ui, accessible: bool) {
+        let battle = self.current_save.get_save().get_battle();
+        let notebook = self.current_save.get_save_mut().get_notebook_mut();
+        ui.horizontal_wrapped(|ui| {
+            for (index, page) in notebook.get_pages().iter().enumerate() {
+                let response = ui.selectable_label(self.notes_page == index, page.get_title());
+                let response = match page.get_battle() {
+                    Some(page_battle) => response.on_hover_text(t!(
+                        "notes.session_hover",
+                        battle = i18n::number(page_battle)
+                    )),
+                    None => response,
+                };
+                if response.clicked() {
+                    self.notes_page = index;
+                }
+            }
+            if ui.button(t!("notes.add_page")).clicked() {
+                self.notes_page = notebook.add_page(t!("notes.new_page_title"));
+                self.notes_editing = true;
+            }
+            if ui
+                .button(t!("notes.session", battle = i18n::number(battle)))
+                .clicked()
+            {
+                self.notes_page = notebook.session_page(battle);
+            }
+            if notebook.get_pages().len() > 1 && ui.button(t!("notes.remove_page")).clicked() {
+                notebook.remove_page(self.notes_page);
             }
+            ui.toggle_value(&mut self.notes_editing, t!("notes.edit"));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.notes_search)
+                    .hint_text(t!("notes.search"))
+                    .desired_width(150.0),
+            );
+        });
+        self.notes_page = self
+            .notes_page
+            .min(notebook.get_pages().len().saturating_sub(1));
+
+        if !self.notes_search.trim().is_empty() {
+            let matches = notebook.search(&self.notes_search);
+            if matches.is_empty() {
+                focusable_label(ui, accessible, t!("notes.no_matches"));
+            }
+            for found in matches {
+                let title = notebook
+                    .get_page(found.page)
+                    .map(|page| page.get_title().to_owned())
+                    .unwrap_or_default();
+                if ui.button(format!("{title}: {}", found.line)).clicked() {
+                    self.notes_page = found.page;
+                    self.notes_search.clear();
+                }
+            }
+            return;
+        }
+
+        let Some(page) = notebook.page_mut(self.notes_page) else {
+            return;
+        };
+        if self.notes_editing {
+            ui.text_edit_singleline(page.title_mut());
+            ui.add_sized(
+                ui.available_size(),
+                egui::TextEdit::multiline(page.body_mut()),
+            );
         } else {
-            class_count
+            egui::ScrollArea::vertical()
+                .id_source("notes_view")
+                .show(ui, |ui| {
+                    if accessible {
+                        focusable_label(ui, true, page.get_body());
+                    } else {
+                        markdown::show(ui, page.get_body());
+                    }
+                });
         }
     }
 
-    fn campaign_menu(&mut self, ui: &mut egui::Ui) {
+    /**
+     * Checklist for the pinned plan, flagging it when it can no longer be finished
+     */
+    fn plan_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(plan) = self.current_save.get_save().get_plan().cloned() else {
+            return;
+        };
+        let character = self.current_save.get_save().get_character();
+        let mut class_to_take = None;
+        let mut clear_plan = false;
         ui.group(|ui| {
-            ui.horizontal(|ui| {
-                ui.menu_button("Origin", |ui| {
-                    let old_origin = self.character_origin.clone();
-                    for origin in self.class_cache.borrow().get_origins() {
-                        ui.radio_value(
-                            &mut self.character_origin,
-                            Some(origin.clone()),
-                            origin.get_name(),
+            egui::CollapsingHeader::new(t!("plan.header"))
+                .id_source("plan_panel")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        let class_cache = self.class_cache.borrow();
+                        for (step, done) in plan.checklist(&character) {
+                            ui.add_enabled(
+                                false,
+                                egui::Checkbox::new(
+                                    &mut done.clone(),
+                                    class_cache.get_class_name(&step),
+                                ),
+                            );
+                        }
+                    });
+                    if let Err(err) = plan.check(&character, &self.class_cache.borrow()) {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            t!("plan.unfinishable", error = err),
                         );
                     }
-                    if self.character_origin != old_origin {
-                        self.change_origin(self.character_origin.clone());
-                    }
-                });
-                ui.menu_button("Actions", |ui| {
-                    if ui.button("Refresh Primary Action").clicked() {
-                        self.game_state.extra_primary();
-                    }
-                    if ui.button("Refresh Secondary Action").clicked() {
-                        self.game_state.extra_secondary();
-                    }
-                    if ui.button("Refresh Special Action").clicked() {
-                        self.game_state.extra_special();
-                    }
-                    if ui.button("Refresh Inspiration").clicked() {
-                        self.game_state.refresh_inspiration();
-                    }
+                    ui.horizontal(|ui| {
+                        if let Some(next_step) = plan.next_step(&character) {
+                            let class_cache = self.class_cache.borrow();
+                            let next_class = class_cache
+                                .get_class(next_step)
+                                .filter(|class| class.get_class_available(&self.character_classes))
+                                .cloned();
+                            let next_name = class_cache.get_class_name(next_step);
+                            drop(class_cache);
+                            if ui
+                                .add_enabled(
+                                    next_class.is_some(),
+                                    egui::Button::new(t!("plan.take", class = next_name)),
+                                )
+                                .clicked()
+                            {
+                                class_to_take = next_class;
+                            }
+                        } else {
+                            ui.label(t!("plan.complete"));
+                        }
+                        if ui.button(t!("plan.clear")).clicked() {
+                            clear_plan = true;
+                        }
+                    });
                 });
+        });
+        if let Some(class) = class_to_take {
+            self.record_undo();
+            self.add_new_class(class);
+        }
+        if clear_plan {
+            self.current_save.get_save_mut().set_plan(None);
+        }
+    }
 
-                ui.menu_button("Classes", |ui| self.classes_menu(ui));
-                self.next_part_buttons(ui);
+    pub fn apply_repairs(&mut self, repairs: &[SaveRepair]) {
+        self.record_undo();
+        for repair in repairs {
+            info!("Repairing save: {repair}");
+            repair.apply(self.current_save.get_save_mut());
+        }
+        self.refresh_campaign();
+    }
 
-                ui.menu_button("Stats", |ui| {
-                    ui.add(StatsPanel::new(
-                        self.current_save.get_save(),
-                        &mut self.game_state,
-                    ))
-                });
+    pub fn get_save_issues(&self) -> &[SaveIssue] {
+        &self.save_issues
+    }
+
+    fn revalidate(&mut self) {
+        self.save_issues = validate_save(self.current_save.get_save(), &self.class_cache.borrow());
+    }
+
+    fn record_undo(&mut self) {
+        push_undo_snapshot(
+            &mut self.undo_history,
+            &self.game_state,
+            self.current_save.get_save(),
+        );
+    }
+
+    /**
+     * Step back to before the last action, returning false when there is nothing to undo
+     */
+    pub fn undo(&mut self) -> bool {
+        let Some((game_state, play_state)) = self.undo_history.pop() else {
+            return false;
+        };
+        self.current_save
+            .get_save_mut()
+            .restore_play_state(play_state);
+        self.refresh_campaign();
+        self.game_state = game_state;
+        true
+    }
+
+    pub fn get_primary_action_names(&self) -> Vec<String> {
+        self.primary_actions
+            .iter()
+            .map(PrimaryAction::get_name)
+            .collect()
+    }
+
+    pub fn get_secondary_action_names(&self) -> Vec<String> {
+        self.secondary_actions
+            .iter()
+            .map(SecondaryAction::get_name)
+            .collect()
+    }
+
+    pub fn get_special_action_names(&self) -> Vec<String> {
+        self.game_state
+            .get_special_actions()
+            .iter()
+            .map(SpecialAction::get_name)
+            .collect()
+    }
+
+    /**
+     * Classes the character could take right now, in rules order
+     */
+    pub fn get_addable_classes(&self) -> Vec<Class> {
+        self.class_cache
+            .borrow()
+            .get_classes()
+            .into_iter()
+            .filter(|class| {
+                !self
+                    .character_classes
+                    .iter()
+                    .any(|taken| taken.get_id() == class.get_id())
+                    && class.get_class_available(&self.character_classes)
+            })
+            .cloned()
+            .collect()
+    }
 
-                ui.checkbox(&mut self.description_hovering, "Hover Description");
-                ui.checkbox(&mut self.track_turns, "Track Turns");
+    /**
+     * Commands for this campaign's actions and classes, as offered in the command palette
+     */
+    pub fn palette_entries(&self) -> Vec<(String, AppCommand)> {
+        let mut entries = vec![];
+        for (index, name) in self.get_primary_action_names().into_iter().enumerate() {
+            entries.push((
+                t!("palette.use_primary", name = name),
+                AppCommand::UsePrimary(index),
+            ));
+        }
+        for (index, name) in self.get_secondary_action_names().into_iter().enumerate() {
+            entries.push((
+                t!("palette.use_secondary", name = name),
+                AppCommand::UseSecondary(index),
+            ));
+        }
+        for (index, name) in self.get_special_action_names().into_iter().enumerate() {
+            entries.push((
+                t!("palette.use_special", name = name),
+                AppCommand::UseSpecial(index),
+            ));
+        }
+        for class in self.get_addable_classes() {
+            let command = AppCommand::AddClass(class.get_name());
+            entries.push((command.label(), command));
+        }
+        entries
+    }
+
+    /**
+     * Add a class by name if the character can take it, returning whether it was added
+     */
+    pub fn add_class_by_name(&mut self, class_name: &str) -> bool {
+        let Some(class) = self
+            .get_addable_classes()
+            .into_iter()
+            .find(|class| class.get_name() == class_name)
+        else {
+            return false;
+        };
+        self.record_undo();
+        self.add_new_class(class);
+        true
+    }
+
+    pub fn get_level(&self) -> usize {
+        self.current_save
+            .get_save()
+            .get_character()
+            .get_level(&self.class_cache.borrow())
+    }
+
+    fn campaign_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        accessible: bool,
+        level_numerals: LevelNumerals,
+    ) {
+        let contents = |ui: &mut egui::Ui| {
+            menu_section(ui, accessible, t!("campaign.origin"), |ui| {
+                let old_origin = self.character_origin.clone();
+                for origin in self.class_cache.borrow().get_origins() {
+                    ui.radio_value(
+                        &mut self.character_origin,
+                        Some(origin.clone()),
+                        origin.get_name(),
+                    );
+                }
+                if self.character_origin != old_origin {
+                    self.record_undo();
+                    let new_origin = self.character_origin.clone();
+                    self.change_origin(new_origin.as_ref());
+                }
+            });
+            menu_section(ui, accessible, t!("campaign.actions"), |ui| {
+                if ui.button(t!("campaign.refresh_primary")).clicked() {
+                    self.record_undo();
+                    self.game_state.extra_primary();
+                }
+                if ui.button(t!("campaign.refresh_secondary")).clicked() {
+                    self.record_undo();
+                    self.game_state.extra_secondary();
+                }
+                if ui.button(t!("campaign.refresh_special")).clicked() {
+                    self.record_undo();
+                    self.game_state.extra_special();
+                }
+                if ui.button(t!("campaign.refresh_inspiration")).clicked() {
+                    self.record_undo();
+                    self.game_state.refresh_inspiration();
+                }
+            });
+
+            menu_section(ui, accessible, t!("campaign.classes"), |ui| {
+                self.classes_menu(ui, accessible, level_numerals);
+            });
+            menu_section(ui, accessible, t!("campaign.planner"), |ui| {
+                self.planner_menu(ui);
             });
+            self.next_part_buttons(ui);
+
+            menu_section(ui, accessible, t!("campaign.stats"), |ui| {
+                ui.add(
+                    StatsPanel::new(self.current_save.get_save(), &mut self.game_state)
+                        .accessible(accessible),
+                );
+            });
+
+            ui.checkbox(
+                &mut self.description_hovering,
+                t!("campaign.hover_description"),
+            );
+            ui.checkbox(&mut self.track_turns, t!("campaign.track_turns"));
+        };
+        // Collapsing sections read better stacked than in a row
+        ui.group(|ui| {
+            if accessible {
+                ui.vertical(contents);
+            } else {
+                ui.horizontal(contents);
+            }
         });
     }
 
-    fn utility_panel(&mut self, ui: &mut egui::Ui) {
+    fn utility_panel(&mut self, ui: &mut egui::Ui, colors: &ColorScheme, accessible: bool) {
         ui.set_width(ui.available_width() / 5.0);
         ui.group(|ui| {
-            ui.label("Utilities:");
+            let color = colors.get_category(AbilityCategory::Utilities);
+            focusable_label(
+                ui,
+                accessible,
+                egui::RichText::new(t!("campaign.utilities")).color(color),
+            );
             for utility in &self.utilities {
-                if self.description_hovering {
-                    ui.label(egui::RichText::new(utility.get_name()).strong())
-                        .on_hover_text(utility.get_description());
-                } else {
-                    ui.label(egui::RichText::new(utility.get_name()).strong());
-                    ui.label(utility.get_description());
-                }
+                ability_label(
+                    ui,
+                    accessible,
+                    self.description_hovering,
+                    egui::RichText::new(utility.get_name())
+                        .strong()
+                        .color(color),
+                    &self.term_index.link(&utility.get_description()),
+                );
             }
         });
     }
 
-    fn passive_panel(&mut self, ui: &mut egui::Ui) {
+    fn passive_panel(&mut self, ui: &mut egui::Ui, colors: &ColorScheme, accessible: bool) {
         ui.set_width(ui.available_width() / 4.0);
         ui.group(|ui| {
-            ui.label("Passives:");
+            let color = colors.get_category(AbilityCategory::Passives);
+            focusable_label(
+                ui,
+                accessible,
+                egui::RichText::new(t!("campaign.passives")).color(color),
+            );
             for passive in &self.passives {
-                if self.description_hovering {
-                    ui.label(egui::RichText::new(passive.get_name()).strong())
-                        .on_hover_text(passive.get_description());
-                } else {
-                    ui.label(egui::RichText::new(passive.get_name()).strong());
-                    ui.label(passive.get_description());
-                }
+                ability_label(
+                    ui,
+                    accessible,
+                    self.description_hovering,
+                    egui::RichText::new(passive.get_name())
+                        .strong()
+                        .color(color),
+                    &self.term_index.link(&passive.get_description()),
+                );
             }
         });
     }
 
-    fn primary_panel(&mut self, ui: &mut egui::Ui) {
+    fn primary_panel(&mut self, ui: &mut egui::Ui, colors: &ColorScheme, accessible: bool) {
         ui.set_width(ui.available_width() / 3.0);
         ui.group(|ui| {
             let label_text = if self.track_turns {
-                format!(
-                    "Primary Actions ({} remaining):",
-                    self.game_state.get_primary_actions()
+                t!(
+                    "campaign.primary_remaining",
+                    count = i18n::number(self.game_state.get_primary_actions())
                 )
             } else {
-                "Primary Actions:".to_owned()
+                t!("campaign.primary")
             };
-            ui.label(label_text);
+            let color = colors.get_category(AbilityCategory::Primary);
+            focusable_label(ui, accessible, egui::RichText::new(label_text).color(color));
             for action in &self.primary_actions {
                 if self.track_turns {
-                    let button_response = ui.add_enabled(
-                        self.game_state.get_primary_usable(),
-                        egui::Button::new(action.get_name()),
+                    let usable = self.game_state.get_primary_usable();
+                    let used = action_button(
+                        ui,
+                        accessible,
+                        self.description_hovering,
+                        egui::RichText::new(action.get_name()).color(colors.action(usable)),
+                        &self.term_index.link(&action.get_description()),
+                        usable,
                     );
-                    let button_response = if self.description_hovering {
-                        button_response
-                            .on_hover_text(action.get_description())
-                            .on_disabled_hover_text(action.get_description())
-                    } else {
-                        ui.label(action.get_description());
-                        button_response
-                    };
-
-                    if button_response.clicked() && action.get_name() != "Execute" {
+                    if used && action.get_name() != "Execute" {
+                        push_undo_snapshot(
+                            &mut self.undo_history,
+                            &self.game_state,
+                            self.current_save.get_save(),
+                        );
                         self.game_state.use_primary();
                     }
-                } else if self.description_hovering {
-                    ui.label(egui::RichText::new(action.get_name()).strong())
-                        .on_hover_text(action.get_description());
                 } else {
-                    ui.label(egui::RichText::new(action.get_name()).strong());
-                    ui.label(action.get_description());
+                    ability_label(
+                        ui,
+                        accessible,
+                        self.description_hovering,
+                        egui::RichText::new(action.get_name()).strong().color(color),
+                        &self.term_index.link(&action.get_description()),
+                    );
                 }
             }
         });
     }
 
-    fn secondary_panel(&mut self, ui: &mut egui::Ui) {
+    fn secondary_panel(&mut self, ui: &mut egui::Ui, colors: &ColorScheme, accessible: bool) {
         ui.set_width(ui.available_width() / 2.0);
         ui.group(|ui| {
             let label_text = if self.track_turns {
-                format!(
-                    "Secondary Actions ({} remaining):",
-                    self.game_state.get_secondary_actions()
+                t!(
+                    "campaign.secondary_remaining",
+                    count = i18n::number(self.game_state.get_secondary_actions())
                 )
             } else {
-                "Secondary Actions:".to_owned()
+                t!("campaign.secondary")
             };
-            ui.label(label_text);
+            let color = colors.get_category(AbilityCategory::Secondary);
+            focusable_label(ui, accessible, egui::RichText::new(label_text).color(color));
             for action in &self.secondary_actions {
                 if self.track_turns {
-                    let button_response = ui.add_enabled(
-                        self.game_state.get_secondary_usable(),
-                        egui::Button::new(action.get_name()),
+                    let usable = self.game_state.get_secondary_usable();
+                    let used = action_button(
+                        ui,
+                        accessible,
+                        self.description_hovering,
+                        egui::RichText::new(action.get_name()).color(colors.action(usable)),
+                        &self.term_index.link(&action.get_description()),
+                        usable,
                     );
-                    let button_response = if self.description_hovering {
-                        button_response
-                            .on_hover_text(action.get_description())
-                            .on_disabled_hover_text(action.get_description())
-                    } else {
-                        ui.label(action.get_description());
-                        button_response
-                    };
-                    if button_response.clicked() {
+                    if used {
+                        push_undo_snapshot(
+                            &mut self.undo_history,
+                            &self.game_state,
+                            self.current_save.get_save(),
+                        );
                         self.game_state.use_secondary();
                     }
-                } else if self.description_hovering {
-                    ui.label(egui::RichText::new(action.get_name()).strong())
-                        .on_hover_text(action.get_description());
                 } else {
-                    ui.label(egui::RichText::new(action.get_name()).strong());
-                    ui.label(action.get_description());
+                    ability_label(
+                        ui,
+                        accessible,
+                        self.description_hovering,
+                        egui::RichText::new(action.get_name()).strong().color(color),
+                        &self.term_index.link(&action.get_description()),
+                    );
                 }
             }
         });
     }
 
-    fn special_panel(&mut self, ui: &mut egui::Ui) {
+    fn special_panel(&mut self, ui: &mut egui::Ui, colors: &ColorScheme, accessible: bool) {
         ui.group(|ui| {
-            ui.label("Specials:");
+            focusable_label(
+                ui,
+                accessible,
+                egui::RichText::new(t!("campaign.specials"))
+                    .color(colors.get_category(AbilityCategory::Special)),
+            );
 
             for action in &self.game_state.get_special_actions().clone() {
-                let button_response = ui.add_enabled(
-                    action.is_usable()
-                        && (self.game_state.get_any_special_usable() || !self.track_turns),
-                    egui::Button::new(action.get_name()),
+                let usable = action.is_usable()
+                    && (self.game_state.get_any_special_usable() || !self.track_turns);
+                let used = action_button(
+                    ui,
+                    accessible,
+                    self.description_hovering,
+                    egui::RichText::new(action.get_name()).color(colors.action(usable)),
+                    &self.term_index.link(&action.get_description()),
+                    usable,
                 );
-                let button_response = if self.description_hovering {
-                    button_response
-                        .on_hover_text(action.get_description())
-                        .on_disabled_hover_text(action.get_description())
-                } else {
-                    ui.label(action.get_description());
-                    button_response
-                };
-                if button_response.clicked() {
-                    if action
-                        .get_description()
-                        .to_lowercase()
-                        .contains("repeatable")
-                    {
-                        self.game_state.use_repeatable_special();
-                    } else {
-                        self.game_state.use_special(action.get_name().as_str());
-                        self.current_save
-                            .get_save_mut()
-                            .use_special(action.get_name().as_str());
+                if used {
+                    self.record_undo();
+                    self.use_special_action(action);
+                }
+            }
+        });
+    }
+
+    fn use_special_action(&mut self, action: &SpecialAction) {
+        if action
+            .get_description()
+            .to_lowercase()
+            .contains("repeatable")
+        {
+            self.game_state.use_repeatable_special();
+        } else {
+            self.game_state.use_special(action);
+            if let Some(special_id) = action.get_id() {
+                self.current_save.get_save_mut().use_special(special_id);
+            }
+        }
+        if action.is_named("Action Surge") {
+            self.game_state.extra_primary();
+            self.game_state.extra_primary();
+        }
+    }
+
+    /**
+     * Perform an action requested over the API, following the same rules as the buttons
+     */
+    pub fn apply_api_action(&mut self, action: &ApiAction) -> Result<(), ApiActionError> {
+        // Turn and battle changes record their own undo step
+        let records_undo = !matches!(action, ApiAction::NextTurn | ApiAction::NextBattle);
+        if records_undo {
+            self.record_undo();
+        }
+        let result = self.apply_action_unrecorded(action);
+        if result.is_err() && records_undo {
+            self.undo_history.pop();
+        }
+        result
+    }
+
+    fn apply_action_unrecorded(&mut self, action: &ApiAction) -> Result<(), ApiActionError> {
+        match action {
+            ApiAction::UsePrimary { name } => {
+                if !self
+                    .primary_actions
+                    .iter()
+                    .any(|primary| &primary.get_name() == name)
+                {
+                    return Err(ApiActionError::UnknownAction(name.clone()));
+                }
+                if self.track_turns {
+                    if !self.game_state.get_primary_usable() {
+                        return Err(ApiActionError::ActionUnavailable(name.clone()));
                     }
-                    if action.is_named("Action Surge") {
-                        self.game_state.extra_primary();
-                        self.game_state.extra_primary();
+                    if name != "Execute" {
+                        self.game_state.use_primary();
                     }
                 }
             }
-        });
+            ApiAction::UseSecondary { name } => {
+                if !self
+                    .secondary_actions
+                    .iter()
+                    .any(|secondary| &secondary.get_name() == name)
+                {
+                    return Err(ApiActionError::UnknownAction(name.clone()));
+                }
+                if self.track_turns {
+                    if !self.game_state.get_secondary_usable() {
+                        return Err(ApiActionError::ActionUnavailable(name.clone()));
+                    }
+                    self.game_state.use_secondary();
+                }
+            }
+            ApiAction::UseSpecial { name } => {
+                let special = self
+                    .game_state
+                    .get_special_actions()
+                    .iter()
+                    .find(|special| special.is_named(name.as_str()))
+                    .cloned()
+                    .ok_or_else(|| ApiActionError::UnknownAction(name.clone()))?;
+                if !(special.is_usable()
+                    && (self.game_state.get_any_special_usable() || !self.track_turns))
+                {
+                    return Err(ApiActionError::ActionUnavailable(name.clone()));
+                }
+                self.use_special_action(&special);
+            }
+            ApiAction::UseInspiration => {
+                if !self.game_state.get_inspiration_usable() {
+                    return Err(ApiActionError::ActionUnavailable("Inspiration".to_owned()));
+                }
+                self.game_state.use_inspiration();
+            }
+            ApiAction::NextTurn => self.next_turn(),
+            ApiAction::NextBattle => self.next_battle(),
+        }
+        Ok(())
+    }
+
+    /**
+     * Id of this campaign for API clients, unaffected by closing or moving other tabs
+     */
+    pub fn get_campaign_id(&self) -> usize {
+        self.campaign_id
+    }
+
+    /**
+     * Capture the current state of the campaign for API clients
+     */
+    pub fn api_snapshot(&self) -> CampaignSnapshot {
+        let save = self.current_save.get_save();
+        CampaignSnapshot {
+            id: self.campaign_id,
+            name: save.get_campaign_name(),
+            path: self
+                .get_path()
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned()),
+            level: self.get_level(),
+            origin: self.character_origin.as_ref().map(Origin::get_name),
+            classes: self.character_classes.iter().map(Class::get_name).collect(),
+            game_state: GameStateSnapshot {
+                battle: save.get_battle(),
+                round: self.game_state.get_round_num(),
+                turn: self.game_state.get_turn_side().to_string(),
+                power: self.game_state.get_power(),
+                defense: self.game_state.get_defense(),
+                primary_actions_remaining: self.game_state.get_primary_actions(),
+                secondary_actions_remaining: self.game_state.get_secondary_actions(),
+                inspiration_usable: self.game_state.get_inspiration_usable(),
+                any_special_usable: self.game_state.get_any_special_usable(),
+                primary_actions: self
+                    .primary_actions
+                    .iter()
+                    .map(PrimaryAction::get_name)
+                    .collect(),
+                secondary_actions: self
+                    .secondary_actions
+                    .iter()
+                    .map(SecondaryAction::get_name)
+                    .collect(),
+                special_actions: self
+                    .game_state
+                    .get_special_actions()
+                    .iter()
+                    .map(|special| SpecialSnapshot {
+                        name: special.get_name(),
+                        usable: special.is_usable(),
+                    })
+                    .collect(),
+            },
+        }
     }
 
     pub fn refresh_campaign(&mut self) {
-        let current_campaign = self.current_save.get_save().get_character();
         let class_cache = self.class_cache.borrow();
+        self.current_save
+            .get_save_mut()
+            .follow_renames(&class_cache);
+        self.current_save
+            .get_save_mut()
+            .upgrade_legacy_specials(&class_cache);
+        let current_campaign = self.current_save.get_save().get_character();
         let (utility, passive, primary, secondary, mut special) =
             current_campaign.get_all_actions(&class_cache);
         self.primary_actions = primary;
@@ -338,58 +984,69 @@ impl CampaignGui {
         self.game_state = GameState::default();
         let used_specials = self.current_save.get_save().get_used_specials();
         for action in &mut special {
-            if used_specials.contains(&action.get_name()) {
+            if action
+                .get_id()
+                .is_some_and(|special_id| used_specials.contains(&special_id))
+            {
                 action.use_action();
             }
             self.game_state.push_special(action.clone());
         }
-        let new_origin = current_campaign
-            .get_origin()
-            .and_then(|origin_name| class_cache.get_origin(origin_name.as_str()))
-            .cloned();
+        let new_origin = current_campaign.resolve_origin(&class_cache).cloned();
         self.character_origin = new_origin;
         self.character_classes =
             class_cache.map_to_concrete_classes(current_campaign.get_classes());
+        self.term_index = TermIndex::new(&class_cache);
         self.game_state
             .set_round(self.current_save.get_save().get_round());
         self.game_state
             .change_power_for_battle(self.current_save.get_save().get_battle_power());
         self.game_state
             .change_defense_for_battle(self.current_save.get_save().get_battle_defense());
+        drop(class_cache);
+        self.revalidate();
+        for issue in &self.save_issues {
+            warn!(
+                "{}: {issue}",
+                self.current_save.get_save().get_campaign_name()
+            );
+        }
     }
 
-    pub fn change_origin(&mut self, new_origin: Option<Class>) {
+    pub fn change_origin(&mut self, new_origin: Option<&Origin>) {
         let campaign = self.current_save.get_save_mut().get_character_mut();
-        campaign.replace_origin(new_origin.map(|class| class.get_name()));
+        campaign.set_origin(new_origin);
         self.refresh_campaign();
     }
 
     fn next_part_buttons(&mut self, ui: &mut egui::Ui) {
-        if ui.button("Next Battle").clicked() {
+        if ui.button(t!("common.next_battle")).clicked() {
             self.next_battle();
         }
 
-        if ui.button("Next Turn").clicked() {
+        if ui.button(t!("common.next_turn")).clicked() {
             self.next_turn();
         }
     }
 
     pub fn next_battle(&mut self) {
+        self.record_undo();
         self.game_state.next_battle();
         self.current_save.get_save_mut().refresh_specials();
         self.current_save.get_save_mut().inc_battle();
     }
 
     pub fn next_turn(&mut self) {
+        self.record_undo();
         self.game_state.next_turn();
         self.current_save
             .get_save_mut()
             .set_round(self.game_state.get_round_num());
     }
 
-    fn classes_menu(&mut self, ui: &mut egui::Ui) {
+    fn classes_menu(&mut self, ui: &mut egui::Ui, accessible: bool, level_numerals: LevelNumerals) {
         if self.character_classes.len() != self.class_cache.borrow().get_class_cache_count() {
-            ui.menu_button("Add", |ui| {
+            menu_section(ui, accessible, t!("classes.add"), |ui| {
                 let mut classes_to_add = vec![];
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     for class in self.class_cache.borrow().get_classes() {
@@ -401,8 +1058,13 @@ impl CampaignGui {
                             && class.get_class_available(&self.character_classes)
                         {
                             let class_label = class.get_name()
-                                + &(match class.get_level() {
-                                    Some(level) => format!(" (Level {level})"),
+                                + &(match *class.get_level() {
+                                    Some(level) => {
+                                        t!(
+                                            "classes.level_suffix",
+                                            level = i18n::level(level, level_numerals)
+                                        )
+                                    }
                                     None => String::new(),
                                 });
                             if ui.button(class_label).clicked() {
@@ -411,21 +1073,30 @@ impl CampaignGui {
                         }
                     }
                 });
+                if !classes_to_add.is_empty() {
+                    self.record_undo();
+                }
                 for class in classes_to_add {
                     self.add_new_class(class);
                 }
             });
         }
         if !self.character_classes.is_empty() {
-            ui.menu_button("Remove", |ui| {
+            menu_section(ui, accessible, t!("classes.remove"), |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     for class in self.character_classes.clone() {
                         let class_label = class.get_name()
-                            + &(match class.get_level() {
-                                Some(level) => format!(" (Level {level})"),
+                            + &(match *class.get_level() {
+                                Some(level) => {
+                                    t!(
+                                        "classes.level_suffix",
+                                        level = i18n::level(level, level_numerals)
+                                    )
+                                }
                                 None => String::new(),
                             });
                         if ui.button(class_label).clicked() {
+                            self.record_undo();
                             self.remove_class(&class);
                         }
                     }
@@ -443,8 +1114,9 @@ impl CampaignGui {
         self.game_state.push_special(class.get_special_action());
 
         let campaign = self.current_save.get_save_mut().get_character_mut();
-        campaign.add_class(class.get_name());
+        campaign.add_class(class.get_id());
         self.character_classes.push(class);
+        self.revalidate();
     }
 
     fn remove_class(&mut self, class: &Class) {
@@ -473,16 +1145,16 @@ impl CampaignGui {
             .game_state
             .get_special_actions()
             .iter()
-            .position(|action| action.clone().get_name() == class.get_special_action().get_name())
+            .position(|action| *action == class.get_special_action())
         {
             self.game_state.remove_special_action(special_index);
         }
         self.character_classes
-            .retain(|stored_class| stored_class.get_name() != class.get_name());
+            .retain(|stored_class| stored_class.get_id() != class.get_id());
         self.current_save
             .get_save_mut()
             .get_character_mut()
-            .remove_class(class.get_name());
+            .remove_class(&class.get_id());
 
         let mut subclasses = vec![];
         for remaining_class in &self.character_classes {
@@ -494,6 +1166,7 @@ impl CampaignGui {
         for subclass in &subclasses {
             self.remove_class(subclass);
         }
+        self.revalidate();
     }
 
     pub fn clear_campaign(&mut self) {
@@ -518,6 +1191,7 @@ impl CampaignGui {
         self.current_save.set_path(path)
     }
 
+    #[allow(clippy::ref_option, reason = "mirrors Save::get_path")]
     pub fn get_path(&self) -> &Option<OsString> {
         self.current_save.get_path()
     }
@@ -526,3 +1200,72 @@ impl CampaignGui {
         self.current_save.is_dirty()
     }
 }
+
+fn push_undo_snapshot(
+    undo_history: &mut Vec<(GameState, PlayState)>,
+    game_state: &GameState,
+    save: &Save,
+) {
+    if undo_history.len() >= UNDO_LIMIT {
+        undo_history.remove(0);
+    }
+    undo_history.push((game_state.clone(), save.get_play_state()));
+}
+
+/**
+ * The problem in the current language, with names as the rules write them
+ */
+fn save_issue(issue: &SaveIssue) -> String {
+    match issue {
+        SaveIssue::UnknownOrigin { name, .. } => t!("repair.unknown_origin", name = name),
+        SaveIssue::UnknownClass { name, .. } => t!("repair.unknown_class", name = name),
+        SaveIssue::UnmetPrerequisites { name, .. } => {
+            t!("repair.unmet_prerequisites", name = name)
+        }
+        SaveIssue::DuplicateClass { name, count, .. } => t!(
+            "repair.duplicate_class",
+            name = name,
+            count = i18n::number(*count)
+        ),
+        SaveIssue::StaleSpecial { source, .. } => t!("repair.stale_special", source = source),
+        SaveIssue::IllegalLevel {
+            name,
+            level,
+            classes_taken,
+            ..
+        } => t!(
+            "repair.illegal_level",
+            name = name,
+            level = i18n::number(*level),
+            classes_taken = i18n::number(*classes_taken)
+        ),
+    }
+}
+
+fn save_repair(repair: &SaveRepair) -> String {
+    match repair {
+        SaveRepair::ReplaceOrigin(Some(origin)) => t!("repair.replace_origin", origin = origin),
+        SaveRepair::ReplaceOrigin(None) => t!("repair.clear_origin"),
+        SaveRepair::ReplaceClass { to, .. } => t!("repair.replace_class", name = to),
+        SaveRepair::RemoveClass { name, .. } => t!("repair.remove_class", name = name),
+        SaveRepair::RemoveDuplicateClasses => t!("repair.remove_duplicates"),
+        SaveRepair::ForgetSpecial(_) => t!("repair.forget_special"),
+    }
+}
+
+/**
+ * Why a target can't be planned in the current language, naming classes as the rules do
+ */
+fn plan_error(err: &PlanError, class_cache: &ClassCache) -> String {
+    match err {
+        PlanError::UnknownClass(class_id) => t!(
+            "planner.unknown_class",
+            name = class_cache.get_class_name(class_id)
+        ),
+        PlanError::UnmetRequirement { class, requires } => t!(
+            "planner.unmet_requirement",
+            name = class,
+            requires = requires.join(&t!("planner.or"))
+        ),
+    }
+}
@@ -0,0 +1,389 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use super::i18n::{self, t};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Number of primary, secondary and special actions that get their own bindings
+pub const NUMBERED_ACTIONS: usize = 9;
+const NUMBER_KEYS: [Key; NUMBERED_ACTIONS] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+/**
+ * Anything that can be run from a shortcut or the command palette
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppCommand {
+    NextTurn,
+    NextBattle,
+    /// Zero based index into the focused campaign's primary actions
+    UsePrimary(usize),
+    UseSecondary(usize),
+    UseSpecial(usize),
+    UseInspiration,
+    Save,
+    Open,
+    NextTab,
+    PreviousTab,
+    Undo,
+    CommandPalette,
+    AddClass(String),
+}
+
+impl AppCommand {
+    pub fn label(&self) -> String {
+        match self {
+            AppCommand::NextTurn => t!("common.next_turn"),
+            AppCommand::NextBattle => t!("common.next_battle"),
+            AppCommand::UsePrimary(index) => {
+                t!("commands.use_primary", number = i18n::number(index + 1))
+            }
+            AppCommand::UseSecondary(index) => {
+                t!("commands.use_secondary", number = i18n::number(index + 1))
+            }
+            AppCommand::UseSpecial(index) => {
+                t!("commands.use_special", number = i18n::number(index + 1))
+            }
+            AppCommand::UseInspiration => t!("commands.use_inspiration"),
+            AppCommand::Save => t!("commands.save"),
+            AppCommand::Open => t!("commands.open"),
+            AppCommand::NextTab => t!("commands.next_tab"),
+            AppCommand::PreviousTab => t!("commands.previous_tab"),
+            AppCommand::Undo => t!("commands.undo"),
+            AppCommand::CommandPalette => t!("commands.command_palette"),
+            AppCommand::AddClass(class_name) => t!("commands.add_class", class = class_name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    command: AppCommand,
+    shortcut: Option<KeyboardShortcut>,
+}
+
+impl KeyBinding {
+    pub fn get_command(&self) -> &AppCommand {
+        &self.command
+    }
+
+    pub fn get_shortcut(&self) -> Option<KeyboardShortcut> {
+        self.shortcut
+    }
+
+    pub fn set_shortcut(&mut self, shortcut: Option<KeyboardShortcut>) {
+        self.shortcut = shortcut;
+    }
+}
+
+/**
+ * User configurable shortcuts, stored in the app state
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keybindings {
+    bindings: Vec<KeyBinding>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let binding = |command, modifiers, key| KeyBinding {
+            command,
+            shortcut: Some(KeyboardShortcut::new(modifiers, key)),
+        };
+        let mut bindings = vec![
+            binding(AppCommand::NextTurn, Modifiers::NONE, Key::N),
+            binding(AppCommand::NextBattle, Modifiers::SHIFT, Key::N),
+            binding(AppCommand::UseInspiration, Modifiers::NONE, Key::I),
+            binding(AppCommand::Save, Modifiers::COMMAND, Key::S),
+            binding(AppCommand::Open, Modifiers::COMMAND, Key::O),
+            binding(AppCommand::NextTab, Modifiers::COMMAND, Key::Tab),
+            binding(
+                AppCommand::PreviousTab,
+                Modifiers::COMMAND | Modifiers::SHIFT,
+                Key::Tab,
+            ),
+            binding(AppCommand::Undo, Modifiers::COMMAND, Key::Z),
+            binding(AppCommand::CommandPalette, Modifiers::COMMAND, Key::K),
+        ];
+        for (index, key) in NUMBER_KEYS.into_iter().enumerate() {
+            bindings.push(binding(AppCommand::UsePrimary(index), Modifiers::NONE, key));
+            bindings.push(binding(
+                AppCommand::UseSecondary(index),
+                Modifiers::COMMAND,
+                key,
+            ));
+            bindings.push(binding(AppCommand::UseSpecial(index), Modifiers::ALT, key));
+        }
+        Keybindings { bindings }
+    }
+}
+
+impl Keybindings {
+    pub fn bindings_mut(&mut self) -> &mut [KeyBinding] {
+        &mut self.bindings
+    }
+
+    /**
+     * Add any default bindings for commands missing from an older saved set
+     */
+    pub fn add_missing_defaults(&mut self) {
+        for default in Keybindings::default().bindings {
+            if !self
+                .bindings
+                .iter()
+                .any(|binding| binding.command == default.command)
+            {
+                self.bindings.push(default);
+            }
+        }
+    }
+
+    /**
+     * Commands whose shortcuts were pressed this frame, consuming the key presses.
+     * Bindings without Ctrl/Cmd or Alt are skipped while a text field has focus.
+     */
+    pub fn triggered(&self, ctx: &egui::Context) -> Vec<AppCommand> {
+        let typing = ctx.wants_keyboard_input();
+        let mut bindings = self
+            .bindings
+            .iter()
+            .filter_map(|binding| binding.shortcut.map(|shortcut| (shortcut, binding)))
+            .filter(|(shortcut, _)| !typing || shortcut.modifiers.command || shortcut.modifiers.alt)
+            .collect::<Vec<_>>();
+        // egui ignores extra Shift and Alt when matching, so try the most specific first
+        bindings.sort_by_key(|(shortcut, _)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+        ctx.input_mut(|input| {
+            bindings
+                .into_iter()
+                .filter(|(shortcut, _)| input.consume_shortcut(shortcut))
+                .map(|(_, binding)| binding.command.clone())
+                .collect()
+        })
+    }
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [
+        modifiers.alt,
+        modifiers.shift,
+        modifiers.command || modifiers.ctrl || modifiers.mac_cmd,
+    ]
+    .into_iter()
+    .filter(|held| *held)
+    .count()
+}
+
+/**
+ * Score a candidate against a query by matching the query's characters in order,
+ * rewarding runs and word starts. None when the candidate doesn't contain the query.
+ */
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match = None;
+    for query_char in query.chars() {
+        let offset = candidate[position..]
+            .iter()
+            .position(|candidate_char| *candidate_char == query_char)?;
+        let index = position + offset;
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous_match = Some(index);
+        position = index + 1;
+    }
+    Some(score * 100 - i64::try_from(candidate.len()).unwrap_or(i64::MAX / 200))
+}
+
+/**
+ * Ctrl+K style window that filters commands as you type
+ */
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /**
+     * Draw the palette over the given entries, returning the command picked this frame
+     */
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        entries: Vec<(String, AppCommand)>,
+    ) -> Option<AppCommand> {
+        if !self.open {
+            return None;
+        }
+
+        let mut matches = entries
+            .into_iter()
+            .filter_map(|(label, command)| {
+                fuzzy_score(&self.query, &label).map(|score| (score, label, command))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let (up, down, enter, escape) = ctx.input_mut(|input| {
+            (
+                input.consume_key(Modifiers::NONE, Key::ArrowUp),
+                input.consume_key(Modifiers::NONE, Key::ArrowDown),
+                input.consume_key(Modifiers::NONE, Key::Enter),
+                input.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down && self.selected + 1 < matches.len() {
+            self.selected += 1;
+        }
+
+        let mut picked = None;
+        egui::Window::new(t!("commands.command_palette"))
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                ui.set_width(400.0);
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text(t!("palette.hint"))
+                        .desired_width(f32::INFINITY),
+                );
+                if search.changed() {
+                    self.selected = 0;
+                }
+                search.request_focus();
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (index, (_, label, command)) in matches.iter().enumerate() {
+                            let response = ui.selectable_label(index == self.selected, label);
+                            if index == self.selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                picked = Some(command.clone());
+                            }
+                        }
+                        if matches.is_empty() {
+                            ui.label(t!("palette.no_matches"));
+                        }
+                    });
+            });
+
+        if enter {
+            picked = matches
+                .get(self.selected)
+                .map(|(_, _, command)| command.clone());
+        }
+        if picked.is_some() || escape {
+            self.open = false;
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("nt", "Next Turn").is_some());
+        assert!(fuzzy_score("xyz", "Next Turn").is_none());
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+        assert!(
+            fuzzy_score("next", "Next Turn").unwrap()
+                > fuzzy_score("next", "Add Class: Nextra").unwrap()
+        );
+        assert!(
+            fuzzy_score("kni", "Add Class: Knight").unwrap()
+                > fuzzy_score("kni", "Add Class: Kobold Ninja").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_default_bindings_are_unique() {
+        let keybindings = Keybindings::default();
+        let shortcuts = keybindings
+            .bindings
+            .iter()
+            .filter_map(KeyBinding::get_shortcut)
+            .collect::<Vec<_>>();
+
+        for (index, shortcut) in shortcuts.iter().enumerate() {
+            assert!(!shortcuts[index + 1..].contains(shortcut), "{shortcut:?}");
+        }
+        assert_eq!(keybindings.bindings.len(), 9 + 3 * NUMBERED_ACTIONS);
+    }
+
+    #[test]
+    fn test_add_missing_defaults() {
+        let mut keybindings = Keybindings {
+            bindings: vec![KeyBinding {
+                command: AppCommand::Undo,
+                shortcut: None,
+            }],
+        };
+
+        keybindings.add_missing_defaults();
+
+        assert_eq!(
+            keybindings.bindings.len(),
+            Keybindings::default().bindings.len()
+        );
+        assert_eq!(keybindings.bindings[0].get_shortcut(), None);
+    }
+}
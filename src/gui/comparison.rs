@@ -0,0 +1,255 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::i18n::{self, t};
+use crate::model::{
+    classes::ClassCache,
+    comparison::{AbilityCategory, ColumnDiff, ComparisonColumn},
+    ids::ClassId,
+    sheets::Character,
+};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+const GAINED_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 170, 60);
+const LOST_COLOR: egui::Color32 = egui::Color32::from_rgb(200, 70, 70);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ComparisonMode {
+    Classes,
+    Characters,
+}
+
+/**
+ * Tab showing classes or character builds side by side, highlighting what
+ * each column gains or loses against the first
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonGui {
+    class_cache: Rc<RefCell<ClassCache>>,
+    mode: ComparisonMode,
+    selected_classes: Vec<Option<ClassId>>,
+    selected_characters: Vec<Option<usize>>,
+    open_characters: Vec<(String, Character)>,
+}
+
+impl ComparisonGui {
+    pub fn new(class_cache: Rc<RefCell<ClassCache>>) -> ComparisonGui {
+        ComparisonGui {
+            class_cache,
+            mode: ComparisonMode::Classes,
+            selected_classes: vec![None, None],
+            selected_characters: vec![None, None],
+            open_characters: vec![],
+        }
+    }
+
+    /**
+     * Characters from the open campaigns that can be picked as columns
+     */
+    pub fn set_open_characters(&mut self, characters: Vec<(String, Character)>) {
+        for selected in &mut self.selected_characters {
+            if selected.is_some_and(|index| index >= characters.len()) {
+                *selected = None;
+            }
+        }
+        self.open_characters = characters;
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut self.mode,
+                ComparisonMode::Classes,
+                t!("comparison.classes"),
+            );
+            ui.radio_value(
+                &mut self.mode,
+                ComparisonMode::Characters,
+                t!("comparison.characters"),
+            );
+            ui.separator();
+            if ui.button(t!("comparison.add_column")).clicked() {
+                self.selected_classes.push(None);
+                self.selected_characters.push(None);
+            }
+            if self.selected_classes.len() > 2
+                && ui.button(t!("comparison.remove_column")).clicked()
+            {
+                self.selected_classes.pop();
+                self.selected_characters.pop();
+            }
+        });
+        ui.horizontal(|ui| match self.mode {
+            ComparisonMode::Classes => self.class_pickers(ui),
+            ComparisonMode::Characters => self.character_pickers(ui),
+        });
+        ui.separator();
+
+        let columns = self.columns();
+        if columns.len() < 2 {
+            ui.label(t!("comparison.pick_two"));
+            return;
+        }
+        egui::ScrollArea::both().show(ui, |ui| comparison_grid(ui, &columns));
+    }
+
+    fn class_pickers(&mut self, ui: &mut egui::Ui) {
+        let classes = self
+            .class_cache
+            .borrow()
+            .get_classes()
+            .into_iter()
+            .map(|class| (class.get_id(), class.get_name()))
+            .collect::<Vec<_>>();
+        for (index, selected) in self.selected_classes.iter_mut().enumerate() {
+            let selected_name = classes
+                .iter()
+                .find(|(class_id, _)| Some(class_id) == selected.as_ref())
+                .map_or_else(
+                    || t!("comparison.pick_class"),
+                    |(_, class_name)| class_name.clone(),
+                );
+            egui::ComboBox::from_id_source(("compare_class", index))
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (class_id, class_name) in &classes {
+                        ui.selectable_value(selected, Some(class_id.clone()), class_name);
+                    }
+                });
+        }
+    }
+
+    fn character_pickers(&mut self, ui: &mut egui::Ui) {
+        if self.open_characters.is_empty() {
+            ui.label(t!("comparison.no_campaigns"));
+            return;
+        }
+        for (index, selected) in self.selected_characters.iter_mut().enumerate() {
+            egui::ComboBox::from_id_source(("compare_character", index))
+                .selected_text(
+                    selected
+                        .and_then(|index| self.open_characters.get(index))
+                        .map_or_else(|| t!("comparison.pick_campaign"), |(name, _)| name.clone()),
+                )
+                .show_ui(ui, |ui| {
+                    for (character_index, (name, _)) in self.open_characters.iter().enumerate() {
+                        ui.selectable_value(selected, Some(character_index), name);
+                    }
+                });
+        }
+    }
+
+    fn columns(&self) -> Vec<ComparisonColumn> {
+        let class_cache = self.class_cache.borrow();
+        match self.mode {
+            ComparisonMode::Classes => self
+                .selected_classes
+                .iter()
+                .flatten()
+                .filter_map(|class_id| class_cache.get_class(class_id))
+                .map(|class| ComparisonColumn::from_class(class, &class_cache))
+                .collect(),
+            ComparisonMode::Characters => self
+                .selected_characters
+                .iter()
+                .flatten()
+                .filter_map(|index| self.open_characters.get(*index))
+                .map(|(name, character)| {
+                    ComparisonColumn::from_character(name.clone(), character, &class_cache)
+                })
+                .collect(),
+        }
+    }
+}
+
+/**
+ * One row per ability category with the first column as the baseline
+ */
+fn comparison_grid(ui: &mut egui::Ui, columns: &[ComparisonColumn]) {
+    let baseline = &columns[0];
+    egui::Grid::new("comparison_grid")
+        .striped(true)
+        .min_col_width(160.0)
+        .show(ui, |ui| {
+            ui.label("");
+            for column in columns {
+                ui.label(egui::RichText::new(&column.title).heading());
+            }
+            ui.end_row();
+
+            for category in AbilityCategory::ALL {
+                ui.label(egui::RichText::new(i18n::ability_category(category)).strong());
+                for (index, column) in columns.iter().enumerate() {
+                    let diff = (index != 0).then(|| column.diff_abilities(baseline, category));
+                    ui.vertical(|ui| {
+                        for ability in column.get_abilities(category) {
+                            let text = egui::RichText::new(&ability.name);
+                            let text = if diff
+                                .as_ref()
+                                .is_some_and(|diff| diff.gained.contains(&ability.name))
+                            {
+                                text.color(GAINED_COLOR)
+                            } else {
+                                text
+                            };
+                            ui.label(text).on_hover_text(&ability.description);
+                        }
+                        if let Some(diff) = &diff {
+                            lost_labels(ui, diff);
+                        }
+                    });
+                }
+                ui.end_row();
+            }
+
+            ui.label(egui::RichText::new(t!("comparison.opens")).strong())
+                .on_hover_text(t!("comparison.opens_hover"));
+            for (index, column) in columns.iter().enumerate() {
+                let diff = (index != 0).then(|| column.diff_opens(baseline));
+                ui.vertical(|ui| {
+                    for class_name in &column.opens {
+                        let text = egui::RichText::new(class_name);
+                        let text = if diff
+                            .as_ref()
+                            .is_some_and(|diff| diff.gained.contains(class_name))
+                        {
+                            text.color(GAINED_COLOR)
+                        } else {
+                            text
+                        };
+                        ui.label(text);
+                    }
+                    if let Some(diff) = &diff {
+                        lost_labels(ui, diff);
+                    }
+                });
+            }
+            ui.end_row();
+        });
+}
+
+fn lost_labels(ui: &mut egui::Ui, diff: &ColumnDiff) {
+    for name in &diff.lost {
+        ui.label(
+            egui::RichText::new(format!("- {name}"))
+                .strikethrough()
+                .color(LOST_COLOR),
+        );
+    }
+}
@@ -0,0 +1,559 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::i18n::{self, t};
+use super::widgets::description::linked_description;
+use crate::model::{
+    classes::{Class, ClassCache, Origin, SubclassMismatch},
+    glossary::{TermIndex, TermLink},
+    idea_bank::DraftClass,
+    sheets::Character,
+};
+use crate::util::LevelNumerals;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompendiumEntry {
+    Origin,
+    Class,
+    Keyword,
+    Draft,
+}
+
+/**
+ * Tab for browsing every origin and class in the rules without touching a character
+ */
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompendiumGui {
+    class_cache: Rc<RefCell<ClassCache>>,
+    search: String,
+    show_origins: bool,
+    show_classes: bool,
+    show_glossary: bool,
+    show_idea_bank: bool,
+    level_filter: Option<usize>,
+    root_filter: Option<String>,
+    only_available: bool,
+    focused_character: Option<Character>,
+    selected: Option<(CompendiumEntry, String)>,
+}
+
+impl CompendiumGui {
+    pub fn new(class_cache: Rc<RefCell<ClassCache>>) -> CompendiumGui {
+        CompendiumGui {
+            class_cache,
+            search: String::new(),
+            show_origins: true,
+            show_classes: true,
+            show_glossary: true,
+            show_idea_bank: true,
+            level_filter: None,
+            root_filter: None,
+            only_available: false,
+            focused_character: None,
+            selected: None,
+        }
+    }
+
+    /**
+     * Character used for the availability filter, normally the last focused campaign
+     */
+    pub fn set_focused_character(&mut self, character: Option<Character>) {
+        self.focused_character = character;
+    }
+
+    /**
+     * Show an origin, class or rules keyword, as when its link is clicked in a description
+     */
+    pub fn select(&mut self, link: &TermLink) {
+        self.selected = Some(match link {
+            TermLink::Origin(name) => (CompendiumEntry::Origin, name.clone()),
+            TermLink::Class(name) => (CompendiumEntry::Class, name.clone()),
+            TermLink::Keyword(entry) => (CompendiumEntry::Keyword, entry.get_term().to_owned()),
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, level_numerals: LevelNumerals) {
+        egui::SidePanel::left("compendium_list")
+            .resizable(true)
+            .default_width(250.0)
+            .show_inside(ui, |ui| {
+                self.filter_panel(ui, level_numerals);
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| self.entry_list(ui, level_numerals));
+            });
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| self.detail_view(ui, level_numerals));
+        });
+    }
+
+    fn filter_panel(&mut self, ui: &mut egui::Ui, level_numerals: LevelNumerals) {
+        ui.horizontal(|ui| {
+            ui.label(t!("compendium.search"));
+            ui.text_edit_singleline(&mut self.search);
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_origins, t!("compendium.origins"));
+            ui.checkbox(&mut self.show_classes, t!("compendium.classes"));
+            ui.checkbox(&mut self.show_glossary, t!("compendium.glossary"));
+            ui.checkbox(&mut self.show_idea_bank, t!("compendium.idea_bank"));
+        });
+
+        let class_cache = self.class_cache.borrow();
+        let mut levels = class_cache
+            .get_classes()
+            .into_iter()
+            .filter_map(|class| *class.get_level())
+            .collect::<Vec<_>>();
+        levels.sort_unstable();
+        levels.dedup();
+        egui::ComboBox::from_label(t!("compendium.level"))
+            .selected_text(self.level_filter.map_or_else(
+                || t!("compendium.any"),
+                |level| i18n::level(level, level_numerals),
+            ))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.level_filter, None, t!("compendium.any"));
+                for level in levels {
+                    ui.selectable_value(
+                        &mut self.level_filter,
+                        Some(level),
+                        i18n::level(level, level_numerals),
+                    );
+                }
+            });
+
+        let mut roots = class_cache
+            .get_classes()
+            .into_iter()
+            .flat_map(|class| class_cache.get_prerequisite_roots(class))
+            .collect::<Vec<_>>();
+        roots.sort();
+        roots.dedup();
+        egui::ComboBox::from_label(t!("compendium.prerequisite_root"))
+            .selected_text(
+                self.root_filter
+                    .clone()
+                    .unwrap_or_else(|| t!("compendium.any")),
+            )
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.root_filter, None, t!("compendium.any"));
+                for root in roots {
+                    ui.selectable_value(&mut self.root_filter, Some(root.clone()), root);
+                }
+            });
+
+        ui.add_enabled(
+            self.focused_character.is_some(),
+            egui::Checkbox::new(&mut self.only_available, t!("compendium.only_available")),
+        )
+        .on_disabled_hover_text(t!("compendium.only_available_hover"));
+    }
+
+    fn entry_list(&mut self, ui: &mut egui::Ui, level_numerals: LevelNumerals) {
+        let class_cache_rc = self.class_cache.clone();
+        let class_cache = class_cache_rc.borrow();
+        if self.show_origins {
+            let origins = class_cache
+                .get_origins()
+                .into_iter()
+                .filter(|_| {
+                    self.level_filter.is_none()
+                        && self.root_filter.is_none()
+                        && !self.only_available
+                })
+                .filter(|origin| origin.matches_search(&self.search))
+                .collect::<Vec<_>>();
+            if !origins.is_empty() {
+                ui.label(egui::RichText::new(t!("compendium.origins")).strong());
+                for origin in origins {
+                    self.entry_button(
+                        ui,
+                        CompendiumEntry::Origin,
+                        origin.get_name(),
+                        None,
+                        level_numerals,
+                    );
+                }
+            }
+        }
+        if self.show_classes {
+            let classes = class_cache
+                .get_classes()
+                .into_iter()
+                .filter(|class| self.class_matches_filters(class, &class_cache))
+                .collect::<Vec<_>>();
+            if !classes.is_empty() {
+                ui.label(egui::RichText::new(t!("compendium.classes")).strong());
+                for class in classes {
+                    self.entry_button(
+                        ui,
+                        CompendiumEntry::Class,
+                        class.get_name(),
+                        *class.get_level(),
+                        level_numerals,
+                    );
+                }
+            }
+        }
+        if self.show_glossary {
+            let search = self.search.to_lowercase();
+            let terms = class_cache
+                .get_glossary()
+                .get_entries()
+                .iter()
+                .filter(|entry| {
+                    entry.get_term().to_lowercase().contains(&search)
+                        || entry.get_definition().to_lowercase().contains(&search)
+                })
+                .map(|entry| entry.get_term().to_owned())
+                .collect::<Vec<_>>();
+            if !terms.is_empty() {
+                ui.label(egui::RichText::new(t!("compendium.glossary")).strong());
+                for term in terms {
+                    let selected =
+                        self.selected.as_ref() == Some(&(CompendiumEntry::Keyword, term.clone()));
+                    if ui.selectable_label(selected, &term).clicked() {
+                        self.selected = Some((CompendiumEntry::Keyword, term));
+                    }
+                }
+            }
+        }
+        if self.show_idea_bank {
+            let drafts = class_cache
+                .get_idea_bank()
+                .iter()
+                .filter(|draft| self.draft_matches_filters(draft))
+                .collect::<Vec<_>>();
+            if !drafts.is_empty() {
+                ui.label(egui::RichText::new(t!("compendium.idea_bank")).strong());
+                for draft in drafts {
+                    self.entry_button(
+                        ui,
+                        CompendiumEntry::Draft,
+                        draft.get_name().to_owned(),
+                        draft.get_level(),
+                        level_numerals,
+                    );
+                }
+            }
+        }
+
+        subclass_report(ui, &class_cache);
+    }
+
+    fn entry_button(
+        &mut self,
+        ui: &mut egui::Ui,
+        kind: CompendiumEntry,
+        name: String,
+        level: Option<usize>,
+        level_numerals: LevelNumerals,
+    ) {
+        let label = match level {
+            Some(level) => t!(
+                "compendium.entry_level",
+                name = name,
+                level = i18n::level(level, level_numerals)
+            ),
+            None => name.clone(),
+        };
+        let selected = self.selected.as_ref() == Some(&(kind, name.clone()));
+        if ui.selectable_label(selected, label).clicked() {
+            self.selected = Some((kind, name));
+        }
+    }
+
+    fn class_matches_filters(&self, class: &Class, class_cache: &ClassCache) -> bool {
+        class.matches_search(&self.search)
+            && self
+                .level_filter
+                .is_none_or(|level| *class.get_level() == Some(level))
+            && self
+                .root_filter
+                .as_ref()
+                .is_none_or(|root| class_cache.get_prerequisite_roots(class).contains(root))
+            && (!self.only_available || self.is_available(class, class_cache) == Some(true))
+    }
+
+    /**
+     * Drafts aren't playable, so they're hidden while filtering on prerequisites or availability
+     */
+    fn draft_matches_filters(&self, draft: &DraftClass) -> bool {
+        draft.matches_search(&self.search)
+            && self
+                .level_filter
+                .is_none_or(|level| draft.get_level() == Some(level))
+            && self.root_filter.is_none()
+            && !self.only_available
+    }
+
+    /**
+     * Whether the focused character could take the class next, None without a character
+     */
+    fn is_available(&self, class: &Class, class_cache: &ClassCache) -> Option<bool> {
+        self.focused_character.as_ref().map(|character| {
+            !character.get_classes().contains(&class.get_id())
+                && class.get_class_available(
+                    &class_cache.map_to_concrete_classes(character.get_classes()),
+                )
+        })
+    }
+
+    /**
+     * Where a class sits among the others and whether the focused character can take it
+     */
+    fn class_summary(
+        &self,
+        ui: &mut egui::Ui,
+        class: &Class,
+        class_cache: &ClassCache,
+        level_numerals: LevelNumerals,
+    ) {
+        if let Some(level) = *class.get_level() {
+            ui.label(t!(
+                "compendium.level_value",
+                level = i18n::level(level, level_numerals)
+            ));
+        }
+        let parents = class.get_parent_classes(class_cache);
+        if !parents.is_empty() {
+            ui.label(t!("compendium.requires", requirements = parents.join(", ")));
+        }
+        if !class.get_subclasses().is_empty() {
+            ui.label(t!(
+                "compendium.subclasses",
+                subclasses = class.get_subclasses().join(", ")
+            ));
+        }
+        ui.label(t!(
+            "compendium.prerequisite_roots",
+            roots = class_cache.get_prerequisite_roots(class).join(", ")
+        ));
+        match self.is_available(class, class_cache) {
+            Some(true) => ui.label(t!("compendium.available")),
+            Some(false) => ui.label(t!("compendium.unavailable")),
+            None => ui.label(t!("compendium.no_focus")),
+        };
+    }
+
+    fn detail_view(&mut self, ui: &mut egui::Ui, level_numerals: LevelNumerals) {
+        let class_cache = self.class_cache.borrow();
+        let Some((kind, name)) = &self.selected else {
+            ui.label(t!("compendium.select_entry"));
+            return;
+        };
+        let term_index = TermIndex::new(&class_cache);
+        let class = match kind {
+            CompendiumEntry::Origin => {
+                match class_cache.find_origin(name) {
+                    Some(origin) => origin_details(ui, &term_index, origin),
+                    None => {
+                        ui.label(t!("compendium.missing", name = name));
+                    }
+                }
+                return;
+            }
+            CompendiumEntry::Class => class_cache.find_class(name),
+            CompendiumEntry::Keyword => {
+                match class_cache.get_glossary().get_definition(name) {
+                    Some(definition) => {
+                        ui.heading(name);
+                        ui.label(t!("compendium.keyword"));
+                        ui.add_space(8.0);
+                        linked_description(ui, &term_index.link(definition));
+                    }
+                    None => {
+                        ui.label(t!("compendium.missing", name = name));
+                    }
+                }
+                return;
+            }
+            CompendiumEntry::Draft => {
+                let draft = class_cache
+                    .get_idea_bank()
+                    .iter()
+                    .find(|draft| draft.get_name() == name);
+                draft_details(ui, &term_index, name, draft, level_numerals);
+                return;
+            }
+        };
+        let Some(class) = class else {
+            ui.label(t!("compendium.missing", name = name));
+            return;
+        };
+
+        ui.heading(class.get_name());
+        self.class_summary(ui, class, &class_cache, level_numerals);
+        ability_sections(ui, &term_index, class);
+    }
+}
+
+fn origin_details(ui: &mut egui::Ui, term_index: &TermIndex, origin: &Origin) {
+    ui.heading(origin.get_name());
+    ui.label(t!("compendium.origin"));
+    match origin.get_rules().get_bonus_classes() {
+        0 => {}
+        1 => {
+            ui.label(t!("compendium.bonus_class"));
+        }
+        bonus_classes => {
+            ui.label(t!(
+                "compendium.bonus_classes",
+                count = i18n::number(bonus_classes)
+            ));
+        }
+    }
+    if let Some(source) = origin.as_ability_source() {
+        ability_sections(ui, term_index, &source);
+    }
+}
+
+fn ability_sections(ui: &mut egui::Ui, term_index: &TermIndex, class: &Class) {
+    detail_section(
+        ui,
+        term_index,
+        &t!("compendium.utilities"),
+        class
+            .get_utilities()
+            .iter()
+            .map(|utility| (utility.get_name(), utility.get_description())),
+    );
+    detail_section(
+        ui,
+        term_index,
+        &t!("compendium.passives"),
+        class
+            .get_passives()
+            .iter()
+            .map(|passive| (passive.get_name(), passive.get_description())),
+    );
+    let primary = class.get_primary_action();
+    let secondary = class.get_secondary_action();
+    let special = class.get_special_action();
+    detail_section(
+        ui,
+        term_index,
+        &t!("compendium.primary"),
+        std::iter::once((primary.get_name(), primary.get_description())),
+    );
+    detail_section(
+        ui,
+        term_index,
+        &t!("compendium.secondary"),
+        std::iter::once((secondary.get_name(), secondary.get_description())),
+    );
+    detail_section(
+        ui,
+        term_index,
+        &t!("compendium.special"),
+        std::iter::once((special.get_name(), special.get_description())),
+    );
+}
+
+fn draft_details(
+    ui: &mut egui::Ui,
+    term_index: &TermIndex,
+    name: &str,
+    draft: Option<&DraftClass>,
+    level_numerals: LevelNumerals,
+) {
+    let Some(draft) = draft else {
+        ui.label(t!("compendium.missing", name = name));
+        return;
+    };
+    ui.heading(draft.get_name());
+    ui.label(t!("compendium.draft"));
+    if let Some(level) = draft.get_level() {
+        ui.label(t!(
+            "compendium.level_value",
+            level = i18n::level(level, level_numerals)
+        ));
+    }
+    if let Some(requirements) = draft.get_requirements() {
+        ui.label(t!("compendium.requires", requirements = requirements));
+    }
+    if !draft.get_notes().is_empty() {
+        ui.add_space(8.0);
+        linked_description(ui, &term_index.link(draft.get_notes()));
+    }
+}
+
+fn detail_section(
+    ui: &mut egui::Ui,
+    term_index: &TermIndex,
+    title: &str,
+    entries: impl Iterator<Item = (String, String)>,
+) {
+    let entries = entries
+        .filter(|(name, _)| !name.is_empty())
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return;
+    }
+    ui.add_space(8.0);
+    ui.label(egui::RichText::new(title).strong());
+    ui.group(|ui| {
+        for (name, description) in entries {
+            ui.label(egui::RichText::new(name).strong());
+            linked_description(ui, &term_index.link(&description));
+        }
+    });
+}
+
+fn subclass_report(ui: &mut egui::Ui, class_cache: &ClassCache) {
+    let report = class_cache.get_subclass_report();
+    if !report.is_empty() {
+        ui.add_space(8.0);
+        egui::CollapsingHeader::new(t!(
+            "compendium.subclass_mismatches",
+            count = i18n::number(report.len())
+        ))
+        .id_source("compendium_subclass_report")
+        .show(ui, |ui| {
+            for mismatch in &report {
+                ui.label(subclass_mismatch(mismatch));
+            }
+        });
+    }
+}
+
+/**
+ * The mismatch in the current language, with class names as the rules write them
+ */
+fn subclass_mismatch(mismatch: &SubclassMismatch) -> String {
+    match mismatch {
+        SubclassMismatch::UnknownSubclass { class, subclass } => t!(
+            "compendium.mismatch_unknown_subclass",
+            class = class,
+            subclass = subclass
+        ),
+        SubclassMismatch::MissingPrerequisite { class, subclass } => t!(
+            "compendium.mismatch_missing_prerequisite",
+            class = class,
+            subclass = subclass
+        ),
+        SubclassMismatch::Unlisted { class, subclass } => t!(
+            "compendium.mismatch_unlisted",
+            class = class,
+            subclass = subclass
+        ),
+    }
+}
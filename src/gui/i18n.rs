@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info};
+
+use crate::model::comparison::AbilityCategory;
+use crate::util::LevelNumerals;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Language every other catalogue falls back to, it must contain every UI string
+pub const BASE_LANGUAGE: &str = "en";
+
+static BUILT_IN_CATALOGUES: [(&str, &str); 2] = [
+    ("en", include_str!("../../locales/en.ron")),
+    ("es", include_str!("../../locales/es.ron")),
+];
+
+static LOCALIZER: LazyLock<RwLock<Localizer>> =
+    LazyLock::new(|| RwLock::new(Localizer::built_in()));
+
+#[derive(Error, Debug)]
+pub enum LoadCatalogueError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ron::error::SpannedError),
+}
+
+/**
+ * Translated UI strings for one language, with `{name}` placeholders for arguments
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Catalogue {
+    language: String,
+    #[serde(default = "default_decimal_separator")]
+    decimal_separator: String,
+    #[serde(default = "default_group_separator")]
+    group_separator: String,
+    strings: BTreeMap<String, String>,
+}
+
+fn default_decimal_separator() -> String {
+    ".".to_owned()
+}
+
+fn default_group_separator() -> String {
+    ",".to_owned()
+}
+
+impl Catalogue {
+    pub fn from_ron(text: &str) -> Result<Catalogue, LoadCatalogueError> {
+        Ok(ron::from_str(text)?)
+    }
+
+    pub fn get_language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+
+    /**
+     * Group thousands and swap the decimal point using this language's separators
+     */
+    pub fn format_number(&self, number: &str) -> String {
+        let (sign, unsigned) = match number.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", number),
+        };
+        let (whole, fraction) = match unsigned.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (unsigned, None),
+        };
+        let mut grouped = String::new();
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                grouped.push_str(&self.group_separator);
+            }
+            grouped.push(digit);
+        }
+        match fraction {
+            Some(fraction) => format!("{sign}{grouped}{}{fraction}", self.decimal_separator),
+            None => format!("{sign}{grouped}"),
+        }
+    }
+}
+
+/**
+ * Every known catalogue and the language currently shown
+ */
+#[derive(Debug, Clone)]
+pub struct Localizer {
+    catalogues: BTreeMap<String, Catalogue>,
+    current: String,
+}
+
+impl Localizer {
+    fn built_in() -> Localizer {
+        let catalogues = BUILT_IN_CATALOGUES
+            .iter()
+            .filter_map(|(code, text)| match Catalogue::from_ron(text) {
+                Ok(catalogue) => Some(((*code).to_owned(), catalogue)),
+                Err(err) => {
+                    error!("Built in catalogue '{code}' is invalid: {err}");
+                    None
+                }
+            })
+            .collect();
+        Localizer {
+            catalogues,
+            current: BASE_LANGUAGE.to_owned(),
+        }
+    }
+
+    fn current_catalogue(&self) -> Option<&Catalogue> {
+        self.catalogues.get(&self.current)
+    }
+
+    fn base_catalogue(&self) -> Option<&Catalogue> {
+        self.catalogues.get(BASE_LANGUAGE)
+    }
+
+    /**
+     * Look a key up in the current language, then English, then show the key itself
+     */
+    fn translate(&self, key: &str) -> String {
+        self.current_catalogue()
+            .and_then(|catalogue| catalogue.get(key))
+            .or_else(|| {
+                self.base_catalogue()
+                    .and_then(|catalogue| catalogue.get(key))
+            })
+            .unwrap_or(key)
+            .to_owned()
+    }
+}
+
+/**
+ * Add every `<code>.ron` catalogue in a directory, replacing built in ones with the same code
+ */
+pub fn load_catalogues_from_dir(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.extension().is_none_or(|extension| extension != "ron") {
+            continue;
+        }
+        let Some(code) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(LoadCatalogueError::from)
+            .and_then(|text| Catalogue::from_ron(&text));
+        match loaded {
+            Ok(catalogue) => {
+                info!("Loaded '{code}' catalogue from {}", path.display());
+                LOCALIZER
+                    .write()
+                    .unwrap()
+                    .catalogues
+                    .insert(code, catalogue);
+            }
+            Err(err) => error!("Unable to load catalogue {}: {err}", path.display()),
+        }
+    }
+}
+
+/**
+ * Codes and names of every language that can be picked
+ */
+pub fn get_languages() -> Vec<(String, String)> {
+    LOCALIZER
+        .read()
+        .unwrap()
+        .catalogues
+        .iter()
+        .map(|(code, catalogue)| (code.clone(), catalogue.get_language().to_owned()))
+        .collect()
+}
+
+pub fn get_language() -> String {
+    LOCALIZER.read().unwrap().current.clone()
+}
+
+/**
+ * Switch language, unknown codes fall back to English
+ */
+pub fn set_language(code: &str) {
+    let mut localizer = LOCALIZER.write().unwrap();
+    localizer.current = if localizer.catalogues.contains_key(code) {
+        code.to_owned()
+    } else {
+        BASE_LANGUAGE.to_owned()
+    };
+}
+
+pub fn tr(key: &str) -> String {
+    LOCALIZER.read().unwrap().translate(key)
+}
+
+/**
+ * Translate and fill in `{name}` placeholders
+ */
+pub fn tr_args(key: &str, args: &[(&str, String)]) -> String {
+    let mut text = tr(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+    text
+}
+
+/**
+ * Format a number with the current language's separators
+ */
+pub fn number<T: std::fmt::Display>(value: T) -> String {
+    let localizer = LOCALIZER.read().unwrap();
+    let text = value.to_string();
+    match localizer
+        .current_catalogue()
+        .or_else(|| localizer.base_catalogue())
+    {
+        Some(catalogue) => catalogue.format_number(&text),
+        None => text,
+    }
+}
+
+/**
+ * Format a class level in the given numerals, Arabic ones following the language
+ */
+pub fn level(level: usize, level_numerals: LevelNumerals) -> String {
+    match level_numerals {
+        LevelNumerals::Roman => LevelNumerals::Roman.format(level),
+        LevelNumerals::Arabic => number(level),
+    }
+}
+
+/**
+ * Translate a UI string by its catalogue key, filling placeholders from `name = value` pairs
+ */
+macro_rules! t {
+    ($key:literal) => {
+        $crate::gui::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::gui::i18n::tr_args($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+pub(crate) use t;
+
+/**
+ * Name of an ability category in the current language
+ */
+pub fn ability_category(category: AbilityCategory) -> String {
+    match category {
+        AbilityCategory::Utilities => t!("ability_category.utilities"),
+        AbilityCategory::Passives => t!("ability_category.passives"),
+        AbilityCategory::Primary => t!("ability_category.primary"),
+        AbilityCategory::Secondary => t!("ability_category.secondary"),
+        AbilityCategory::Special => t!("ability_category.special"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeSet;
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    /**
+     * Every key used with `t!` in the source tree
+     */
+    fn used_keys() -> BTreeSet<String> {
+        fn visit(dir: &Path, keys: &mut BTreeSet<String>) {
+            for path in std::fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+            {
+                if path.is_dir() {
+                    visit(&path, keys);
+                } else if path.extension().is_some_and(|extension| extension == "rs") {
+                    let source = std::fs::read_to_string(&path).unwrap();
+                    for (index, _) in source.match_indices("t!(") {
+                        // Skip other macros ending in t, such as format! and assert!, and string literals
+                        if source[..index].chars().next_back().is_some_and(|previous| {
+                            previous.is_alphanumeric() || matches!(previous, '_' | '"')
+                        }) {
+                            continue;
+                        }
+                        let usage = source[index + 3..].trim_start();
+                        if let Some(key) = usage
+                            .strip_prefix('"')
+                            .and_then(|usage| usage.split('"').next())
+                        {
+                            keys.insert(key.to_owned());
+                        }
+                    }
+                }
+            }
+        }
+        let mut keys = BTreeSet::new();
+        visit(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut keys,
+        );
+        keys
+    }
+
+    #[test]
+    fn test_base_catalogue_has_every_ui_string() {
+        let localizer = Localizer::built_in();
+        let base = localizer.base_catalogue().unwrap();
+
+        let missing = used_keys()
+            .into_iter()
+            .filter(|key| base.get(key).is_none())
+            .collect::<Vec<_>>();
+
+        assert!(missing.is_empty(), "Missing from en.ron: {missing:?}");
+    }
+
+    #[test]
+    fn test_translations_match_base_catalogue() {
+        let localizer = Localizer::built_in();
+        assert_eq!(localizer.catalogues.len(), BUILT_IN_CATALOGUES.len());
+        let base = localizer.base_catalogue().unwrap();
+
+        for (code, catalogue) in &localizer.catalogues {
+            for key in catalogue.strings.keys() {
+                let base_text = base
+                    .get(key)
+                    .unwrap_or_else(|| panic!("'{code}' has unknown key {key}"));
+                assert_eq!(
+                    placeholders(catalogue.get(key).unwrap()),
+                    placeholders(base_text),
+                    "'{code}' placeholders differ for {key}"
+                );
+            }
+        }
+    }
+
+    /**
+     * Falling back to English works, but a shipped translation should never need it
+     */
+    #[test]
+    fn test_built_in_translations_are_complete() {
+        let localizer = Localizer::built_in();
+        let base = localizer.base_catalogue().unwrap();
+
+        for (code, catalogue) in &localizer.catalogues {
+            let missing = base
+                .strings
+                .keys()
+                .filter(|key| !catalogue.strings.contains_key(*key))
+                .collect::<Vec<_>>();
+            assert!(missing.is_empty(), "'{code}' is missing {missing:?}");
+        }
+    }
+
+    #[test]
+    fn test_fallback_to_base_language() {
+        let mut localizer = Localizer::built_in();
+        localizer.catalogues.insert(
+            "xx".to_owned(),
+            Catalogue::from_ron("(language: \"Test\", strings: {\"menu.about\": \"Über\"})")
+                .unwrap(),
+        );
+        localizer.current = "xx".to_owned();
+
+        assert_eq!(localizer.translate("menu.about"), "Über");
+        assert_eq!(localizer.translate("menu.settings"), "Settings");
+        assert_eq!(localizer.translate("no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn test_format_number() {
+        let english = Catalogue::from_ron("(language: \"English\", strings: {})").unwrap();
+        let spanish = Catalogue::from_ron(
+            "(language: \"Español\", decimal_separator: \",\", group_separator: \".\", strings: {})",
+        )
+        .unwrap();
+
+        assert_eq!(english.format_number("1234567"), "1,234,567");
+        assert_eq!(english.format_number("-1234.5"), "-1,234.5");
+        assert_eq!(english.format_number("12"), "12");
+        assert_eq!(spanish.format_number("1234.5"), "1.234,5");
+    }
+}
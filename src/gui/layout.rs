@@ -0,0 +1,281 @@
+use std::{cell::RefCell, ffi::OsString};
+
+use egui_dock::{DockState, NodeIndex, TabIndex};
+use serde::{Deserialize, Serialize};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * A tab as stored in a saved layout, campaigns are referenced by their save file
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TabRef {
+    /// Campaigns that were never saved have no path and can only be matched while still open
+    Campaign {
+        path: Option<OsString>,
+        name: String,
+    },
+    Compendium,
+    Comparison,
+    Lint,
+}
+
+impl TabRef {
+    /**
+     * Whether this reference points at the same tab as another, preferring the save path
+     */
+    pub fn refers_to(&self, other: &TabRef) -> bool {
+        match (self, other) {
+            (
+                TabRef::Campaign {
+                    path: Some(path), ..
+                },
+                TabRef::Campaign {
+                    path: Some(other_path),
+                    ..
+                },
+            ) => path == other_path,
+            (
+                TabRef::Campaign { path: None, name },
+                TabRef::Campaign {
+                    path: None,
+                    name: other_name,
+                },
+            ) => name == other_name,
+            (TabRef::Compendium, TabRef::Compendium)
+            | (TabRef::Comparison, TabRef::Comparison)
+            | (TabRef::Lint, TabRef::Lint) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_campaign(&self) -> bool {
+        matches!(self, TabRef::Campaign { .. })
+    }
+}
+
+/**
+ * A layout saved under a name so it can be switched to from the menu
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutPreset {
+    name: String,
+    layout: DockState<TabRef>,
+}
+
+impl LayoutPreset {
+    pub fn new<T: Into<String>>(name: T, layout: DockState<TabRef>) -> LayoutPreset {
+        LayoutPreset {
+            name: name.into(),
+            layout,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_layout(&self) -> &DockState<TabRef> {
+        &self.layout
+    }
+}
+
+/**
+ * Built in arrangements of whatever tabs are currently open
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinLayout {
+    /// Every campaign side by side with the comparison underneath
+    GameMaster,
+    /// Campaigns as tabs with the compendium beside them
+    Player,
+}
+
+impl BuiltinLayout {
+    pub const ALL: [BuiltinLayout; 2] = [BuiltinLayout::GameMaster, BuiltinLayout::Player];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            BuiltinLayout::GameMaster => "GM Layout",
+            BuiltinLayout::Player => "Player Layout",
+        }
+    }
+
+    /**
+     * Arrange the open tabs, adding the reference tab the layout is built around
+     */
+    pub fn arrange(self, open_tabs: &[TabRef]) -> DockState<TabRef> {
+        let campaigns = open_tabs
+            .iter()
+            .filter(|tab| tab.is_campaign())
+            .cloned()
+            .collect::<Vec<_>>();
+        let others = open_tabs
+            .iter()
+            .filter(|tab| !tab.is_campaign())
+            .cloned()
+            .collect::<Vec<_>>();
+        match self {
+            BuiltinLayout::GameMaster => {
+                let mut bottom = others;
+                if !bottom.contains(&TabRef::Comparison) {
+                    bottom.insert(0, TabRef::Comparison);
+                }
+                if campaigns.is_empty() {
+                    return DockState::new(bottom);
+                }
+                let mut dock_state = DockState::new(vec![campaigns[0].clone()]);
+                let tree = dock_state.main_surface_mut();
+                let [top, _] = tree.split_below(NodeIndex::root(), 0.7, bottom);
+                let mut remaining = top;
+                for (index, campaign) in campaigns.iter().enumerate().skip(1) {
+                    // Each split hands the new column an equal share of what is left
+                    #[allow(clippy::cast_precision_loss)]
+                    let fraction = 1.0 / (campaigns.len() - index + 1) as f32;
+                    let [_, right] = tree.split_right(remaining, fraction, vec![campaign.clone()]);
+                    remaining = right;
+                }
+                dock_state
+            }
+            BuiltinLayout::Player => {
+                let mut side = others;
+                if !side.contains(&TabRef::Compendium) {
+                    side.insert(0, TabRef::Compendium);
+                }
+                if campaigns.is_empty() {
+                    return DockState::new(side);
+                }
+                let mut dock_state = DockState::new(campaigns);
+                dock_state
+                    .main_surface_mut()
+                    .split_right(NodeIndex::root(), 0.65, side);
+                dock_state
+            }
+        }
+    }
+}
+
+/**
+ * Rebuild a saved layout, reusing already open tabs where the layout refers to them
+ * and opening the rest. Open tabs the layout doesn't mention are kept in the first leaf.
+ */
+pub fn restore_layout<Tab>(
+    layout: &DockState<TabRef>,
+    open_tabs: Vec<Tab>,
+    refers_to: impl Fn(&Tab, &TabRef) -> bool,
+    open_tab: impl FnMut(&TabRef) -> Option<Tab>,
+) -> DockState<Tab> {
+    let open_tabs = RefCell::new(open_tabs.into_iter().map(Some).collect::<Vec<_>>());
+    let open_tab = RefCell::new(open_tab);
+    let mut restored = layout.filter_map_tabs(|tab_ref| {
+        let reused = open_tabs
+            .borrow_mut()
+            .iter_mut()
+            .find(|tab| tab.as_ref().is_some_and(|tab| refers_to(tab, tab_ref)))
+            .and_then(Option::take);
+        reused.or_else(|| (open_tab.borrow_mut())(tab_ref))
+    });
+
+    for (_, node) in restored.iter_all_nodes_mut() {
+        if let egui_dock::node::Node::Leaf { tabs, active, .. } = node {
+            if active.0 >= tabs.len() {
+                *active = TabIndex(tabs.len().saturating_sub(1));
+            }
+        }
+    }
+    for tab in open_tabs.into_inner().into_iter().flatten() {
+        restored.push_to_first_leaf(tab);
+    }
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn campaign(path: &str) -> TabRef {
+        TabRef::Campaign {
+            path: Some(path.into()),
+            name: path.to_owned(),
+        }
+    }
+
+    fn tabs_in_order(dock_state: &DockState<TabRef>) -> Vec<TabRef> {
+        dock_state
+            .iter_all_tabs()
+            .map(|(_, tab)| tab.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_refers_to() {
+        assert!(campaign("a.ron").refers_to(&campaign("a.ron")));
+        assert!(!campaign("a.ron").refers_to(&campaign("b.ron")));
+        let unsaved = TabRef::Campaign {
+            path: None,
+            name: "a.ron".to_owned(),
+        };
+        assert!(!unsaved.refers_to(&campaign("a.ron")));
+        assert!(TabRef::Compendium.refers_to(&TabRef::Compendium));
+    }
+
+    #[test]
+    fn test_builtin_layouts_keep_every_tab() {
+        let open_tabs = vec![campaign("a.ron"), campaign("b.ron"), campaign("c.ron")];
+
+        let game_master = BuiltinLayout::GameMaster.arrange(&open_tabs);
+        let player = BuiltinLayout::Player.arrange(&open_tabs);
+
+        assert_eq!(
+            tabs_in_order(&game_master).len(),
+            open_tabs.len() + 1,
+            "{game_master:?}"
+        );
+        assert!(tabs_in_order(&game_master).contains(&TabRef::Comparison));
+        assert_eq!(
+            tabs_in_order(&player),
+            vec![
+                campaign("a.ron"),
+                campaign("b.ron"),
+                campaign("c.ron"),
+                TabRef::Compendium
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_layout() {
+        let mut layout = DockState::new(vec![campaign("a.ron"), campaign("missing.ron")]);
+        layout
+            .main_surface_mut()
+            .split_right(NodeIndex::root(), 0.5, vec![campaign("b.ron")]);
+
+        let restored = restore_layout(
+            &layout,
+            vec![campaign("b.ron"), campaign("cli.ron")],
+            TabRef::refers_to,
+            |tab_ref| (tab_ref != &campaign("missing.ron")).then(|| tab_ref.clone()),
+        );
+
+        assert_eq!(
+            tabs_in_order(&restored),
+            vec![campaign("a.ron"), campaign("cli.ron"), campaign("b.ron")]
+        );
+    }
+}
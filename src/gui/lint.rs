@@ -0,0 +1,177 @@
+use super::i18n::{self, t};
+use crate::google::lint::{LintIssue, LintPosition, LintProblem};
+use crate::util::LevelNumerals;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * Tab listing what in the active rules document strays from the expected format, for the
+ * people editing it. The app runs the check and hands the issues over.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintGui {
+    /// Profile the shown issues are for
+    profile_name: Option<String>,
+    issues: Option<Result<Vec<LintIssue>, String>>,
+    running: bool,
+    run_requested: bool,
+}
+
+impl LintGui {
+    /**
+     * A new tab, asking for a check straight away
+     */
+    pub fn new() -> LintGui {
+        LintGui {
+            profile_name: None,
+            issues: None,
+            running: false,
+            run_requested: true,
+        }
+    }
+
+    /**
+     * Whether a check was asked for since the last call
+     */
+    pub fn take_run_request(&mut self) -> bool {
+        std::mem::take(&mut self.run_requested)
+    }
+
+    pub fn set_running(&mut self, profile_name: String) {
+        self.profile_name = Some(profile_name);
+        self.running = true;
+    }
+
+    pub fn set_issues(&mut self, issues: Result<Vec<LintIssue>, String>) {
+        self.issues = Some(issues);
+        self.running = false;
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.running, egui::Button::new(t!("lint.run")))
+                .clicked()
+            {
+                self.run_requested = true;
+            }
+            if self.running {
+                ui.spinner();
+            }
+            if let Some(profile_name) = &self.profile_name {
+                ui.label(t!("lint.profile", name = profile_name));
+            }
+        });
+        ui.separator();
+
+        match &self.issues {
+            None => {}
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, t!("lint.failed", error = err));
+            }
+            Some(Ok(issues)) if issues.is_empty() => {
+                ui.label(t!("lint.no_issues"));
+            }
+            Some(Ok(issues)) => {
+                ui.label(t!("lint.issue_count", count = i18n::number(issues.len())));
+                ui.add_space(4.0);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("lint_issues")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for issue in issues {
+                                ui.label(issue.get_position().map_or_else(String::new, position));
+                                ui.label(problem(issue.get_problem()));
+                                ui.label(egui::RichText::new(issue.get_text()).monospace().weak());
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+        }
+    }
+}
+
+fn position(position: LintPosition) -> String {
+    match position {
+        LintPosition::Line(line) => t!("lint.line", line = i18n::number(line)),
+        LintPosition::Paragraph(paragraph) => {
+            t!("lint.paragraph", paragraph = i18n::number(paragraph))
+        }
+    }
+}
+
+/**
+ * The problem in the current language. Section names and levels are left as the rules
+ * document writes them.
+ */
+fn problem(problem: &LintProblem) -> String {
+    let roman = |level| LevelNumerals::Roman.format(level);
+    match problem {
+        LintProblem::Unparsable(error) => t!("lint.unparsable", error = error),
+        LintProblem::MissingOrigins => t!("lint.missing_origins"),
+        LintProblem::MissingBonusClasses { unit } => t!("lint.missing_bonus_classes", unit = unit),
+        LintProblem::MissingSection { unit, section } => {
+            t!("lint.missing_section", unit = unit, section = section)
+        }
+        LintProblem::MisorderedSection {
+            unit,
+            section,
+            after,
+        } => t!(
+            "lint.misordered_section",
+            unit = unit,
+            section = section,
+            after = after
+        ),
+        LintProblem::EmptySection { unit, section } => {
+            t!("lint.empty_section", unit = unit, section = section)
+        }
+        LintProblem::UnnamedAbility { unit, section } => {
+            t!("lint.unnamed_ability", unit = unit, section = section)
+        }
+        LintProblem::MissingDescription { unit, ability } => {
+            t!("lint.missing_description", unit = unit, ability = ability)
+        }
+        LintProblem::DuplicateName { name, first } => {
+            t!("lint.duplicate_name", name = name, first = position(*first))
+        }
+        LintProblem::InvalidLevel { class, level } => {
+            t!("lint.invalid_level", class = class, level = level)
+        }
+        LintProblem::UnknownRequirement { class, requirement } => t!(
+            "lint.unknown_requirement",
+            class = class,
+            requirement = requirement
+        ),
+        LintProblem::RequirementLevel {
+            class,
+            level,
+            requirement,
+            requirement_level,
+        } => t!(
+            "lint.requirement_level",
+            class = class,
+            level = roman(*level),
+            requirement = requirement,
+            requirement_level = roman(*requirement_level)
+        ),
+    }
+}
@@ -1,24 +1,41 @@
 use super::campaign::CampaignGui;
+use super::commands::{AppCommand, CommandPalette, Keybindings};
+use super::comparison::ComparisonGui;
+use super::compendium::CompendiumGui;
+use super::i18n::{self, t};
+use super::layout::{restore_layout, BuiltinLayout, LayoutPreset, TabRef};
+use super::lint::LintGui;
 use super::state::AppState;
-use super::tabs::CampaignTabViewer;
-use crate::google::GetOriginsAndClassesError;
-use crate::gui::util::{error_log_and_notify, info_log_and_notify};
+use super::tabs::{focused_campaign, CampaignTabViewer, GreedTab};
+use super::widgets::description::take_clicked_term;
+use crate::api::snapshot::CampaignSnapshot;
+use crate::api::{self, ApiAction, ApiActionError, ApiServer};
+use crate::export::share_code::{SharedBuild, SharedBuildReport};
+use crate::export::summary::{parse_summary, render_summary, SummaryDetail, SummaryMarkup};
+use crate::export::{self, sheet::CharacterSheet, SheetFormat};
+use crate::google::lint::{self, LintIssue};
+use crate::google::profile::{
+    supported_file_extensions, RulesProfile, RulesProfiles, SUPPORTED_EXPORT_FORMATS,
+};
+use crate::google::{self, GetOriginsAndClassesError, GetUpdateTimeError};
+use crate::gui::util::{error_log_and_notify, info_log_and_notify, warning_log_and_notify};
 use crate::model::classes::{Class, ClassCache};
+use crate::model::glossary::TermLink;
 use crate::model::save::{Save, SaveWithPath};
+use crate::util::LevelNumerals;
 
 use eframe::egui;
 use eframe::glow::Context;
 use eframe::Storage;
 use egui::emath::Numeric;
-use egui::TextStyle;
-use egui_dock::{DockState, Style};
+use egui_dock::{DockState, NodeIndex, Style, SurfaceIndex, TabIndex};
 use egui_notify::Toasts;
 use rfd::{FileDialog, MessageDialog, MessageDialogResult};
 use self_update::cargo_crate_version;
 use tokio::join;
 use tokio::runtime::Runtime;
 use tokio::task::JoinHandle;
-use tracing::info;
+use tracing::{error, info};
 
 use std::cell::RefCell;
 use std::collections::HashSet;
@@ -63,7 +80,7 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 ";
 
 pub struct GuiGreedApp {
-    dock_state: DockState<CampaignGui>,
+    dock_state: DockState<GreedTab>,
     tab_viewer: CampaignTabViewer,
     app_state: AppState,
     new_campaign_name_entry: String,
@@ -71,18 +88,45 @@ pub struct GuiGreedApp {
     class_cache_rc: Rc<RefCell<ClassCache>>,
     rule_refresh_runtime: Runtime,
     rule_refresh_handle: RefCell<Option<JoinHandle<Result<ClassCache, GetOriginsAndClassesError>>>>,
+    rules_profiles: RulesProfiles,
+    /// Written out with the rest of the app's state when set
+    rules_profiles_dirty: bool,
+    /// Startup check for newer rules than the cached ones
+    rules_update_check: Option<JoinHandle<Result<i64, GetUpdateTimeError>>>,
+    /// Profile being checked with a test fetch
+    rules_profile_test: Option<(
+        usize,
+        JoinHandle<Result<ClassCache, GetOriginsAndClassesError>>,
+    )>,
+    /// Check of the active rules asked for by the lint tab
+    rules_lint: Option<JoinHandle<Result<Vec<LintIssue>, GetOriginsAndClassesError>>>,
     toasts: Toasts,
     random_level: f64,
+    api_server: Option<ApiServer>,
+    summary_markup: SummaryMarkup,
+    summary_import_entry: String,
+    share_code_fingerprint: bool,
+    share_code_entry: String,
+    share_code_name_entry: String,
+    command_palette: CommandPalette,
+    /// Index of the binding waiting for a new shortcut
+    rebinding: Option<usize>,
+    layout_name_entry: String,
+    /// Set once the window is closing, the layout was stored before tabs started closing
+    closing: bool,
 }
 
 impl GuiGreedApp {
     pub fn new(
         class_cache: ClassCache,
         campaigns: &[OsString],
-        app_state: AppState,
+        mut app_state: AppState,
+        rules_profiles: RulesProfiles,
     ) -> GuiGreedApp {
         info!("Starting up app!");
 
+        app_state.keybindings_mut().add_missing_defaults();
+
         let class_cache_rc = Rc::new(RefCell::new(class_cache));
 
         let rule_refresh_runtime = tokio::runtime::Builder::new_multi_thread()
@@ -93,6 +137,14 @@ impl GuiGreedApp {
 
         let mut toasts = Toasts::default();
 
+        let rules_update_check = match rules_profiles.get_active().get_source() {
+            Ok(source) => Some(rule_refresh_runtime.spawn(google::get_update_time(source))),
+            Err(err) => {
+                error_log_and_notify(&mut toasts, t!("rules.update_check_failed", error = err));
+                None
+            }
+        };
+
         let mut campaign_guis: Vec<CampaignGui> = campaigns
             .iter()
             .map(SaveWithPath::from_path)
@@ -108,7 +160,16 @@ impl GuiGreedApp {
             .iter_mut()
             .for_each(CampaignGui::refresh_campaign);
 
-        let dock_state = DockState::new(campaign_guis);
+        let open_tabs = campaign_guis.into_iter().map(GreedTab::from).collect();
+        let dock_state = match app_state.get_dock_layout() {
+            Some(layout) => restore_layout(
+                layout,
+                open_tabs,
+                |tab, tab_ref| tab.to_tab_ref().refers_to(tab_ref),
+                |tab_ref| GuiGreedApp::open_tab_ref(tab_ref, &class_cache_rc, &mut toasts),
+            ),
+            None => DockState::new(open_tabs),
+        };
 
         GuiGreedApp {
             dock_state,
@@ -119,103 +180,901 @@ impl GuiGreedApp {
             class_cache_rc,
             rule_refresh_runtime,
             rule_refresh_handle: RefCell::new(None),
+            rules_profiles,
+            rules_profiles_dirty: false,
+            rules_update_check,
+            rules_profile_test: None,
+            rules_lint: None,
             toasts,
             random_level: 0.0,
+            api_server: None,
+            summary_markup: SummaryMarkup::default(),
+            summary_import_entry: String::new(),
+            share_code_fingerprint: true,
+            share_code_entry: String::new(),
+            share_code_name_entry: t!("share.default_name"),
+            command_palette: CommandPalette::default(),
+            rebinding: None,
+            layout_name_entry: String::new(),
+            closing: false,
         }
     }
 
     fn menu_panel(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        egui::TopBottomPanel::top("menu").resizable(false).show(ctx, |ui| {
-            egui::menu::bar(ui, |ui| {
-                let about_response = ui.button("About");
-                let about_popup_id = ui.make_persistent_id("about_popup_id");
-                if about_response.clicked() {
-                    ui.memory_mut(|mem| mem.toggle_popup(about_popup_id));
-                }
-                egui::popup_below_widget(ui, about_popup_id, &about_response, |ui| {
-                    ui.set_min_width(450.0);
-                    ui.label(format!("Greed Console Version {}", cargo_crate_version!()));
-                    ui.label(COPYRIGHT_NOTICE);
-                });
+        egui::TopBottomPanel::top("menu")
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::menu::bar(ui, |ui| {
+                    let about_response = ui.button(t!("menu.about"));
+                    let about_popup_id = ui.make_persistent_id("about_popup_id");
+                    if about_response.clicked() {
+                        ui.memory_mut(|mem| mem.toggle_popup(about_popup_id));
+                    }
+                    egui::popup_below_widget(ui, about_popup_id, &about_response, |ui| {
+                        ui.set_min_width(450.0);
+                        ui.label(t!("menu.version", version = cargo_crate_version!()));
+                        ui.label(COPYRIGHT_NOTICE);
+                    });
 
-                ui.menu_button("Settings", |ui| {
-                    ui.checkbox(self.app_state.skip_rules_update_confirmation_mut(), "Skip Confirmation for rules update on start");
-                        ui.label("Font Size:");
-                    ui.horizontal(|ui| {
-                        if ui.button("+").clicked() {
-                            let old_size = self.app_state.get_font_size();
-                            if old_size < 128f32 {
-                                self.app_state.set_font_size(old_size + 1.0);
-                                ctx.style_mut(|style| {
-                                    for (style, font_id) in &mut style.text_styles {
-                                        if style == &TextStyle::Body || style == &TextStyle::Button {
-                                            font_id.size = old_size + 1.0;
-                                        }
-                                    }
-                                });
+                    ui.menu_button(t!("menu.settings"), |ui| {
+                        ui.checkbox(
+                            self.app_state.skip_rules_update_confirmation_mut(),
+                            t!("settings.skip_rules_confirmation"),
+                        );
+                        ui.checkbox(
+                            self.app_state.accessibility_mode_mut(),
+                            t!("settings.accessibility_mode"),
+                        )
+                        .on_hover_text(t!("settings.accessibility_mode_hover"));
+                        ui.menu_button(t!("settings.appearance"), |ui| {
+                            if self.app_state.theme_mut().settings_ui(ui) {
+                                self.app_state.get_theme().apply(ctx);
                             }
+                        });
+                        ui.menu_button(t!("settings.rules"), |ui| self.rules_settings_menu(ui));
+                        ui.menu_button(t!("settings.http_api"), |ui| self.api_settings_menu(ui));
+                        ui.menu_button(t!("settings.language"), |ui| self.language_menu(ui));
+                        ui.menu_button(t!("settings.level_numerals"), |ui| {
+                            self.level_numerals_menu(ui);
+                        });
+                        ui.menu_button(t!("settings.keybindings"), |ui| {
+                            self.keybindings_menu(ui, ctx);
+                        });
+                    });
+
+                    ui.menu_button(t!("menu.campaign"), |ui| {
+                        self.campaign_menu(ui);
+                    });
+
+                    ui.menu_button(t!("menu.layout"), |ui| self.layout_menu(ui));
+
+                    if ui.button(t!("menu.compendium")).clicked() {
+                        let class_cache = self.class_cache_rc.clone();
+                        self.focus_or_open_tab(
+                            |tab| matches!(tab, GreedTab::Compendium(_)),
+                            || GreedTab::Compendium(CompendiumGui::new(class_cache)),
+                        );
+                    }
+
+                    if ui.button(t!("menu.compare")).clicked() {
+                        let class_cache = self.class_cache_rc.clone();
+                        self.focus_or_open_tab(
+                            |tab| matches!(tab, GreedTab::Comparison(_)),
+                            || GreedTab::Comparison(ComparisonGui::new(class_cache)),
+                        );
+                    }
+
+                    if ui.button(t!("menu.lint")).clicked() {
+                        self.focus_or_open_tab(
+                            |tab| matches!(tab, GreedTab::Lint(_)),
+                            || GreedTab::Lint(LintGui::new()),
+                        );
+                    }
+
+                    if focused_campaign(&mut self.dock_state).is_some() {
+                        if ui.button(t!("common.next_battle")).clicked() {
+                            self.perform_on_all_guis_mut(&CampaignGui::next_battle);
                         }
-                        ui.label(self.app_state.get_font_size().to_string());
-                        if ui.button("-").clicked() {
-                            let old_size = self.app_state.get_font_size();
-                            if old_size > 5f32 {
-                                self.app_state.set_font_size(old_size - 1.0);
-                                ctx.style_mut(|style| {
-                                    for (style, font_id) in &mut style.text_styles {
-                                        if style == &TextStyle::Body || style == &TextStyle::Button {
-                                            font_id.size = old_size - 1.0;
-                                        }
+
+                        if ui.button(t!("common.next_turn")).clicked() {
+                            self.perform_on_all_guis_mut(&CampaignGui::next_turn);
+                        }
+                    }
+
+                    self.refresh_rules(ui, frame);
+
+                    ui.hyperlink_to(
+                        t!("menu.ruleset"),
+                        self.rules_profiles.get_active().get_document_url(),
+                    );
+                });
+            });
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn rules_settings_menu(&mut self, ui: &mut egui::Ui) {
+        ui.set_min_width(350.0);
+        let mut switch_to = None;
+        let mut remove = None;
+        let mut test = None;
+        let profile_count = self.rules_profiles.get_profiles().len();
+        for index in 0..profile_count {
+            let name = self.rules_profiles.get_profiles()[index]
+                .get_name()
+                .to_owned();
+            if ui
+                .radio(self.rules_profiles.is_active(index), &name)
+                .clicked()
+            {
+                switch_to = Some(index);
+            }
+            egui::CollapsingHeader::new(t!("rules_profile.edit"))
+                .id_source(("rules_profile", index))
+                .show(ui, |ui| {
+                    egui::Grid::new(("rules_profile_grid", index))
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            let mut new_name = name.clone();
+                            ui.label(t!("rules_profile.name"));
+                            if ui.text_edit_singleline(&mut new_name).changed() {
+                                self.rules_profiles.rename(index, new_name);
+                                self.rules_profiles_dirty = true;
+                            }
+                            ui.end_row();
+
+                            let Some(profile) = self.rules_profiles.profile_mut(index) else {
+                                return;
+                            };
+                            ui.label(t!("rules_profile.document_id"));
+                            let mut changed =
+                                ui.text_edit_singleline(profile.document_id_mut()).changed();
+                            ui.end_row();
+
+                            ui.label(t!("rules_profile.api_key"));
+                            changed |= ui
+                                .add(
+                                    egui::TextEdit::singleline(profile.api_key_mut())
+                                        .password(true)
+                                        .hint_text(if google::has_built_in_api_key() {
+                                            t!("rules_profile.api_key_hint")
+                                        } else {
+                                            t!("rules_profile.api_key_required")
+                                        }),
+                                )
+                                .changed();
+                            ui.end_row();
+
+                            ui.label(t!("rules_profile.export_format"));
+                            let mut export_format = profile.get_export_format().to_owned();
+                            egui::ComboBox::from_id_source(("rules_profile_format", index))
+                                .selected_text(&export_format)
+                                .show_ui(ui, |ui| {
+                                    for format in SUPPORTED_EXPORT_FORMATS {
+                                        ui.selectable_value(
+                                            &mut export_format,
+                                            (*format).to_owned(),
+                                            *format,
+                                        );
                                     }
                                 });
+                            if export_format != profile.get_export_format() {
+                                profile.set_export_format(export_format);
+                                changed = true;
                             }
+                            ui.end_row();
+
+                            ui.label(t!("rules_profile.base_url"));
+                            changed |= ui.text_edit_singleline(profile.base_url_mut()).changed();
+                            ui.end_row();
+
+                            ui.label(t!("rules_profile.local_file"));
+                            ui.horizontal(|ui| {
+                                match profile.get_local_file() {
+                                    Some(path) => ui.label(path.display().to_string()),
+                                    None => ui.weak(t!("rules_profile.no_local_file")),
+                                };
+                                if ui.button(t!("rules_profile.browse")).clicked() {
+                                    let dialog = FileDialog::new();
+                                    #[cfg(any(target_os = "windows", target_os = "linux"))]
+                                    let dialog =
+                                        dialog.set_title(t!("dialog.open_rules")).add_filter(
+                                            t!("dialog.rules_filter"),
+                                            &supported_file_extensions(),
+                                        );
+                                    if let Some(picked_file) = dialog.pick_file() {
+                                        profile.set_local_file(Some(picked_file));
+                                        changed = true;
+                                    }
+                                }
+                                if profile.get_local_file().is_some()
+                                    && ui.button(t!("rules_profile.clear_local_file")).clicked()
+                                {
+                                    profile.set_local_file(None);
+                                    changed = true;
+                                }
+                            });
+                            ui.end_row();
+
+                            self.rules_profiles_dirty |= changed;
+                        });
+                    let validation = self.rules_profiles.validate(index);
+                    if let Err(err) = &validation {
+                        ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+                    }
+                    ui.horizontal(|ui| {
+                        let testing = self
+                            .rules_profile_test
+                            .as_ref()
+                            .is_some_and(|(tested, _)| *tested == index);
+                        if ui
+                            .add_enabled(
+                                validation.is_ok() && self.rules_profile_test.is_none(),
+                                egui::Button::new(t!("rules_profile.test")),
+                            )
+                            .clicked()
+                        {
+                            test = Some(index);
+                        }
+                        if testing {
+                            ui.spinner();
+                        }
+                        if ui
+                            .add_enabled(
+                                profile_count > 1,
+                                egui::Button::new(t!("rules_profile.remove")),
+                            )
+                            .clicked()
+                        {
+                            remove = Some(index);
                         }
                     });
                 });
+        }
+        ui.separator();
+        if ui.button(t!("rules_profile.add")).clicked() {
+            self.rules_profiles
+                .add(RulesProfile::new(t!("rules_profile.new_name"), ""));
+            self.save_rules_profiles();
+        }
 
-                ui.menu_button("Campaign", |ui| {
-                    self.campaign_menu(ui);
-                });
-
-                if self.dock_state.find_active_focused().is_some() {
-                    if ui.button("Next Battle").clicked() {
-                        self.perform_on_all_guis_mut(&CampaignGui::next_battle);
+        if let Some(index) = test {
+            match self.rules_profiles.get_profiles()[index].get_source() {
+                Ok(source) => {
+                    self.rules_profile_test = Some((
+                        index,
+                        self.rule_refresh_runtime
+                            .spawn(google::get_origins_and_classes(source)),
+                    ));
+                }
+                Err(err) => {
+                    error_log_and_notify(
+                        &mut self.toasts,
+                        t!("rules_profile.invalid", error = err),
+                    );
+                }
+            }
+        }
+        if let Some(index) = remove {
+            let was_active = self.rules_profiles.is_active(index);
+            self.rules_profiles.remove(index);
+            self.rules_profile_test = None;
+            self.save_rules_profiles();
+            if was_active {
+                self.start_rules_refresh();
+            }
+        }
+        if let Some(index) = switch_to {
+            match self.rules_profiles.validate(index) {
+                Ok(()) => {
+                    if self.rules_profiles.set_active(index) {
+                        self.save_rules_profiles();
+                        self.start_rules_refresh();
                     }
+                }
+                Err(err) => {
+                    error_log_and_notify(
+                        &mut self.toasts,
+                        t!("rules_profile.invalid", error = err),
+                    );
+                }
+            }
+        }
+    }
+
+    /**
+     * Once the startup check is back, fetch the rules if there are none cached or,
+     * when updates don't need confirming, if they changed. Otherwise just say so.
+     */
+    fn poll_rules_update_check(&mut self) {
+        if !self
+            .rules_update_check
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            return;
+        }
+        let Some(handle) = self.rules_update_check.take() else {
+            return;
+        };
+        let cached_update_time = self.class_cache_rc.borrow().get_cache_update_time();
+        match self.rule_refresh_runtime.block_on(handle) {
+            Ok(Ok(update_time)) if cached_update_time == Some(update_time) => {
+                info!("Cached rules are up to date");
+            }
+            Ok(Ok(_))
+                if cached_update_time.is_none()
+                    || self.app_state.skip_rules_update_confirmation() =>
+            {
+                self.start_rules_refresh();
+            }
+            Ok(Ok(_)) => info_log_and_notify(
+                &mut self.toasts,
+                t!(
+                    "rules.update_available",
+                    name = self.rules_profiles.get_active().get_name()
+                ),
+            ),
+            Ok(Err(err)) => {
+                error_log_and_notify(
+                    &mut self.toasts,
+                    t!("rules.update_check_failed", error = err),
+                );
+            }
+            Err(err) => {
+                error_log_and_notify(
+                    &mut self.toasts,
+                    t!("rules.update_check_failed", error = err),
+                );
+            }
+        }
+    }
 
-                    if ui.button("Next Turn").clicked() {
-                        self.perform_on_all_guis_mut(&CampaignGui::next_turn);
+    /**
+     * Check the active rules when the lint tab asks, then hand it the issues once they're back
+     */
+    fn poll_rules_lint(&mut self) {
+        if self.rules_lint.is_none() {
+            let profile = self.rules_profiles.get_active();
+            let mut requested = false;
+            for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+                if let GreedTab::Lint(lint_gui) = tab {
+                    if lint_gui.take_run_request() {
+                        lint_gui.set_running(profile.get_name().to_owned());
+                        requested = true;
+                    }
+                }
+            }
+            if requested {
+                match profile.get_source() {
+                    Ok(source) => {
+                        self.rules_lint = Some(self.rule_refresh_runtime.spawn(lint::lint(source)));
+                    }
+                    Err(err) => {
+                        error_log_and_notify(&mut self.toasts, t!("lint.failed", error = err));
                     }
                 }
+            }
+            return;
+        }
+        if !self
+            .rules_lint
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            return;
+        }
+        let Some(handle) = self.rules_lint.take() else {
+            return;
+        };
+        let issues = match self.rule_refresh_runtime.block_on(handle) {
+            Ok(Ok(issues)) => Ok(issues),
+            Ok(Err(err)) => Err(err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        if let Err(err) = &issues {
+            error!("Failed to check the rules: {err}");
+        }
+        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+            if let GreedTab::Lint(lint_gui) = tab {
+                lint_gui.set_issues(issues.clone());
+            }
+        }
+    }
+
+    /**
+     * Report on a finished test fetch from the rules settings
+     */
+    fn poll_rules_profile_test(&mut self) {
+        if !self
+            .rules_profile_test
+            .as_ref()
+            .is_some_and(|(_, handle)| handle.is_finished())
+        {
+            return;
+        }
+        let Some((index, handle)) = self.rules_profile_test.take() else {
+            return;
+        };
+        let name = self
+            .rules_profiles
+            .get_profiles()
+            .get(index)
+            .map(|profile| profile.get_name().to_owned())
+            .unwrap_or_default();
+        match self.rule_refresh_runtime.block_on(handle) {
+            Ok(Ok(class_cache)) => info_log_and_notify(
+                &mut self.toasts,
+                t!(
+                    "rules_profile.test_passed",
+                    name = name,
+                    origins = class_cache.get_origins().len(),
+                    classes = class_cache.get_classes().len()
+                ),
+            ),
+            Ok(Err(err)) => error_log_and_notify(
+                &mut self.toasts,
+                t!("rules_profile.test_failed", name = name, error = err),
+            ),
+            Err(err) => error_log_and_notify(
+                &mut self.toasts,
+                t!("rules_profile.test_failed", name = name, error = err),
+            ),
+        }
+    }
+
+    fn save_rules_profiles(&mut self) {
+        self.rules_profiles_dirty = false;
+        let Some(path) = RulesProfiles::default_path() else {
+            return;
+        };
+        if let Err(err) = self.rules_profiles.to_file(&path) {
+            error_log_and_notify(
+                &mut self.toasts,
+                t!("rules_profile.save_failed", error = err),
+            );
+        }
+    }
+
+    fn api_settings_menu(&mut self, ui: &mut egui::Ui) {
+        let settings = self.app_state.api_server_settings_mut();
+        ui.checkbox(settings.enabled_mut(), t!("api.enable"));
+        ui.horizontal(|ui| {
+            ui.label(t!("api.port"));
+            ui.add_enabled(
+                !settings.is_enabled(),
+                egui::DragValue::new(settings.port_mut()).clamp_range(1024..=u16::MAX),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label(t!("api.token"));
+            ui.monospace(settings.get_token());
+        });
+        ui.horizontal(|ui| {
+            if ui.button(t!("api.copy_token")).clicked() {
+                let token = settings.get_token().to_owned();
+                ui.output_mut(|output| output.copied_text = token);
+            }
+            if ui.button(t!("api.regenerate_token")).clicked() {
+                settings.set_token(api::generate_token());
+                // Restart the server so the old token stops working
+                self.api_server = None;
+            }
+        });
+        if let Some(server) = &self.api_server {
+            ui.label(t!("api.listening", address = server.get_address()));
+        }
+    }
+
+    fn layout_menu(&mut self, ui: &mut egui::Ui) {
+        ui.set_min_width(200.0);
+        for builtin in BuiltinLayout::ALL {
+            if ui.button(builtin.get_name()).clicked() {
+                let open_tabs = self
+                    .dock_state
+                    .iter_all_tabs()
+                    .map(|(_, tab)| tab.to_tab_ref())
+                    .collect::<Vec<_>>();
+                self.apply_layout(&builtin.arrange(&open_tabs));
+                ui.close_menu();
+            }
+        }
+
+        if !self.app_state.get_layout_presets().is_empty() {
+            ui.separator();
+        }
+        let mut applied = None;
+        let mut removed = None;
+        for (pos, preset) in self.app_state.get_layout_presets().iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button(preset.get_name()).clicked() {
+                    applied = Some(preset.get_layout().clone());
+                }
+                if ui
+                    .small_button("x")
+                    .on_hover_text(t!("layout.delete_preset"))
+                    .clicked()
+                {
+                    removed = Some(pos);
+                }
+            });
+        }
+        if let Some(layout) = applied {
+            self.apply_layout(&layout);
+            ui.close_menu();
+        }
+        if let Some(pos) = removed {
+            self.app_state.remove_layout_preset(pos);
+        }
+
+        ui.separator();
+        ui.menu_button(t!("layout.save_current"), |ui| {
+            ui.text_edit_singleline(&mut self.layout_name_entry);
+            if !self.layout_name_entry.is_empty() && ui.button(t!("common.save")).clicked() {
+                self.app_state.add_layout_preset(LayoutPreset::new(
+                    self.layout_name_entry.clone(),
+                    self.current_layout(),
+                ));
+                info_log_and_notify(
+                    &mut self.toasts,
+                    t!("layout.saved", name = self.layout_name_entry),
+                );
+                self.layout_name_entry.clear();
+                ui.close_menu();
+            }
+        });
+    }
+
+    fn current_layout(&self) -> DockState<TabRef> {
+        self.dock_state.map_tabs(GreedTab::to_tab_ref)
+    }
+
+    /**
+     * Switch to a layout, keeping every open tab and opening any campaigns it adds
+     */
+    fn apply_layout(&mut self, layout: &DockState<TabRef>) {
+        let open_tabs = self
+            .dock_state
+            .iter_all_tabs()
+            .map(|(_, tab)| tab.clone())
+            .collect();
+        self.dock_state = restore_layout(
+            layout,
+            open_tabs,
+            |tab, tab_ref| tab.to_tab_ref().refers_to(tab_ref),
+            |tab_ref| GuiGreedApp::open_tab_ref(tab_ref, &self.class_cache_rc, &mut self.toasts),
+        );
+    }
+
+    /**
+     * Create a tab from a saved layout, None when its campaign can't be loaded
+     */
+    fn open_tab_ref(
+        tab_ref: &TabRef,
+        class_cache_rc: &Rc<RefCell<ClassCache>>,
+        toasts: &mut Toasts,
+    ) -> Option<GreedTab> {
+        match tab_ref {
+            TabRef::Campaign { path: None, .. } => None,
+            TabRef::Campaign {
+                path: Some(path), ..
+            } => match SaveWithPath::from_path(path) {
+                Ok(save) => {
+                    let mut campaign_gui =
+                        CampaignGui::new_refreshable(save, class_cache_rc.clone());
+                    campaign_gui.refresh_campaign();
+                    Some(campaign_gui.into())
+                }
+                Err(err) => {
+                    warning_log_and_notify(
+                        toasts,
+                        t!(
+                            "layout.reopen_failed",
+                            path = path.to_string_lossy(),
+                            error = err
+                        ),
+                    );
+                    None
+                }
+            },
+            TabRef::Compendium => Some(GreedTab::Compendium(CompendiumGui::new(
+                class_cache_rc.clone(),
+            ))),
+            TabRef::Comparison => Some(GreedTab::Comparison(ComparisonGui::new(
+                class_cache_rc.clone(),
+            ))),
+            TabRef::Lint => Some(GreedTab::Lint(LintGui::new())),
+        }
+    }
+
+    fn language_menu(&mut self, ui: &mut egui::Ui) {
+        let current = i18n::get_language();
+        for (code, name) in i18n::get_languages() {
+            if ui.radio(current == code, name).clicked() {
+                i18n::set_language(&code);
+                self.app_state.set_language(code);
+            }
+        }
+    }
+
+    fn level_numerals_menu(&mut self, ui: &mut egui::Ui) {
+        let current = self.app_state.get_level_numerals();
+        for (level_numerals, label) in [
+            (LevelNumerals::Roman, t!("settings.level_numerals_roman")),
+            (LevelNumerals::Arabic, t!("settings.level_numerals_arabic")),
+        ] {
+            if ui.radio(current == level_numerals, label).clicked() {
+                self.app_state.set_level_numerals(level_numerals);
+            }
+        }
+    }
 
-                self.refresh_rules(ui, frame);
+    fn keybindings_menu(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.set_min_width(300.0);
+        egui::Grid::new("keybindings_grid").show(ui, |ui| {
+            for (index, binding) in self
+                .app_state
+                .keybindings_mut()
+                .bindings_mut()
+                .iter_mut()
+                .enumerate()
+            {
+                ui.label(binding.get_command().label());
+                if self.rebinding == Some(index) {
+                    ui.label(t!("keybindings.press_key"));
+                } else {
+                    ui.monospace(binding.get_shortcut().map_or_else(
+                        || t!("keybindings.unbound"),
+                        |shortcut| ctx.format_shortcut(&shortcut),
+                    ));
+                }
+                if ui.button(t!("keybindings.rebind")).clicked() {
+                    self.rebinding = Some(index);
+                }
+                if ui.button(t!("keybindings.clear")).clicked() {
+                    binding.set_shortcut(None);
+                }
+                ui.end_row();
+            }
+        });
+        if ui.button(t!("keybindings.reset")).clicked() {
+            *self.app_state.keybindings_mut() = Keybindings::default();
+            self.rebinding = None;
+        }
+    }
 
-                ui.hyperlink_to("Greed Ruleset", "https://docs.google.com/document/d/1154Ep1n8AuiG5iQVxNmahIzjb69BQD28C3QmLfta1n4/edit?usp=sharing");
+    /**
+     * Give the binding being edited the next key pressed with its modifiers, Escape cancels
+     */
+    fn capture_rebinding(&mut self, ctx: &egui::Context, index: usize) {
+        let pressed = ctx.input_mut(|input| {
+            let pressed = input.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
             });
+            if let Some((key, modifiers)) = pressed {
+                input.consume_key(modifiers, key);
+            }
+            pressed
         });
+        match pressed {
+            Some((egui::Key::Escape, _)) => self.rebinding = None,
+            Some((key, modifiers)) => {
+                if let Some(binding) = self
+                    .app_state
+                    .keybindings_mut()
+                    .bindings_mut()
+                    .get_mut(index)
+                {
+                    binding.set_shortcut(Some(egui::KeyboardShortcut::new(modifiers, key)));
+                }
+                self.rebinding = None;
+            }
+            None => {}
+        }
+    }
+
+    /**
+     * Run shortcuts pressed this frame and anything picked from the command palette
+     */
+    fn handle_commands(&mut self, ctx: &egui::Context) {
+        if let Some(index) = self.rebinding {
+            self.capture_rebinding(ctx, index);
+            return;
+        }
+
+        let mut commands = if self.command_palette.is_open() {
+            vec![]
+        } else {
+            self.app_state.get_keybindings().triggered(ctx)
+        };
+
+        if self.command_palette.is_open() {
+            let mut entries = [
+                AppCommand::NextTurn,
+                AppCommand::NextBattle,
+                AppCommand::UseInspiration,
+                AppCommand::Save,
+                AppCommand::Open,
+                AppCommand::NextTab,
+                AppCommand::PreviousTab,
+                AppCommand::Undo,
+            ]
+            .into_iter()
+            .map(|command| (command.label(), command))
+            .collect::<Vec<_>>();
+            if let Some(campaign_gui) = focused_campaign(&mut self.dock_state) {
+                entries.extend(campaign_gui.palette_entries());
+            }
+            commands.extend(self.command_palette.show(ctx, entries));
+        }
+
+        for command in commands {
+            self.run_command(command);
+        }
+    }
+
+    fn run_command(&mut self, command: AppCommand) {
+        let action = match command {
+            AppCommand::NextTurn => {
+                self.perform_on_all_guis_mut(&CampaignGui::next_turn);
+                return;
+            }
+            AppCommand::NextBattle => {
+                self.perform_on_all_guis_mut(&CampaignGui::next_battle);
+                return;
+            }
+            AppCommand::Save => {
+                self.save_focused();
+                return;
+            }
+            AppCommand::Open => {
+                self.open_campaign();
+                return;
+            }
+            AppCommand::NextTab => {
+                self.cycle_tabs(true);
+                return;
+            }
+            AppCommand::PreviousTab => {
+                self.cycle_tabs(false);
+                return;
+            }
+            AppCommand::CommandPalette => {
+                self.command_palette.open();
+                return;
+            }
+            AppCommand::Undo => {
+                if !focused_campaign(&mut self.dock_state).is_some_and(CampaignGui::undo) {
+                    info_log_and_notify(&mut self.toasts, t!("commands.nothing_to_undo"));
+                }
+                return;
+            }
+            AppCommand::AddClass(class_name) => {
+                if !focused_campaign(&mut self.dock_state)
+                    .is_some_and(|campaign_gui| campaign_gui.add_class_by_name(&class_name))
+                {
+                    warning_log_and_notify(
+                        &mut self.toasts,
+                        t!("commands.cannot_add_class", class = class_name),
+                    );
+                }
+                return;
+            }
+            AppCommand::UseInspiration => Some(ApiAction::UseInspiration),
+            AppCommand::UsePrimary(index) => focused_campaign(&mut self.dock_state)
+                .and_then(|campaign_gui| {
+                    campaign_gui.get_primary_action_names().get(index).cloned()
+                })
+                .map(|name| ApiAction::UsePrimary { name }),
+            AppCommand::UseSecondary(index) => focused_campaign(&mut self.dock_state)
+                .and_then(|campaign_gui| {
+                    campaign_gui
+                        .get_secondary_action_names()
+                        .get(index)
+                        .cloned()
+                })
+                .map(|name| ApiAction::UseSecondary { name }),
+            AppCommand::UseSpecial(index) => focused_campaign(&mut self.dock_state)
+                .and_then(|campaign_gui| {
+                    campaign_gui.get_special_action_names().get(index).cloned()
+                })
+                .map(|name| ApiAction::UseSpecial { name }),
+        };
+
+        let Some(campaign_gui) = focused_campaign(&mut self.dock_state) else {
+            return;
+        };
+        match action {
+            Some(action) => {
+                if let Err(err) = campaign_gui.apply_api_action(&action) {
+                    warning_log_and_notify(&mut self.toasts, format!("{err}"));
+                }
+            }
+            None => warning_log_and_notify(&mut self.toasts, t!("commands.no_such_action")),
+        }
+    }
+
+    /**
+     * Move focus to the next or previous tab on the main surface, wrapping around
+     */
+    fn cycle_tabs(&mut self, forward: bool) {
+        let all_tabs = self
+            .dock_state
+            .main_surface()
+            .iter()
+            .enumerate()
+            .filter_map(|(node_index, node)| {
+                if let egui_dock::node::Node::Leaf { tabs, .. } = node {
+                    Some(
+                        (0..tabs.len())
+                            .map(move |tab_index| (NodeIndex(node_index), TabIndex(tab_index))),
+                    )
+                } else {
+                    None
+                }
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        if all_tabs.is_empty() {
+            return;
+        }
+
+        let current = self
+            .dock_state
+            .focused_leaf()
+            .filter(|(surface, _)| *surface == SurfaceIndex::main())
+            .and_then(|(_, node_index)| {
+                if let egui_dock::node::Node::Leaf { active, .. } =
+                    &self.dock_state.main_surface()[node_index]
+                {
+                    all_tabs
+                        .iter()
+                        .position(|position| *position == (node_index, *active))
+                } else {
+                    None
+                }
+            });
+        let next = match (current, forward) {
+            (None, _) => 0,
+            (Some(current), true) => (current + 1) % all_tabs.len(),
+            (Some(current), false) => (current + all_tabs.len() - 1) % all_tabs.len(),
+        };
+        let (node_index, tab_index) = all_tabs[next];
+        self.dock_state
+            .set_active_tab((SurfaceIndex::main(), node_index, tab_index));
+        self.dock_state
+            .set_focused_node_and_surface((SurfaceIndex::main(), node_index));
     }
 
     fn campaign_menu(&mut self, ui: &mut egui::Ui) {
         ui.set_min_width(200.0);
-        ui.menu_button("New", |ui| {
+        ui.menu_button(t!("campaign_menu.new"), |ui| {
             ui.text_edit_singleline(&mut self.new_campaign_name_entry);
-            if !self.new_campaign_name_entry.is_empty() && ui.button("Create").clicked() {
+            if !self.new_campaign_name_entry.is_empty() && ui.button(t!("common.create")).clicked()
+            {
                 let mut campaign_gui = CampaignGui::new_refreshable(
                     SaveWithPath::new(Save::new(self.new_campaign_name_entry.clone())),
                     self.class_cache_rc.clone(),
                 );
                 campaign_gui.refresh_campaign();
                 self.new_campaign_name_entry.clear();
-                self.dock_state.push_to_first_leaf(campaign_gui);
+                self.dock_state.push_to_first_leaf(campaign_gui.into());
             }
         });
 
         self.random_campaign_submenu(ui);
 
-        if ui.button("Open").clicked() {
+        if ui.button(t!("campaign_menu.open")).clicked() {
             self.open_campaign();
         }
 
         if !self.app_state.is_campaign_history_empty() {
-            ui.menu_button("Recent Campaigns", |ui| {
+            ui.menu_button(t!("campaign_menu.recent"), |ui| {
                 let mut invalid_paths = vec![];
                 let mut distintness_decider = HashSet::new();
                 for (pos, path) in self
@@ -242,86 +1101,267 @@ impl GuiGreedApp {
             });
         }
 
-        if let Some((_, campaign_gui)) = self.dock_state.find_active_focused() {
-            if ui.button("Save").clicked() {
-                info_log_and_notify(&mut self.toasts, "Attempting to save campaign!");
-                let open_file_picker = if campaign_gui.get_path().is_some() {
-                    campaign_gui
-                        .save()
-                        .unwrap()
-                        .map_err(|err| {
-                            error_log_and_notify(
+        if focused_campaign(&mut self.dock_state).is_some()
+            && ui.button(t!("common.save")).clicked()
+        {
+            self.save_focused();
+        }
+
+        if focused_campaign(&mut self.dock_state).is_some()
+            && ui.button(t!("campaign_menu.save_as")).clicked()
+        {
+            self.save_as();
+        }
+
+        if focused_campaign(&mut self.dock_state).is_some() {
+            ui.menu_button(t!("campaign_menu.export_sheet"), |ui| {
+                if ui.button(t!("campaign_menu.export_html")).clicked() {
+                    self.export_sheet(SheetFormat::Html);
+                }
+                if ui.button(t!("campaign_menu.export_pdf")).clicked() {
+                    self.export_sheet(SheetFormat::Pdf);
+                }
+            });
+        }
+
+        self.summary_menu(ui);
+        self.share_code_menu(ui);
+    }
+
+    fn share_code_menu(&mut self, ui: &mut egui::Ui) {
+        if focused_campaign(&mut self.dock_state).is_some() {
+            ui.menu_button(t!("share.copy_menu"), |ui| {
+                ui.checkbox(
+                    &mut self.share_code_fingerprint,
+                    t!("share.include_fingerprint"),
+                );
+                if ui.button(t!("common.copy")).clicked() {
+                    if let Some(campaign_gui) = focused_campaign(&mut self.dock_state) {
+                        let fingerprint = self
+                            .share_code_fingerprint
+                            .then(|| self.class_cache_rc.borrow().fingerprint());
+                        let code = SharedBuild::from_character(
+                            &campaign_gui.get_save().get_character(),
+                            fingerprint,
+                        )
+                        .encode();
+                        ui.output_mut(|output| output.copied_text = code);
+                        info_log_and_notify(&mut self.toasts, t!("share.copied"));
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+
+        ui.menu_button(t!("share.import_menu"), |ui| {
+            ui.label(t!("share.campaign_name"));
+            ui.text_edit_singleline(&mut self.share_code_name_entry);
+            ui.label(t!("share.code"));
+            ui.text_edit_singleline(&mut self.share_code_entry);
+            if !self.share_code_entry.trim().is_empty()
+                && !self.share_code_name_entry.is_empty()
+                && ui.button(t!("common.import")).clicked()
+            {
+                match SharedBuild::decode(&self.share_code_entry) {
+                    Ok(build) => {
+                        let class_cache = self.class_cache_rc.borrow();
+                        let report = build.check(&class_cache);
+                        if report.has_warnings() {
+                            warning_log_and_notify(
                                 &mut self.toasts,
-                                format!("Failed to save campaign: {err}",),
+                                share_report(&report, &class_cache),
                             );
-                        })
-                        .is_err()
-                } else {
-                    true
-                };
+                        }
+                        drop(class_cache);
 
-                if open_file_picker {
-                    self.save_as();
+                        let mut save = Save::new(self.share_code_name_entry.clone());
+                        *save.get_character_mut() = build.to_character();
+                        let mut campaign_gui = CampaignGui::new_refreshable(
+                            SaveWithPath::new(save),
+                            self.class_cache_rc.clone(),
+                        );
+                        campaign_gui.refresh_campaign();
+                        self.dock_state.push_to_first_leaf(campaign_gui.into());
+                        self.share_code_entry.clear();
+                        ui.close_menu();
+                    }
+                    Err(err) => {
+                        error_log_and_notify(
+                            &mut self.toasts,
+                            t!("share.import_failed", error = err),
+                        );
+                    }
                 }
             }
+        });
+    }
+
+    fn summary_menu(&mut self, ui: &mut egui::Ui) {
+        if focused_campaign(&mut self.dock_state).is_some() {
+            ui.menu_button(t!("summary.copy_menu"), |ui| {
+                ui.radio_value(
+                    &mut self.summary_markup,
+                    SummaryMarkup::Markdown,
+                    t!("summary.markdown"),
+                );
+                ui.radio_value(
+                    &mut self.summary_markup,
+                    SummaryMarkup::PlainText,
+                    t!("summary.plain_text"),
+                );
+                ui.separator();
+                for (label, detail) in [
+                    (t!("summary.character_compact"), SummaryDetail::Compact),
+                    (t!("summary.character_full"), SummaryDetail::Full),
+                ] {
+                    if ui.button(label).clicked() {
+                        if let Some(campaign_gui) = focused_campaign(&mut self.dock_state) {
+                            let sheet = CharacterSheet::new(
+                                campaign_gui.get_save(),
+                                &self.class_cache_rc.borrow(),
+                            )
+                            .with_level_numerals(self.app_state.get_level_numerals());
+                            let summary = render_summary(&[sheet], detail, self.summary_markup);
+                            ui.output_mut(|output| output.copied_text = summary);
+                            info_log_and_notify(&mut self.toasts, t!("summary.copied"));
+                        }
+                    }
+                }
+                for (label, detail) in [
+                    (t!("summary.party_compact"), SummaryDetail::Compact),
+                    (t!("summary.party_full"), SummaryDetail::Full),
+                ] {
+                    if ui.button(label).clicked() {
+                        let class_cache = self.class_cache_rc.clone();
+                        let level_numerals = self.app_state.get_level_numerals();
+                        let sheets =
+                            self.perform_on_all_guis_mut(&|campaign_gui: &mut CampaignGui| {
+                                CharacterSheet::new(campaign_gui.get_save(), &class_cache.borrow())
+                                    .with_level_numerals(level_numerals)
+                            });
+                        let summary = render_summary(&sheets, detail, self.summary_markup);
+                        ui.output_mut(|output| output.copied_text = summary);
+                        info_log_and_notify(&mut self.toasts, t!("summary.copied_party"));
+                    }
+                }
+            });
         }
 
-        if self.dock_state.find_active_focused().is_some() && ui.button("Save As...").clicked() {
-            self.save_as();
+        ui.menu_button(t!("summary.import_menu"), |ui| {
+            ui.label(t!("summary.paste"));
+            ui.text_edit_multiline(&mut self.summary_import_entry);
+            if !self.summary_import_entry.trim().is_empty()
+                && ui.button(t!("common.import")).clicked()
+            {
+                let imported =
+                    parse_summary(&self.summary_import_entry, &self.class_cache_rc.borrow());
+                match imported {
+                    Ok(characters) => {
+                        for imported in characters {
+                            let mut save = Save::new(imported.name);
+                            *save.get_character_mut() = imported.character;
+                            let mut campaign_gui = CampaignGui::new_refreshable(
+                                SaveWithPath::new(save),
+                                self.class_cache_rc.clone(),
+                            );
+                            campaign_gui.refresh_campaign();
+                            self.dock_state.push_to_first_leaf(campaign_gui.into());
+                        }
+                        self.summary_import_entry.clear();
+                        ui.close_menu();
+                    }
+                    Err(err) => {
+                        error_log_and_notify(
+                            &mut self.toasts,
+                            t!("summary.import_failed", error = err),
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    fn export_sheet(&mut self, format: SheetFormat) {
+        let Some(campaign_gui) = focused_campaign(&mut self.dock_state) else {
+            return;
+        };
+        let sheet = CharacterSheet::new(campaign_gui.get_save(), &self.class_cache_rc.borrow())
+            .with_level_numerals(self.app_state.get_level_numerals());
+
+        let dialog = FileDialog::new().set_file_name(format!(
+            "{}.{}",
+            sheet.campaign_name,
+            format.extension()
+        ));
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        let dialog = dialog
+            .set_title(t!("dialog.export_sheet"))
+            .add_filter(t!("dialog.character_sheet_filter"), &[format.extension()]);
+
+        if let Some(picked_file) = dialog.save_file() {
+            match export::export_sheet(&sheet, format, &picked_file) {
+                Ok(()) => {
+                    info_log_and_notify(
+                        &mut self.toasts,
+                        t!("export.done", path = picked_file.display()),
+                    );
+                }
+                Err(err) => {
+                    error_log_and_notify(&mut self.toasts, t!("export.failed", error = err));
+                }
+            }
         }
     }
 
     fn random_campaign_submenu(&mut self, ui: &mut egui::Ui) {
-        ui.menu_button("New Random Campaign", |ui| {
+        ui.menu_button(t!("random.new"), |ui| {
             ui.text_edit_singleline(&mut self.random_campaign_name_entry);
             ui.horizontal(|ui| {
-                ui.label("Level:");
+                ui.label(t!("random.level"));
                 ui.add(egui::Slider::new(
                     &mut self.random_level,
                     0.0..=self.class_cache_rc.borrow().get_classes().len().to_f64(),
                 ));
             });
-            if !self.random_campaign_name_entry.is_empty() && ui.button("Create").clicked() {
+            if !self.random_campaign_name_entry.is_empty()
+                && ui.button(t!("common.create")).clicked()
+            {
                 let mut campaign = CampaignGui::new_refreshable(
                     SaveWithPath::new(Save::new(self.random_campaign_name_entry.clone())),
                     self.class_cache_rc.clone(),
                 );
                 self.random_campaign_name_entry.clear();
                 self.random_campaign(&mut campaign);
-                self.dock_state.push_to_first_leaf(campaign);
+                self.dock_state.push_to_first_leaf(campaign.into());
             }
         });
-        if self.dock_state.find_active_focused().is_some() {
-            ui.menu_button("Randomize Campaign", |ui| {
-                ui.label("Level:");
+        if focused_campaign(&mut self.dock_state).is_some() {
+            ui.menu_button(t!("random.randomize_menu"), |ui| {
+                ui.label(t!("random.level"));
                 ui.add(egui::Slider::new(
                     &mut self.random_level,
                     0.0..=self.class_cache_rc.borrow().get_classes().len().to_f64(),
                 ));
-                if ui.button("Randomize").clicked() {
+                if ui.button(t!("random.randomize")).clicked() {
                     let mut new_gui = None;
-                    if let Some((_, campaign_gui)) = self.dock_state.find_active_focused() {
+                    if let Some(campaign_gui) = focused_campaign(&mut self.dock_state) {
                         new_gui = Some(campaign_gui.clone());
                     }
                     if let Some(campaign_gui) = &mut new_gui {
                         campaign_gui.clear_campaign();
                         self.random_campaign(campaign_gui);
-                        if let Some((_, active_gui)) = self.dock_state.find_active_focused() {
+                        if let Some(active_gui) = focused_campaign(&mut self.dock_state) {
                             *active_gui = campaign_gui.clone();
                         }
-                    };
+                    }
                 }
             });
         }
     }
 
     fn refresh_rules(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame) {
-        if ui.button("Refresh Rules").clicked() {
-            info_log_and_notify(&mut self.toasts, "Started refreshing rules!");
-            self.rule_refresh_handle = RefCell::new(Some(
-                self.rule_refresh_runtime
-                    .spawn(crate::google::get_origins_and_classes()),
-            ));
+        if ui.button(t!("rules.refresh")).clicked() {
+            self.start_rules_refresh();
         }
         if self.rule_refresh_handle.borrow().is_some()
             && self
@@ -331,7 +1371,7 @@ impl GuiGreedApp {
                 .unwrap()
                 .is_finished()
         {
-            info_log_and_notify(&mut self.toasts, "Rules refreshed...");
+            info_log_and_notify(&mut self.toasts, t!("rules.refreshed"));
             let refresh_handle = self.rule_refresh_handle.replace(None);
             match self
                 .rule_refresh_runtime
@@ -339,32 +1379,95 @@ impl GuiGreedApp {
                 .0
                 .unwrap()
             {
-                Ok(class_cache) => {
+                Ok(mut class_cache) => {
+                    class_cache.carry_renames_from(&self.class_cache_rc.borrow());
+                    if let Some(server) = &self.api_server {
+                        server.publish_class_cache(class_cache.clone());
+                    }
                     *self.class_cache_rc.borrow_mut() = class_cache;
                     if let Some(storage) = frame.storage_mut() {
                         eframe::set_value(storage, "class_cache", &*self.class_cache_rc.borrow());
                     }
-                    info_log_and_notify(&mut self.toasts, "Campaign updated to new rules.");
-                    if let Some((_, campaign_gui)) = self.dock_state.find_active_focused() {
-                        campaign_gui.refresh_campaign();
+                    info_log_and_notify(&mut self.toasts, t!("rules.campaigns_updated"));
+                    let campaigns_with_issues =
+                        self.perform_on_all_guis_mut(&|campaign_gui: &mut CampaignGui| {
+                            campaign_gui.refresh_campaign();
+                            (!campaign_gui.get_save_issues().is_empty())
+                                .then(|| campaign_gui.get_save().get_campaign_name())
+                        });
+                    let campaigns_with_issues = campaigns_with_issues
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>();
+                    if !campaigns_with_issues.is_empty() {
+                        warning_log_and_notify(
+                            &mut self.toasts,
+                            t!(
+                                "rules.needs_repair",
+                                campaigns = campaigns_with_issues.join(", ")
+                            ),
+                        );
                     }
                 }
                 Err(err) => {
-                    error_log_and_notify(
-                        &mut self.toasts,
-                        format!("Error refreshing rules: {err}"),
-                    );
+                    error_log_and_notify(&mut self.toasts, t!("rules.refresh_failed", error = err));
                 }
             }
         }
     }
 
+    /**
+     * Fetch the rules for the active profile in the background
+     */
+    fn start_rules_refresh(&mut self) {
+        match self.rules_profiles.get_active().get_source() {
+            Ok(source) => {
+                info_log_and_notify(
+                    &mut self.toasts,
+                    t!(
+                        "rules.refresh_started",
+                        name = self.rules_profiles.get_active().get_name()
+                    ),
+                );
+                self.rule_refresh_handle = RefCell::new(Some(
+                    self.rule_refresh_runtime
+                        .spawn(google::get_origins_and_classes(source)),
+                ));
+            }
+            Err(err) => {
+                error_log_and_notify(&mut self.toasts, t!("rules.refresh_failed", error = err));
+            }
+        }
+    }
+
     fn main_panel(&mut self, ctx: &egui::Context) {
         egui_dock::DockArea::new(&mut self.dock_state)
             .style(Style::from_egui(ctx.style().as_ref()))
             .show(ctx, &mut self.tab_viewer);
     }
 
+    fn save_focused(&mut self) {
+        let Some(campaign_gui) = focused_campaign(&mut self.dock_state) else {
+            return;
+        };
+        info_log_and_notify(&mut self.toasts, t!("save.attempting"));
+        let open_file_picker = if campaign_gui.get_path().is_some() {
+            campaign_gui
+                .save()
+                .unwrap()
+                .map_err(|err| {
+                    error_log_and_notify(&mut self.toasts, t!("save.failed", error = err));
+                })
+                .is_err()
+        } else {
+            true
+        };
+
+        if open_file_picker {
+            self.save_as();
+        }
+    }
+
     fn save_as(&mut self) -> bool {
         let dialog = FileDialog::new();
         #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
@@ -380,21 +1483,18 @@ impl GuiGreedApp {
 
         #[cfg(any(target_os = "windows", target_os = "linux"))]
         let dialog = dialog
-            .set_title("Save Campaign As")
-            .add_filter("Greed Campaign", &["ron", "gccs"]);
+            .set_title(t!("dialog.save_campaign_as"))
+            .add_filter(t!("dialog.campaign_filter"), &["ron", "gccs"]);
 
         dialog
             .save_file()
             .and_then(|picked_file| {
-                self.dock_state.find_active_focused().map(|(_, campaign)| {
+                focused_campaign(&mut self.dock_state).map(|campaign| {
                     match campaign.save_to(picked_file.clone()) {
                         Ok(()) => {
                             info_log_and_notify(
                                 &mut self.toasts,
-                                format!(
-                                    "Successfully saved file to {}",
-                                    picked_file.to_string_lossy()
-                                ),
+                                t!("save.saved_to", path = picked_file.to_string_lossy()),
                             );
 
                             if let Some(path) = campaign.set_path(picked_file) {
@@ -405,7 +1505,11 @@ impl GuiGreedApp {
                         Err(err) => {
                             error_log_and_notify(
                                 &mut self.toasts,
-                                format!("Error while saving to file {picked_file:?}: {err}"),
+                                t!(
+                                    "save.save_to_failed",
+                                    path = picked_file.display(),
+                                    error = err
+                                ),
                             );
                             false
                         }
@@ -431,8 +1535,8 @@ impl GuiGreedApp {
 
         #[cfg(any(target_os = "windows", target_os = "linux"))]
         let dialog = dialog
-            .set_title("Open Campaign")
-            .add_filter("Greed Campaign", &["ron", "gccs"]);
+            .set_title(t!("dialog.open_campaign"))
+            .add_filter(t!("dialog.campaign_filter"), &["ron", "gccs"]);
 
         if let Some(picked_file) = dialog.pick_file() {
             self.open_new_save(&picked_file.into_os_string());
@@ -443,25 +1547,125 @@ impl GuiGreedApp {
         if let Ok(new_save) = SaveWithPath::from_path(new_save_path).map_err(|err| {
             error_log_and_notify(
                 &mut self.toasts,
-                format!(
-                    "Error loading save file at '{}': {err}",
-                    new_save_path.to_string_lossy()
+                t!(
+                    "open.failed",
+                    path = new_save_path.to_string_lossy(),
+                    error = err
                 ),
             );
         }) {
             let mut campaign_gui =
                 CampaignGui::new_refreshable(new_save, self.class_cache_rc.clone());
             campaign_gui.refresh_campaign();
-            self.dock_state.push_to_first_leaf(campaign_gui);
+            self.dock_state.push_to_first_leaf(campaign_gui.into());
             self.app_state.add_new_path_to_history(new_save_path);
         }
     }
 
+    /**
+     * Focus the first tab matching the predicate, opening a new one if there isn't one yet
+     */
+    fn focus_or_open_tab(
+        &mut self,
+        is_wanted_tab: impl Fn(&GreedTab) -> bool,
+        new_tab: impl FnOnce() -> GreedTab,
+    ) {
+        let existing =
+            self.dock_state
+                .main_surface()
+                .iter()
+                .enumerate()
+                .find_map(|(node_index, node)| {
+                    if let egui_dock::node::Node::Leaf { tabs, .. } = node {
+                        tabs.iter()
+                            .position(&is_wanted_tab)
+                            .map(|tab_index| (NodeIndex(node_index), TabIndex(tab_index)))
+                    } else {
+                        None
+                    }
+                });
+        match existing {
+            Some((node_index, tab_index)) => {
+                self.dock_state
+                    .set_active_tab((SurfaceIndex::main(), node_index, tab_index));
+                self.dock_state
+                    .set_focused_node_and_surface((SurfaceIndex::main(), node_index));
+            }
+            None => self.dock_state.push_to_first_leaf(new_tab()),
+        }
+    }
+
+    /**
+     * Focus the compendium, opening it if needed, and show a clicked rules term in it
+     */
+    fn open_in_compendium(&mut self, link: &TermLink) {
+        let class_cache = self.class_cache_rc.clone();
+        self.focus_or_open_tab(
+            |tab| matches!(tab, GreedTab::Compendium(_)),
+            || GreedTab::Compendium(CompendiumGui::new(class_cache)),
+        );
+        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+            if let GreedTab::Compendium(compendium_gui) = tab {
+                compendium_gui.select(link);
+            }
+        }
+    }
+
+    /**
+     * Give the reference tabs the characters they work from: the most recently
+     * focused one for the compendium and every open one for comparisons
+     */
+    fn sync_reference_tabs(&mut self) {
+        let focused_character = focused_campaign(&mut self.dock_state)
+            .map(|campaign_gui| campaign_gui.get_save().get_character());
+        let open_characters = self
+            .campaign_guis_mut()
+            .map(|campaign_gui| {
+                let save = campaign_gui.get_save();
+                (save.get_campaign_name(), save.get_character())
+            })
+            .collect::<Vec<_>>();
+        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+            match tab {
+                GreedTab::Compendium(compendium_gui) => {
+                    if focused_character.is_some() {
+                        compendium_gui.set_focused_character(focused_character.clone());
+                    }
+                }
+                GreedTab::Comparison(comparison_gui) => {
+                    comparison_gui.set_open_characters(open_characters.clone());
+                }
+                GreedTab::Campaign(_) | GreedTab::Lint(_) => {}
+            }
+        }
+    }
+
+    fn campaign_guis_mut(&mut self) -> impl Iterator<Item = &mut CampaignGui> {
+        self.dock_state
+            .main_surface_mut()
+            .iter_mut()
+            .filter_map(|node| {
+                if let egui_dock::node::Node::Leaf { tabs, .. } = node {
+                    Some(tabs.iter_mut())
+                } else {
+                    None
+                }
+            })
+            .flatten()
+            .filter_map(GreedTab::as_campaign_mut)
+    }
+
+    fn campaign_snapshots(&mut self) -> Vec<CampaignSnapshot> {
+        self.campaign_guis_mut()
+            .map(|campaign_gui| campaign_gui.api_snapshot())
+            .collect()
+    }
+
     fn perform_on_all_guis_mut<T>(&mut self, gui_action: &dyn Fn(&mut CampaignGui) -> T) -> Vec<T> {
         let mut results = Vec::with_capacity(self.dock_state.main_surface().num_tabs());
         for node in self.dock_state.main_surface_mut().iter_mut() {
             if let egui_dock::node::Node::Leaf { tabs, .. } = node {
-                for gui in tabs {
+                for gui in tabs.iter_mut().filter_map(GreedTab::as_campaign_mut) {
                     results.push(gui_action(gui));
                 }
             }
@@ -469,14 +1673,80 @@ impl GuiGreedApp {
         results
     }
 
+    /**
+     * Start or stop the API server to match the settings, then serve any pending requests
+     */
+    fn serve_api(&mut self, ctx: &egui::Context) {
+        let settings = self.app_state.get_api_server_settings().clone();
+        if !settings.is_enabled() {
+            self.api_server = None;
+            return;
+        }
+
+        if self
+            .api_server
+            .as_ref()
+            .is_none_or(|server| !server.is_running())
+        {
+            if settings.get_token().is_empty() {
+                self.app_state
+                    .api_server_settings_mut()
+                    .set_token(api::generate_token());
+            }
+            match ApiServer::start(
+                &self.rule_refresh_runtime,
+                settings.get_port(),
+                self.app_state.get_api_server_settings().get_token(),
+                self.class_cache_rc.borrow().clone(),
+                ctx.clone(),
+            ) {
+                Ok(server) => {
+                    info_log_and_notify(
+                        &mut self.toasts,
+                        t!("api.started", address = server.get_address()),
+                    );
+                    self.api_server = Some(server);
+                }
+                Err(err) => {
+                    error_log_and_notify(
+                        &mut self.toasts,
+                        t!("api.start_failed", port = settings.get_port(), error = err),
+                    );
+                    *self.app_state.api_server_settings_mut().enabled_mut() = false;
+                    self.api_server = None;
+                    return;
+                }
+            }
+        }
+
+        if let Some(mut server) = self.api_server.take() {
+            while let Some(request) = server.try_next_request() {
+                let id = request.get_campaign();
+                let result = match self
+                    .campaign_guis_mut()
+                    .find(|campaign_gui| campaign_gui.get_campaign_id() == id)
+                {
+                    Some(campaign_gui) => campaign_gui
+                        .apply_api_action(request.get_action())
+                        .map(|()| campaign_gui.api_snapshot()),
+                    None => Err(ApiActionError::UnknownCampaign(id)),
+                };
+                request.respond(result);
+            }
+            server.publish_campaigns(self.campaign_snapshots());
+            self.api_server = Some(server);
+        }
+    }
+
     fn random_campaign(&self, campaign: &mut CampaignGui) {
         let class_cache = self.class_cache_rc.borrow();
         let origins = class_cache.get_origins();
         let classes = class_cache.get_classes();
-        let origin = fastrand::choice(origins).unwrap().clone();
+        let origin = fastrand::choice(origins).unwrap();
+        let bonus_classes = origin.get_rules().get_bonus_classes();
         let level: usize = unsafe {
-            if origin.get_name() == "Human" {
-                (self.random_level + 1.0)
+            if bonus_classes > 0 {
+                (self.random_level + bonus_classes.to_f64())
                     .to_f64()
                     .clamp(0.0, classes.len().to_f64())
             } else {
@@ -494,7 +1764,7 @@ impl GuiGreedApp {
                 .filter(|class| {
                     !character_classes
                         .iter()
-                        .any(|current_class| class.get_name() == current_class.get_name())
+                        .any(|current_class| class.get_id() == current_class.get_id())
                         && class.get_class_available(&character_classes)
                 })
                 .collect::<Vec<_>>();
@@ -520,24 +1790,60 @@ impl eframe::App for GuiGreedApp {
             self.on_close_event(ctx);
         }
 
+        self.serve_api(ctx);
+
         self.toasts.show(ctx);
 
         self.menu_panel(ctx, frame);
 
+        self.poll_rules_update_check();
+        self.poll_rules_profile_test();
+        self.poll_rules_lint();
+
+        self.handle_commands(ctx);
+
+        self.tab_viewer
+            .set_color_scheme(self.app_state.get_theme().get_colors().clone());
+        self.tab_viewer
+            .set_accessibility_mode(self.app_state.accessibility_mode());
+        self.tab_viewer
+            .set_level_numerals(self.app_state.get_level_numerals());
+
+        self.sync_reference_tabs();
+
         self.main_panel(ctx);
+
+        if let Some(link) = take_clicked_term(ctx) {
+            self.open_in_compendium(&link);
+        }
     }
 
     fn save(&mut self, storage: &mut dyn Storage) {
+        if !self.closing {
+            self.app_state.set_dock_layout(self.current_layout());
+        }
         info!("Saving! AppState: {:?}", self.app_state);
         eframe::set_value(storage, eframe::APP_KEY, &self.app_state);
+        if self.rules_profiles_dirty {
+            self.save_rules_profiles();
+        }
+        eframe::set_value(
+            storage,
+            "response_cache",
+            &google::fetch::get_response_cache(),
+        );
         let stored_cache = eframe::get_value::<ClassCache>(storage, "class_cache");
         let current_cache = self.class_cache_rc.borrow();
-        if stored_cache.is_none() || stored_cache.is_some_and(|cache| cache != *current_cache) {
+        if stored_cache.is_none_or(|cache| cache != *current_cache) {
             info!("Saving! AppState: {:?}", current_cache);
             eframe::set_value(storage, "class_cache", &*current_cache);
         }
     }
 
+    #[allow(
+        clippy::duration_suboptimal_units,
+        reason = "eframe counts the interval in seconds"
+    )]
     fn auto_save_interval(&self) -> Duration {
         Duration::from_secs(60)
     }
@@ -549,6 +1855,7 @@ impl eframe::App for GuiGreedApp {
 
 impl GuiGreedApp {
     fn on_close_event(&mut self, ctx: &egui::Context) {
+        self.app_state.set_dock_layout(self.current_layout());
         let tabs_to_close = Rc::new(RefCell::new(Vec::new()));
         let tabs_to_close_clone = tabs_to_close.clone();
         let campaign_results =
@@ -556,7 +1863,7 @@ impl GuiGreedApp {
                 if campaign_gui.save_is_dirty() {
                     match MessageDialog::new()
                         .set_level(rfd::MessageLevel::Info)
-                        .set_title("Save Campaign?")
+                        .set_title(t!("dialog.save_campaign_prompt"))
                         .set_buttons(rfd::MessageButtons::YesNoCancel)
                         .show()
                     {
@@ -571,8 +1878,8 @@ impl GuiGreedApp {
                                 let dialog = FileDialog::new();
                                 #[cfg(any(target_os = "windows", target_os = "linux"))]
                                 let dialog = dialog
-                                    .set_title("Save Campaign As")
-                                    .add_filter("Greed Campaign", &["ron", "gccs"]);
+                                    .set_title(t!("dialog.save_campaign_as"))
+                                    .add_filter(t!("dialog.campaign_filter"), &["ron", "gccs"]);
                                 if let Some(picked_file) = dialog.save_file() {
                                     campaign_gui.set_path(picked_file);
                                     if campaign_gui.save().is_some_and(|result| result.is_ok()) {
@@ -611,6 +1918,38 @@ impl GuiGreedApp {
 
         if !campaign_results.is_empty() && campaign_results.iter().any(|result| !result) {
             ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        } else {
+            self.closing = true;
         }
     }
 }
+
+/**
+ * A message listing everything in an imported build that did not carry over, named as the
+ * rules last wrote it
+ */
+fn share_report(report: &SharedBuildReport, class_cache: &ClassCache) -> String {
+    let mut lines = vec![if report.rules_changed {
+        t!("share.rules_changed")
+    } else {
+        t!("share.partly_missing")
+    }];
+    if let Some(origin) = &report.missing_origin {
+        lines.push(t!(
+            "share.unknown_origin",
+            origin = class_cache.get_origin_name(origin)
+        ));
+    }
+    if !report.missing_classes.is_empty() {
+        lines.push(t!(
+            "share.unknown_classes",
+            classes = report
+                .missing_classes
+                .iter()
+                .map(|class_id| class_cache.get_class_name(class_id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,279 @@
+use egui::text::LayoutJob;
+use egui::{Align, FontSelection, RichText};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * A run of text with the same emphasis
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub strong: bool,
+    pub italics: bool,
+    pub code: bool,
+}
+
+/**
+ * The small subset of Markdown that notes are rendered with
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    Heading(usize, Vec<Span>),
+    Bullet(Vec<Span>),
+    Numbered(String, Vec<Span>),
+    Quote(Vec<Span>),
+    Code(String),
+    Rule,
+    Paragraph(Vec<Span>),
+}
+
+/**
+ * Split Markdown into blocks, one per line apart from fenced code
+ */
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut code: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            match code.take() {
+                Some(lines) => blocks.push(Block::Code(lines.join("\n"))),
+                None => code = Some(vec![]),
+            }
+            continue;
+        }
+        if let Some(lines) = code.as_mut() {
+            lines.push(line);
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        blocks.push(parse_line(trimmed));
+    }
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    blocks
+}
+
+fn parse_line(line: &str) -> Block {
+    if line.chars().all(|character| character == '-') && line.len() >= 3 {
+        return Block::Rule;
+    }
+    let hashes = line
+        .chars()
+        .take_while(|character| *character == '#')
+        .count();
+    if (1..=6).contains(&hashes) {
+        if let Some(heading) = line[hashes..].strip_prefix(' ') {
+            return Block::Heading(hashes, parse_spans(heading.trim()));
+        }
+    }
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Block::Bullet(parse_spans(item));
+    }
+    if let Some(quote) = line.strip_prefix('>') {
+        return Block::Quote(parse_spans(quote.trim_start()));
+    }
+    let digits = line
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+    if !digits.is_empty() {
+        if let Some(item) = line[digits.len()..].strip_prefix(". ") {
+            return Block::Numbered(digits, parse_spans(item));
+        }
+    }
+    Block::Paragraph(parse_spans(line))
+}
+
+/**
+ * Split a line on `**strong**`, `*italics*`/`_italics_` and `` `code` `` markers.
+ * Markers without a partner are kept as plain text.
+ */
+pub fn parse_spans(text: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut current = Span::default();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let marker = if current.code {
+            "`"
+        } else if rest.starts_with("**") {
+            "**"
+        } else if rest.starts_with(['*', '_', '`']) {
+            &rest[..1]
+        } else {
+            ""
+        };
+        let opens = match marker {
+            "**" => !current.strong,
+            "*" | "_" => !current.italics,
+            "`" => !current.code,
+            _ => false,
+        };
+        let closes_later = !marker.is_empty() && rest[marker.len()..].contains(marker);
+        if !marker.is_empty() && rest.starts_with(marker) && (!opens || closes_later) {
+            if !current.text.is_empty() {
+                spans.push(current.clone());
+                current.text.clear();
+            }
+            match marker {
+                "**" => current.strong = !current.strong,
+                "*" | "_" => current.italics = !current.italics,
+                _ => current.code = !current.code,
+            }
+            rest = &rest[marker.len()..];
+            continue;
+        }
+        let character = rest.chars().next().unwrap();
+        current.text.push(character);
+        rest = &rest[character.len_utf8()..];
+    }
+    if !current.text.is_empty() {
+        spans.push(current);
+    }
+    spans
+}
+
+fn layout_spans(ui: &egui::Ui, spans: &[Span], size: Option<f32>) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    for span in spans {
+        let mut text = RichText::new(&span.text);
+        if let Some(size) = size {
+            text = text.size(size).strong();
+        }
+        if span.strong {
+            text = text.strong();
+        }
+        if span.italics {
+            text = text.italics();
+        }
+        if span.code {
+            text = text.code();
+        }
+        text.append_to(&mut job, ui.style(), FontSelection::Default, Align::Center);
+    }
+    job
+}
+
+/**
+ * Show Markdown as formatted, read only text
+ */
+pub fn show(ui: &mut egui::Ui, text: &str) {
+    let body_size = egui::TextStyle::Body.resolve(ui.style()).size;
+    for block in parse(text) {
+        match block {
+            Block::Heading(level, spans) => {
+                // Each level down is a little smaller, bottoming out at body text
+                #[allow(clippy::cast_precision_loss)]
+                let size = body_size * (1.0 + 0.2 * (4_usize.saturating_sub(level)) as f32);
+                ui.label(layout_spans(ui, &spans, Some(size)));
+            }
+            Block::Bullet(spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("•");
+                    ui.label(layout_spans(ui, &spans, None));
+                });
+            }
+            Block::Numbered(number, spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(format!("{number}."));
+                    ui.label(layout_spans(ui, &spans, None));
+                });
+            }
+            Block::Quote(spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.separator();
+                    ui.label(layout_spans(ui, &spans, None));
+                });
+            }
+            Block::Code(code) => {
+                ui.label(RichText::new(code).code());
+            }
+            Block::Rule => {
+                ui.separator();
+            }
+            Block::Paragraph(spans) => {
+                ui.label(layout_spans(ui, &spans, None));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> Span {
+        Span {
+            text: text.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let blocks = parse(
+            "# Loot\n\n- 3 gold\n2. Rope\n> Said the king\n---\n```\nlet x = 1;\n```\n#hashtag",
+        );
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading(1, vec![plain("Loot")]),
+                Block::Bullet(vec![plain("3 gold")]),
+                Block::Numbered("2".to_owned(), vec![plain("Rope")]),
+                Block::Quote(vec![plain("Said the king")]),
+                Block::Rule,
+                Block::Code("let x = 1;".to_owned()),
+                Block::Paragraph(vec![plain("#hashtag")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        assert_eq!(
+            parse_spans("a **bold** and *it* `x*y` 2 * 3"),
+            vec![
+                plain("a "),
+                Span {
+                    text: "bold".to_owned(),
+                    strong: true,
+                    ..Default::default()
+                },
+                plain(" and "),
+                Span {
+                    text: "it".to_owned(),
+                    italics: true,
+                    ..Default::default()
+                },
+                plain(" "),
+                Span {
+                    text: "x*y".to_owned(),
+                    code: true,
+                    ..Default::default()
+                },
+                plain(" 2 * 3"),
+            ]
+        );
+    }
+}
@@ -1,6 +1,14 @@
 pub mod campaign;
+pub mod commands;
+pub mod comparison;
+pub mod compendium;
+pub mod i18n;
+pub mod layout;
+pub mod lint;
 pub mod main;
+pub mod markdown;
 pub mod state;
 pub mod tabs;
+pub mod theme;
 pub mod util;
 pub mod widgets;
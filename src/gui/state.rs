@@ -1,7 +1,14 @@
 use std::collections::VecDeque;
 use std::ffi::OsString;
 
-use serde::{Deserialize, Serialize};
+use egui_dock::DockState;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::commands::Keybindings;
+use super::i18n::BASE_LANGUAGE;
+use super::layout::{LayoutPreset, TabRef};
+use super::theme::Theme;
+use crate::util::LevelNumerals;
 
 /*
  * A console and digital character sheet for campaigns under the greed ruleset.
@@ -26,12 +33,87 @@ pub struct AppState {
     campaign_path_history: VecDeque<OsString>,
     #[serde(default)]
     skip_rules_update_confirmation: bool,
-    #[serde(default = "default_font_size")]
-    font_size: f32,
+    /// Keyboard and screen reader friendly campaign view
+    #[serde(default)]
+    accessibility_mode: bool,
+    /// Only read from states saved before themes, see `upgrade_legacy_settings`
+    #[serde(
+        default,
+        skip_serializing,
+        deserialize_with = "deserialize_legacy_font_size"
+    )]
+    font_size: Option<f32>,
+    #[serde(default)]
+    api_server: ApiServerSettings,
+    #[serde(default)]
+    keybindings: Keybindings,
+    #[serde(default)]
+    theme: Theme,
+    #[serde(default = "default_language")]
+    language: String,
+    /// Dock layout from the last session, restored on start
+    #[serde(default)]
+    dock_layout: Option<DockState<TabRef>>,
+    #[serde(default)]
+    layout_presets: Vec<LayoutPreset>,
+    #[serde(default)]
+    level_numerals: LevelNumerals,
+}
+
+fn default_language() -> String {
+    BASE_LANGUAGE.to_owned()
 }
 
-fn default_font_size() -> f32 {
-    12f32
+fn deserialize_legacy_font_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f32>, D::Error> {
+    f32::deserialize(deserializer).map(Some)
+}
+
+/**
+ * Settings for the embedded HTTP API used by stream overlays and bots
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiServerSettings {
+    enabled: bool,
+    port: u16,
+    token: String,
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        ApiServerSettings {
+            enabled: false,
+            port: 7878,
+            token: String::new(),
+        }
+    }
+}
+
+impl ApiServerSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn port_mut(&mut self) -> &mut u16 {
+        &mut self.port
+    }
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn set_token(&mut self, token: impl Into<String>) {
+        self.token = token.into();
+    }
 }
 
 impl AppState {
@@ -75,12 +157,94 @@ impl AppState {
         &mut self.skip_rules_update_confirmation
     }
 
-    pub fn get_font_size(&self) -> f32 {
-        self.font_size
+    pub fn accessibility_mode(&self) -> bool {
+        self.accessibility_mode
+    }
+
+    pub fn accessibility_mode_mut(&mut self) -> &mut bool {
+        &mut self.accessibility_mode
+    }
+
+    /**
+     * Move settings from older versions into their current home
+     */
+    pub fn upgrade_legacy_settings(&mut self) {
+        if let Some(font_size) = self.font_size.take() {
+            // Older versions could store a negative size, which was flipped on start
+            self.theme.typography_mut().set_body_size(font_size.abs());
+        }
+    }
+
+    pub fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn theme_mut(&mut self) -> &mut Theme {
+        &mut self.theme
+    }
+
+    pub fn get_language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn set_language(&mut self, language: String) {
+        self.language = language;
+    }
+
+    pub fn get_level_numerals(&self) -> LevelNumerals {
+        self.level_numerals
     }
 
-    pub fn set_font_size(&mut self, new_value: f32) {
-        self.font_size = new_value;
+    pub fn set_level_numerals(&mut self, level_numerals: LevelNumerals) {
+        self.level_numerals = level_numerals;
+    }
+
+    pub fn get_api_server_settings(&self) -> &ApiServerSettings {
+        &self.api_server
+    }
+
+    pub fn api_server_settings_mut(&mut self) -> &mut ApiServerSettings {
+        &mut self.api_server
+    }
+
+    pub fn get_keybindings(&self) -> &Keybindings {
+        &self.keybindings
+    }
+
+    pub fn keybindings_mut(&mut self) -> &mut Keybindings {
+        &mut self.keybindings
+    }
+
+    pub fn get_dock_layout(&self) -> Option<&DockState<TabRef>> {
+        self.dock_layout.as_ref()
+    }
+
+    pub fn set_dock_layout(&mut self, dock_layout: DockState<TabRef>) {
+        self.dock_layout = Some(dock_layout);
+    }
+
+    pub fn get_layout_presets(&self) -> &[LayoutPreset] {
+        &self.layout_presets
+    }
+
+    /**
+     * Save a preset, replacing any existing preset with the same name
+     */
+    pub fn add_layout_preset(&mut self, preset: LayoutPreset) {
+        match self
+            .layout_presets
+            .iter_mut()
+            .find(|existing| existing.get_name() == preset.get_name())
+        {
+            Some(existing) => *existing = preset,
+            None => self.layout_presets.push(preset),
+        }
+    }
+
+    pub fn remove_layout_preset(&mut self, pos: usize) {
+        if pos < self.layout_presets.len() {
+            self.layout_presets.remove(pos);
+        }
     }
 }
 
@@ -94,4 +258,76 @@ mod tests {
 
         assert!(app_state.campaign_path_history.is_empty());
     }
+
+    #[test]
+    fn test_api_server_disabled_by_default() {
+        let app_state = AppState::new();
+
+        assert!(!app_state.get_api_server_settings().is_enabled());
+        assert!(app_state.get_api_server_settings().get_token().is_empty());
+    }
+
+    #[test]
+    fn test_old_state_loads_api_defaults() {
+        let app_state: AppState =
+            ron::from_str("(campaign_path_history: [], font_size: 14.0)").unwrap();
+
+        assert_eq!(
+            app_state.get_api_server_settings(),
+            &ApiServerSettings::default()
+        );
+        assert_eq!(app_state.get_keybindings(), &Keybindings::default());
+        assert_eq!(app_state.get_language(), BASE_LANGUAGE);
+        assert!(!app_state.accessibility_mode());
+    }
+
+    #[test]
+    fn test_legacy_font_size_moves_to_theme() {
+        let mut app_state: AppState =
+            ron::from_str("(campaign_path_history: [], font_size: -14.0)").unwrap();
+
+        app_state.upgrade_legacy_settings();
+
+        let typography = app_state.theme_mut().typography_mut();
+        assert_eq!(typography.get_size(&egui::TextStyle::Body), Some(14.0));
+        assert_eq!(typography.get_size(&egui::TextStyle::Button), Some(14.0));
+        assert!(!ron::to_string(&app_state).unwrap().contains("font_size"));
+    }
+
+    #[test]
+    fn test_layout_round_trip() {
+        let mut app_state = AppState::new();
+        let layout = DockState::new(vec![
+            TabRef::Campaign {
+                path: Some("campaign.ron".into()),
+                name: "Campaign".to_owned(),
+            },
+            TabRef::Compendium,
+        ]);
+        app_state.set_dock_layout(layout.clone());
+        app_state.add_layout_preset(LayoutPreset::new("Table", layout.clone()));
+        app_state.add_layout_preset(LayoutPreset::new("Table", DockState::new(vec![])));
+
+        let loaded: AppState = ron::from_str(&ron::to_string(&app_state).unwrap()).unwrap();
+
+        let tabs = |layout: &DockState<TabRef>| {
+            layout
+                .iter_all_tabs()
+                .map(|(_, tab)| tab.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tabs(loaded.get_dock_layout().unwrap()), tabs(&layout));
+        assert_eq!(loaded.get_layout_presets().len(), 1);
+        assert!(tabs(loaded.get_layout_presets()[0].get_layout()).is_empty());
+    }
+
+    #[test]
+    fn test_keybindings_round_trip() {
+        let mut app_state = AppState::new();
+        app_state.keybindings_mut().bindings_mut()[0].set_shortcut(None);
+
+        let loaded: AppState = ron::from_str(&ron::to_string(&app_state).unwrap()).unwrap();
+
+        assert_eq!(loaded.get_keybindings(), app_state.get_keybindings());
+    }
 }
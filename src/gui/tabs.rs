@@ -1,10 +1,72 @@
 use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
 
 use super::campaign::CampaignGui;
+use super::comparison::ComparisonGui;
+use super::compendium::CompendiumGui;
+use super::i18n::{self, t};
+use super::layout::TabRef;
+use super::lint::LintGui;
+use super::theme::ColorScheme;
+use crate::util::LevelNumerals;
 
-#[derive(Default, Debug, PartialEq, PartialOrd, Ord, Eq)]
+use egui_dock::DockState;
+
+/**
+ * Everything that can be docked in the main area
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreedTab {
+    Campaign(Box<CampaignGui>),
+    Compendium(CompendiumGui),
+    Comparison(ComparisonGui),
+    Lint(LintGui),
+}
+
+impl GreedTab {
+    pub fn as_campaign_mut(&mut self) -> Option<&mut CampaignGui> {
+        match self {
+            GreedTab::Campaign(campaign_gui) => Some(campaign_gui.as_mut()),
+            GreedTab::Compendium(_) | GreedTab::Comparison(_) | GreedTab::Lint(_) => None,
+        }
+    }
+
+    /**
+     * How this tab is referenced in a saved layout
+     */
+    pub fn to_tab_ref(&self) -> TabRef {
+        match self {
+            GreedTab::Campaign(campaign_gui) => TabRef::Campaign {
+                path: campaign_gui.get_path().clone(),
+                name: campaign_gui.get_save().get_campaign_name(),
+            },
+            GreedTab::Compendium(_) => TabRef::Compendium,
+            GreedTab::Comparison(_) => TabRef::Comparison,
+            GreedTab::Lint(_) => TabRef::Lint,
+        }
+    }
+}
+
+impl From<CampaignGui> for GreedTab {
+    fn from(campaign_gui: CampaignGui) -> GreedTab {
+        GreedTab::Campaign(Box::new(campaign_gui))
+    }
+}
+
+/**
+ * The focused tab when it is a campaign
+ */
+pub fn focused_campaign(dock_state: &mut DockState<GreedTab>) -> Option<&mut CampaignGui> {
+    dock_state
+        .find_active_focused()
+        .and_then(|(_, tab)| tab.as_campaign_mut())
+}
+
+#[derive(Default, Debug, PartialEq, Eq)]
 pub struct CampaignTabViewer {
     tabs_to_force_close: Vec<String>,
+    color_scheme: ColorScheme,
+    accessibility_mode: bool,
+    level_numerals: LevelNumerals,
 }
 
 impl CampaignTabViewer {
@@ -15,24 +77,54 @@ impl CampaignTabViewer {
     pub fn set_tabs_to_close(&mut self, tabs: &[String]) {
         self.tabs_to_force_close = tabs.to_vec();
     }
+
+    pub fn set_color_scheme(&mut self, color_scheme: ColorScheme) {
+        self.color_scheme = color_scheme;
+    }
+
+    pub fn set_accessibility_mode(&mut self, accessibility_mode: bool) {
+        self.accessibility_mode = accessibility_mode;
+    }
+
+    pub fn set_level_numerals(&mut self, level_numerals: LevelNumerals) {
+        self.level_numerals = level_numerals;
+    }
 }
 
 impl egui_dock::TabViewer for CampaignTabViewer {
-    type Tab = CampaignGui;
+    type Tab = GreedTab;
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
-        tab.ui(ui);
+        match tab {
+            GreedTab::Campaign(campaign_gui) => {
+                campaign_gui.ui(
+                    ui,
+                    &self.color_scheme,
+                    self.accessibility_mode,
+                    self.level_numerals,
+                );
+            }
+            GreedTab::Compendium(compendium_gui) => compendium_gui.ui(ui, self.level_numerals),
+            GreedTab::Comparison(comparison_gui) => comparison_gui.ui(ui),
+            GreedTab::Lint(lint_gui) => lint_gui.ui(ui),
+        }
     }
 
     fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+        let tab = match tab {
+            GreedTab::Campaign(campaign_gui) => campaign_gui,
+            GreedTab::Compendium(_) => return t!("tabs.compendium").into(),
+            GreedTab::Comparison(_) => return t!("tabs.comparison").into(),
+            GreedTab::Lint(_) => return t!("tabs.lint").into(),
+        };
         let dirty_mark = if tab.save_is_dirty() { "* " } else { "" };
 
         match tab.get_path() {
             Some(text) => {
                 format!(
-                    "{dirty_mark}{} (Level: {}) [{}]",
+                    "{dirty_mark}{} ({}) [{}]",
                     tab.get_save().get_campaign_name(),
-                    tab.get_level(),
+                    t!("tabs.level", level = i18n::number(tab.get_level())),
                     text.to_string_lossy()
                 )
             }
@@ -41,11 +133,18 @@ impl egui_dock::TabViewer for CampaignTabViewer {
         .into()
     }
 
+    #[allow(
+        clippy::single_match_else,
+        reason = "saving in place and picking a path are parallel branches"
+    )]
     fn on_close(&mut self, tab: &mut Self::Tab) -> bool {
+        let GreedTab::Campaign(tab) = tab else {
+            return true;
+        };
         if tab.save_is_dirty() {
             match MessageDialog::new()
                 .set_level(MessageLevel::Info)
-                .set_title("Save Campaign?")
+                .set_title(t!("dialog.save_campaign_prompt"))
                 .set_buttons(MessageButtons::YesNo)
                 .show()
             {
@@ -55,7 +154,7 @@ impl egui_dock::TabViewer for CampaignTabViewer {
                         let dialog = FileDialog::new();
                         #[cfg(any(target_os = "windows", target_os = "linux"))]
                         let dialog = dialog
-                            .set_title("Save Campaign As")
+                            .set_title(t!("dialog.save_campaign_as"))
                             .add_filter("Greed Campaign", &["ron"]);
                         if let Some(picked_file) = dialog.save_file() {
                             tab.set_path(picked_file);
@@ -73,6 +172,9 @@ impl egui_dock::TabViewer for CampaignTabViewer {
     }
 
     fn force_close(&mut self, tab: &mut Self::Tab) -> bool {
+        let GreedTab::Campaign(tab) = tab else {
+            return false;
+        };
         self.tabs_to_force_close
             .iter()
             .position(|campaign_name| campaign_name.clone() == tab.get_save().get_campaign_name())
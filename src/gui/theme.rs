@@ -0,0 +1,356 @@
+use egui::{Color32, Stroke, TextStyle, Visuals};
+use serde::{Deserialize, Serialize};
+
+use super::i18n::{self, t};
+use crate::model::comparison::AbilityCategory;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+const MIN_FONT_SIZE: f32 = 5.0;
+const MAX_FONT_SIZE: f32 = 128.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 3] = [
+        ThemePreset::Dark,
+        ThemePreset::Light,
+        ThemePreset::HighContrast,
+    ];
+
+    pub fn get_name(self) -> String {
+        match self {
+            ThemePreset::Dark => t!("theme.dark"),
+            ThemePreset::Light => t!("theme.light"),
+            ThemePreset::HighContrast => t!("theme.high_contrast"),
+        }
+    }
+
+    pub fn visuals(self) -> Visuals {
+        match self {
+            ThemePreset::Dark => Visuals::dark(),
+            ThemePreset::Light => Visuals::light(),
+            ThemePreset::HighContrast => {
+                let mut visuals = Visuals::dark();
+                visuals.override_text_color = Some(Color32::WHITE);
+                visuals.panel_fill = Color32::BLACK;
+                visuals.window_fill = Color32::BLACK;
+                visuals.extreme_bg_color = Color32::BLACK;
+                visuals.faint_bg_color = Color32::from_gray(24);
+                visuals.hyperlink_color = Color32::from_rgb(0, 255, 255);
+                visuals.selection.bg_fill = Color32::from_rgb(0, 70, 200);
+                visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+                visuals.widgets.inactive.bg_fill = Color32::BLACK;
+                visuals.widgets.inactive.weak_bg_fill = Color32::BLACK;
+                visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+                visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+                visuals.widgets.active.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+                visuals
+            }
+        }
+    }
+}
+
+/**
+ * Colours for action states and ability categories on the character sheet
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColorScheme {
+    usable_action: Color32,
+    used_action: Color32,
+    utilities: Color32,
+    passives: Color32,
+    primary: Color32,
+    secondary: Color32,
+    special: Color32,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme::for_preset(ThemePreset::default())
+    }
+}
+
+impl ColorScheme {
+    /**
+     * The colours that read well on a preset's background
+     */
+    pub fn for_preset(preset: ThemePreset) -> ColorScheme {
+        match preset {
+            ThemePreset::Dark => ColorScheme {
+                usable_action: Color32::from_rgb(120, 210, 120),
+                used_action: Color32::from_gray(110),
+                utilities: Color32::from_rgb(140, 180, 255),
+                passives: Color32::from_rgb(200, 170, 255),
+                primary: Color32::from_rgb(255, 180, 110),
+                secondary: Color32::from_rgb(240, 220, 120),
+                special: Color32::from_rgb(255, 130, 160),
+            },
+            ThemePreset::Light => ColorScheme {
+                usable_action: Color32::from_rgb(20, 120, 30),
+                used_action: Color32::from_gray(150),
+                utilities: Color32::from_rgb(30, 80, 180),
+                passives: Color32::from_rgb(110, 50, 170),
+                primary: Color32::from_rgb(170, 80, 0),
+                secondary: Color32::from_rgb(130, 110, 0),
+                special: Color32::from_rgb(180, 20, 70),
+            },
+            ThemePreset::HighContrast => ColorScheme {
+                usable_action: Color32::from_rgb(0, 255, 0),
+                used_action: Color32::from_gray(170),
+                utilities: Color32::from_rgb(0, 255, 255),
+                passives: Color32::from_rgb(255, 120, 255),
+                primary: Color32::from_rgb(255, 170, 0),
+                secondary: Color32::YELLOW,
+                special: Color32::from_rgb(255, 90, 90),
+            },
+        }
+    }
+
+    /**
+     * Colour for an action button depending on whether it can be used
+     */
+    pub fn action(&self, usable: bool) -> Color32 {
+        if usable {
+            self.usable_action
+        } else {
+            self.used_action
+        }
+    }
+
+    pub fn get_category(&self, category: AbilityCategory) -> Color32 {
+        match category {
+            AbilityCategory::Utilities => self.utilities,
+            AbilityCategory::Passives => self.passives,
+            AbilityCategory::Primary => self.primary,
+            AbilityCategory::Secondary => self.secondary,
+            AbilityCategory::Special => self.special,
+        }
+    }
+
+    fn category_mut(&mut self, category: AbilityCategory) -> &mut Color32 {
+        match category {
+            AbilityCategory::Utilities => &mut self.utilities,
+            AbilityCategory::Passives => &mut self.passives,
+            AbilityCategory::Primary => &mut self.primary,
+            AbilityCategory::Secondary => &mut self.secondary,
+            AbilityCategory::Special => &mut self.special,
+        }
+    }
+}
+
+/**
+ * Font sizes for every built in text style
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Typography {
+    small: f32,
+    body: f32,
+    monospace: f32,
+    button: f32,
+    heading: f32,
+}
+
+impl Default for Typography {
+    fn default() -> Self {
+        Typography {
+            small: 9.0,
+            body: 12.5,
+            monospace: 12.0,
+            button: 12.5,
+            heading: 18.0,
+        }
+    }
+}
+
+impl Typography {
+    pub const STYLES: [TextStyle; 5] = [
+        TextStyle::Small,
+        TextStyle::Body,
+        TextStyle::Monospace,
+        TextStyle::Button,
+        TextStyle::Heading,
+    ];
+
+    pub fn get_size(&self, text_style: &TextStyle) -> Option<f32> {
+        match text_style {
+            TextStyle::Small => Some(self.small),
+            TextStyle::Body => Some(self.body),
+            TextStyle::Monospace => Some(self.monospace),
+            TextStyle::Button => Some(self.button),
+            TextStyle::Heading => Some(self.heading),
+            TextStyle::Name(_) => None,
+        }
+    }
+
+    pub fn size_mut(&mut self, text_style: &TextStyle) -> Option<&mut f32> {
+        match text_style {
+            TextStyle::Small => Some(&mut self.small),
+            TextStyle::Body => Some(&mut self.body),
+            TextStyle::Monospace => Some(&mut self.monospace),
+            TextStyle::Button => Some(&mut self.button),
+            TextStyle::Heading => Some(&mut self.heading),
+            TextStyle::Name(_) => None,
+        }
+    }
+
+    /**
+     * Set body and button text together, which is all the old font size setting covered
+     */
+    pub fn set_body_size(&mut self, size: f32) {
+        self.body = size;
+        self.button = size;
+    }
+}
+
+/**
+ * Everything about how the app looks, stored in the app state
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    preset: ThemePreset,
+    colors: ColorScheme,
+    typography: Typography,
+}
+
+impl Theme {
+    pub fn get_colors(&self) -> &ColorScheme {
+        &self.colors
+    }
+
+    pub fn typography_mut(&mut self) -> &mut Typography {
+        &mut self.typography
+    }
+
+    /**
+     * Switch preset, taking its colours along with it
+     */
+    pub fn set_preset(&mut self, preset: ThemePreset) {
+        self.preset = preset;
+        self.colors = ColorScheme::for_preset(preset);
+    }
+
+    pub fn apply(&self, ctx: &egui::Context) {
+        ctx.set_visuals(self.preset.visuals());
+        ctx.style_mut(|style| {
+            for (text_style, font_id) in &mut style.text_styles {
+                if let Some(size) = self.typography.get_size(text_style) {
+                    font_id.size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+                }
+            }
+        });
+    }
+
+    /**
+     * Settings menu for the theme, returning whether anything changed
+     */
+    pub fn settings_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        ui.label(t!("theme.header"));
+        ui.horizontal(|ui| {
+            for preset in ThemePreset::ALL {
+                if ui.radio(self.preset == preset, preset.get_name()).clicked() {
+                    self.set_preset(preset);
+                    changed = true;
+                }
+            }
+        });
+
+        ui.separator();
+        ui.label(t!("theme.text_sizes"));
+        egui::Grid::new("typography_grid").show(ui, |ui| {
+            for text_style in Typography::STYLES {
+                ui.label(text_style.to_string());
+                if let Some(size) = self.typography.size_mut(&text_style) {
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(size)
+                                .clamp_range(MIN_FONT_SIZE..=MAX_FONT_SIZE)
+                                .speed(0.25),
+                        )
+                        .changed();
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        ui.label(t!("theme.colours"));
+        egui::Grid::new("color_scheme_grid").show(ui, |ui| {
+            ui.label(t!("theme.usable_actions"));
+            changed |= ui
+                .color_edit_button_srgba(&mut self.colors.usable_action)
+                .changed();
+            ui.end_row();
+            ui.label(t!("theme.used_actions"));
+            changed |= ui
+                .color_edit_button_srgba(&mut self.colors.used_action)
+                .changed();
+            ui.end_row();
+            for category in AbilityCategory::ALL {
+                ui.label(i18n::ability_category(category));
+                changed |= ui
+                    .color_edit_button_srgba(self.colors.category_mut(category))
+                    .changed();
+                ui.end_row();
+            }
+        });
+        if ui.button(t!("theme.reset_colours")).clicked() {
+            self.colors = ColorScheme::for_preset(self.preset);
+            changed = true;
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_changes_colors() {
+        let mut theme = Theme::default();
+        theme.colors.usable_action = Color32::RED;
+
+        theme.set_preset(ThemePreset::HighContrast);
+
+        assert_eq!(
+            theme.get_colors(),
+            &ColorScheme::for_preset(ThemePreset::HighContrast)
+        );
+    }
+
+    #[test]
+    fn test_typography_covers_every_style() {
+        let typography = Typography::default();
+
+        for text_style in Typography::STYLES {
+            assert!(typography.get_size(&text_style).is_some());
+        }
+        assert_eq!(typography.get_size(&TextStyle::Name("Custom".into())), None);
+    }
+}
@@ -1,5 +1,5 @@
 use std::time::Duration;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub fn error_log_and_notify<M: Into<String>>(toasts_ref: &mut egui_notify::Toasts, message: M) {
     let error = message.into();
@@ -20,3 +20,13 @@ pub fn info_log_and_notify<M: Into<String>>(toasts_ref: &mut egui_notify::Toasts
         .set_closable(true)
         .set_duration(Some(Duration::from_secs(5)));
 }
+
+pub fn warning_log_and_notify<M: Into<String>>(toasts_ref: &mut egui_notify::Toasts, message: M) {
+    let warning = message.into();
+    warn!("{warning}");
+    toasts_ref.dismiss_oldest_toast();
+    toasts_ref
+        .warning(warning)
+        .set_closable(true)
+        .set_duration(Some(Duration::from_secs(5)));
+}
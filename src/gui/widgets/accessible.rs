@@ -0,0 +1,137 @@
+use egui::{Response, RichText, Sense, WidgetInfo, WidgetType};
+
+use super::description::linked_description;
+use crate::gui::i18n::t;
+use crate::model::glossary::{plain_text, DescriptionPart};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * A drop down menu, or in accessibility mode a collapsing section that can be
+ * opened and walked through with the keyboard
+ */
+pub fn menu_section(
+    ui: &mut egui::Ui,
+    accessible: bool,
+    title: impl Into<String>,
+    add_contents: impl FnOnce(&mut egui::Ui),
+) {
+    if accessible {
+        egui::CollapsingHeader::new(title.into()).show(ui, add_contents);
+    } else {
+        ui.menu_button(title.into(), add_contents);
+    }
+}
+
+/**
+ * A label that can take keyboard focus in accessibility mode so it is read out
+ * while tabbing through a panel
+ */
+pub fn focusable_label(
+    ui: &mut egui::Ui,
+    accessible: bool,
+    text: impl Into<egui::WidgetText>,
+) -> Response {
+    let label = egui::Label::new(text);
+    if accessible {
+        ui.add(label.sense(Sense::focusable_noninteractive()))
+    } else {
+        ui.add(label)
+    }
+}
+
+/**
+ * An ability's name with its description either in a tooltip or underneath.
+ * In accessibility mode the description is always shown and the name can be
+ * focused, reading out the description along with it.
+ */
+pub fn ability_label(
+    ui: &mut egui::Ui,
+    accessible: bool,
+    hovering: bool,
+    name: RichText,
+    description: &[DescriptionPart],
+) {
+    if accessible {
+        let spoken = t!(
+            "a11y.ability",
+            name = name.text(),
+            description = plain_text(description)
+        );
+        focusable_label(ui, true, name)
+            .widget_info(|| WidgetInfo::labeled(WidgetType::Label, &spoken));
+        linked_description(ui, description);
+    } else if hovering {
+        ui.label(name)
+            .on_hover_ui(|ui| linked_description(ui, description));
+    } else {
+        ui.label(name);
+        linked_description(ui, description);
+    }
+}
+
+/**
+ * A button for an action, returning whether it was used.
+ * Unusable actions are normally disabled, but in accessibility mode they stay
+ * focusable and tell the screen reader they can't be used right now.
+ */
+pub fn action_button(
+    ui: &mut egui::Ui,
+    accessible: bool,
+    hovering: bool,
+    text: RichText,
+    description: &[DescriptionPart],
+    usable: bool,
+) -> bool {
+    if accessible {
+        let description_text = plain_text(description);
+        let spoken = if usable {
+            t!(
+                "a11y.action_usable",
+                name = text.text(),
+                description = description_text
+            )
+        } else {
+            t!(
+                "a11y.action_unusable",
+                name = text.text(),
+                description = description_text
+            )
+        };
+        let response = ui.add(egui::Button::new(text));
+        response.widget_info(|| WidgetInfo::labeled(WidgetType::Button, &spoken));
+        if !description.is_empty() {
+            linked_description(ui, description);
+        }
+        return response.clicked() && usable;
+    }
+
+    let response = ui.add_enabled(usable, egui::Button::new(text));
+    let response = if description.is_empty() {
+        response
+    } else if hovering {
+        response
+            .on_hover_ui(|ui| linked_description(ui, description))
+            .on_disabled_hover_ui(|ui| linked_description(ui, description))
+    } else {
+        linked_description(ui, description);
+        response
+    };
+    response.clicked()
+}
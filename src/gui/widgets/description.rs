@@ -0,0 +1,76 @@
+use egui::{Id, RichText};
+
+use crate::gui::i18n::t;
+use crate::model::glossary::{DescriptionPart, TermLink};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+fn clicked_term_id() -> Id {
+    Id::new("clicked_term_link")
+}
+
+/**
+ * Show a description with its rules terms underlined. Keywords show their definition
+ * on hover and every term can be clicked to look it up in the compendium.
+ */
+pub fn linked_description(ui: &mut egui::Ui, parts: &[DescriptionPart]) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for part in parts {
+            match part {
+                DescriptionPart::Text(text) => {
+                    for (index, line) in text.split('\n').enumerate() {
+                        if index > 0 {
+                            ui.end_row();
+                        }
+                        if !line.is_empty() {
+                            ui.label(line);
+                        }
+                    }
+                }
+                DescriptionPart::Term(text, link) => {
+                    let response = match link {
+                        TermLink::Keyword(entry) => ui
+                            .add(
+                                egui::Label::new(RichText::new(text).underline())
+                                    .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text(entry.get_definition()),
+                        TermLink::Class(name) | TermLink::Origin(name) => ui
+                            .link(text)
+                            .on_hover_text(t!("glossary.open_in_compendium", name = name)),
+                    };
+                    if response.clicked() {
+                        ui.ctx().data_mut(|data| {
+                            data.insert_temp(clicked_term_id(), Some(link.clone()));
+                        });
+                    }
+                }
+            }
+        }
+    });
+}
+
+/**
+ * The term clicked in any description this frame, so it can be opened in the compendium
+ */
+pub fn take_clicked_term(ctx: &egui::Context) -> Option<TermLink> {
+    ctx.data_mut(|data| data.remove_temp::<Option<TermLink>>(clicked_term_id()))
+        .flatten()
+}
@@ -1 +1,3 @@
+pub mod accessible;
+pub mod description;
 pub mod panels;
@@ -1,65 +1,128 @@
 use egui::Widget;
 
-use crate::model::{game_state::GameState, save::Save};
+use super::accessible::{focusable_label, menu_section};
+use crate::gui::i18n::{self, t};
+use crate::model::{
+    game_state::{GameState, TurnSide},
+    save::Save,
+};
 
 #[derive(Debug)]
 pub struct StatsPanel<'a> {
     save: &'a Save,
     game_state: &'a mut GameState,
+    accessible: bool,
 }
 
 impl<'a> StatsPanel<'a> {
     pub fn new(save: &'a Save, game_state: &'a mut GameState) -> StatsPanel<'a> {
-        StatsPanel { save, game_state }
+        StatsPanel {
+            save,
+            game_state,
+            accessible: false,
+        }
+    }
+
+    /**
+     * Make the stats focusable and the adjustment menus keyboard friendly
+     */
+    pub fn accessible(mut self, accessible: bool) -> StatsPanel<'a> {
+        self.accessible = accessible;
+        self
     }
 }
 
+#[allow(
+    clippy::elidable_lifetime_names,
+    reason = "named like the inherent impl"
+)]
 impl<'a> Widget for StatsPanel<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
         ui.vertical(|ui| {
-            ui.label(format!("Campaign: {}", self.save.get_campaign_name()));
-            ui.label(format!("Batttle Number: {}", self.save.get_battle()));
-            ui.label(format!("Round Number: {}", self.game_state.get_round_num()));
-            ui.label(format!("Turn: {}", self.game_state.get_turn_side()));
-            ui.menu_button(format!("Power: {}", self.game_state.get_power()), |ui| {
-                if ui.button("Increment Power for Turn").clicked() {
-                    self.game_state.change_power_for_turn(1);
-                }
-                if ui.button("Decrement Power for Turn").clicked() {
-                    self.game_state.change_power_for_turn(-1);
-                }
-                if ui.button("Increment Power for Round").clicked() {
-                    self.game_state.change_power_for_round(1);
-                }
-                if ui.button("Decrement Power for Round").clicked() {
-                    self.game_state.change_power_for_round(-1);
-                }
-                if ui.button("Increment Power for Battle").clicked() {
-                    self.game_state.change_power_for_battle(1);
-                }
-                if ui.button("Decrement Power for Battle").clicked() {
-                    self.game_state.change_power_for_battle(-1);
-                }
-            });
-            ui.menu_button(
-                format!("Defense: {}", self.game_state.get_defense()),
+            focusable_label(
+                ui,
+                self.accessible,
+                t!("stats.campaign", name = self.save.get_campaign_name()),
+            );
+            focusable_label(
+                ui,
+                self.accessible,
+                t!(
+                    "stats.battle_number",
+                    number = i18n::number(self.save.get_battle())
+                ),
+            );
+            focusable_label(
+                ui,
+                self.accessible,
+                t!(
+                    "stats.round_number",
+                    number = i18n::number(self.game_state.get_round_num())
+                ),
+            );
+            focusable_label(
+                ui,
+                self.accessible,
+                t!(
+                    "stats.turn",
+                    side = match self.game_state.get_turn_side() {
+                        TurnSide::PlayerSide => t!("stats.side_player"),
+                        TurnSide::OpposingSide => t!("stats.side_opposing"),
+                    }
+                ),
+            );
+            menu_section(
+                ui,
+                self.accessible,
+                t!(
+                    "stats.power",
+                    value = i18n::number(self.game_state.get_power())
+                ),
+                |ui| {
+                    if ui.button(t!("stats.increment_power_turn")).clicked() {
+                        self.game_state.change_power_for_turn(1);
+                    }
+                    if ui.button(t!("stats.decrement_power_turn")).clicked() {
+                        self.game_state.change_power_for_turn(-1);
+                    }
+                    if ui.button(t!("stats.increment_power_round")).clicked() {
+                        self.game_state.change_power_for_round(1);
+                    }
+                    if ui.button(t!("stats.decrement_power_round")).clicked() {
+                        self.game_state.change_power_for_round(-1);
+                    }
+                    if ui.button(t!("stats.increment_power_battle")).clicked() {
+                        self.game_state.change_power_for_battle(1);
+                    }
+                    if ui.button(t!("stats.decrement_power_battle")).clicked() {
+                        self.game_state.change_power_for_battle(-1);
+                    }
+                },
+            );
+            menu_section(
+                ui,
+                self.accessible,
+                t!(
+                    "stats.defense",
+                    value = i18n::number(self.game_state.get_defense())
+                ),
                 |ui| {
-                    if ui.button("Increment Defense for Turn").clicked() {
+                    if ui.button(t!("stats.increment_defense_turn")).clicked() {
                         self.game_state.change_defense_for_turn(1);
                     }
-                    if ui.button("Decrement Defense for Turn").clicked() {
+                    if ui.button(t!("stats.decrement_defense_turn")).clicked() {
                         self.game_state.change_defense_for_turn(-1);
                     }
-                    if ui.button("Increment Defense for Round").clicked() {
+                    if ui.button(t!("stats.increment_defense_round")).clicked() {
                         self.game_state.change_defense_for_round(1);
                     }
-                    if ui.button("Decrement Defense for Round").clicked() {
+                    if ui.button(t!("stats.decrement_defense_round")).clicked() {
                         self.game_state.change_defense_for_round(-1);
                     }
-                    if ui.button("Increment Defense for Battle").clicked() {
+                    if ui.button(t!("stats.increment_defense_battle")).clicked() {
                         self.game_state.change_defense_for_battle(1);
                     }
-                    if ui.button("Decrement Defense for Battle").clicked() {
+                    if ui.button(t!("stats.decrement_defense_battle")).clicked() {
                         self.game_state.change_defense_for_battle(-1);
                     }
                 },
@@ -26,20 +26,22 @@ use crate::gui::main::GuiGreedApp;
 
 use clap::Parser;
 use eframe::NativeOptions;
-use egui::{TextStyle, ViewportBuilder};
+use egui::ViewportBuilder;
+use google::profile::RulesProfiles;
 use gui::state::AppState;
 use model::classes::ClassCache;
-use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use rfd::{MessageDialog, MessageDialogResult};
 use self_update::cargo_crate_version;
 use std::{
     env::{args_os, current_exe},
     process::Command,
 };
-use tokio::runtime::Runtime;
 use tracing::{error, info, warn, Level};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 
+mod api;
 mod cli;
+mod export;
 mod google;
 mod gui;
 mod model;
@@ -63,12 +65,23 @@ fn main() {
         .with_writer(non_blocking_file)
         .init();
 
-    let old_log_path = log_dir.to_owned().join("app.log");
+    let old_log_path = log_dir.join("app.log");
     if old_log_path.exists() {
         info!("Removing old log path '{old_log_path:?}!");
         std::fs::remove_file(old_log_path).unwrap();
     }
 
+    let args = Args::parse();
+
+    if let Some(command) = args.command {
+        if let Err(err) = cli::run(command) {
+            error!("{err}");
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if update_app() {
         let executable_name = current_exe().unwrap();
         let direct_args = args_os();
@@ -79,8 +92,6 @@ fn main() {
         return;
     }
 
-    let args = Args::parse();
-
     let img_bytes = include_bytes!(concat!(env!("OUT_DIR"), "greed_console_icon")).to_vec();
     let icon_data = egui::IconData {
         rgba: img_bytes,
@@ -104,80 +115,36 @@ fn main() {
             } else {
                 AppState::new()
             };
-            let starting_font_size = app_state.get_font_size();
-            if starting_font_size > 0.0 {
-                cc.egui_ctx.style_mut(|style| {
-                    for (style, font_id) in &mut style.text_styles {
-                        if style == &TextStyle::Body || style == &TextStyle::Button {
-                            font_id.size = app_state.get_font_size();
-                        }
-                    }
-                });
-            } else {
-                error!("Unable to set starting font size, attempted to use {starting_font_size} as the starting font size. Inverting the sign of the font size.");
-                app_state.set_font_size(-starting_font_size);
+            app_state.upgrade_legacy_settings();
+            app_state.get_theme().apply(&cc.egui_ctx);
+            if let Some(storage_dir) = eframe::storage_dir("Greed Console") {
+                gui::i18n::load_catalogues_from_dir(&storage_dir.join("locales"));
             }
+            gui::i18n::set_language(app_state.get_language());
 
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .unwrap();
-            let class_cache = if let Some(cache) =
-                eframe::get_value::<ClassCache>(cc.storage.unwrap(), "class_cache")
+            if let Some(response_cache) = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, "response_cache"))
             {
-                let current_rules_update_time = rt.block_on(google::get_update_time());
-                match current_rules_update_time {
-                    Ok(current_rules_update_time) => {
-                        if cache.get_cache_update_time() == Some(current_rules_update_time) {
-                            cache
-                        } else if !app_state.skip_rules_update_confirmation() {
-                            match MessageDialog::new()
-                                .set_title("Update Rules?")
-                                .set_description("Greed Rules Have been updated")
-                                .set_level(MessageLevel::Info)
-                                .set_buttons(MessageButtons::YesNo)
-                                .show()
-                            {
-                                MessageDialogResult::Yes => conditionally_get_new_cache(&rt, cache),
-                                _ => cache,
-                            }
-                        } else {
-                            conditionally_get_new_cache(&rt, cache)
-                        }
-                    }
-                    Err(err) => {
-                        error!("Error fetching current rules update time: {err}");
-                        cache
-                    }
-                }
-            } else {
-                rt.block_on(google::get_origins_and_classes()).unwrap()
-            };
-            Box::new(GuiGreedApp::new(class_cache, &args.campaigns, app_state))
+                google::fetch::restore_response_cache(response_cache);
+            }
+
+            // Checking for newer rules happens in the background once the window is up
+            let class_cache = cc
+                .storage
+                .and_then(|storage| eframe::get_value::<ClassCache>(storage, "class_cache"))
+                .unwrap_or_default();
+            Box::new(GuiGreedApp::new(
+                class_cache,
+                &args.campaigns,
+                app_state,
+                RulesProfiles::load(),
+            ))
         }),
     )
     .unwrap();
 }
 
-fn conditionally_get_new_cache(rt: &Runtime, old_cache: ClassCache) -> ClassCache {
-    match rt.block_on(google::get_origins_and_classes()) {
-        Ok(new_cache) => {
-            info!("Got new cache!");
-            new_cache
-        }
-        Err(err) => {
-            error!("Error getting new cache, using existing cache: {err}");
-            MessageDialog::new()
-                .set_title("Error")
-                .set_description("Error getting new rules from Google")
-                .set_level(MessageLevel::Warning)
-                .set_buttons(MessageButtons::Ok)
-                .show();
-            old_cache
-        }
-    }
-}
-
 fn update_app() -> bool {
     match self_update::backends::github::Update::configure()
         .no_confirm(true)
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::ids::{SourceId, SpecialId};
+
 /*
  * A console and digital character sheet for campaigns under the greed ruleset.
  * Copyright (C) 2023 Claire Moore
@@ -80,6 +82,9 @@ pub struct SpecialAction {
     #[serde(skip)]
     #[serde(default = "default_usable")]
     usable: bool,
+    /// Filled in by the origin or class giving the action, as it isn't written to caches
+    #[serde(skip)]
+    source: Option<SourceId>,
 }
 
 fn default_usable() -> bool {
@@ -92,9 +97,26 @@ impl SpecialAction {
             name: name.into(),
             description: description.into(),
             usable: true,
+            source: None,
         }
     }
 
+    /**
+     * The same action, noted as coming from the given origin or class unless it already
+     * knows where it comes from
+     */
+    pub fn with_source(mut self, source: SourceId) -> Self {
+        self.source.get_or_insert(source);
+        self
+    }
+
+    /**
+     * None until the action is read from the origin or class giving it
+     */
+    pub fn get_id(&self) -> Option<SpecialId> {
+        self.source.clone().map(SpecialId::new)
+    }
+
     pub fn is_named<'a, S: Into<&'a str>>(&self, name: S) -> bool {
         self.name == name.into()
     }
@@ -121,11 +143,12 @@ impl SpecialAction {
 }
 
 /**
- * Impl of ``PartialEq`` for ``SpecialAction``, only compare names
+ * Impl of ``PartialEq`` for ``SpecialAction``, only compare where the action comes from and
+ * its name, so actions sharing a name from different classes are told apart
  */
 impl PartialEq for SpecialAction {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
+        self.source == other.source && self.name == other.name
     }
 }
 
@@ -135,6 +158,7 @@ impl Eq for SpecialAction {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::ids::ClassId;
 
     #[test]
     fn test_primary_name() {
@@ -199,6 +223,25 @@ mod tests {
         assert_eq!(special1, special2);
     }
 
+    #[test]
+    fn test_special_action_eq_by_source() {
+        let fighter = SourceId::Class(ClassId::from_name("Fighter"));
+        let rogue = SourceId::Class(ClassId::from_name("Rogue"));
+        let fighter_surge = SpecialAction::new("Surge", "").with_source(fighter.clone());
+        let rogue_surge = SpecialAction::new("Surge", "").with_source(rogue.clone());
+
+        assert_ne!(fighter_surge, rogue_surge);
+        assert_eq!(
+            fighter_surge.get_id(),
+            Some(SpecialId::new(fighter.clone()))
+        );
+        assert_eq!(
+            fighter_surge.with_source(rogue).get_id(),
+            Some(SpecialId::new(fighter))
+        );
+        assert_eq!(SpecialAction::new("Surge", "").get_id(), None);
+    }
+
     #[test]
     fn test_special_use_and_refresh() {
         let mut special = SpecialAction::new("Test", "");
@@ -1,9 +1,15 @@
 use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
+use crate::model::glossary::Glossary;
+use crate::model::idea_bank::DraftClass;
+use crate::model::ids::{ClassId, OriginId, SourceId};
+use crate::model::renames::Renames;
 
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
 
 use std::any::Any;
+use std::collections::HashSet;
 
 /*
  * A console and digital character sheet for campaigns under the greed ruleset.
@@ -69,6 +75,254 @@ impl ClassPassive {
     }
 }
 
+/**
+ * Rules an origin brings besides its abilities, like taking a class instead of having any
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OriginRules {
+    /// Classes taken without counting toward the character's level
+    bonus_classes: usize,
+}
+
+impl OriginRules {
+    pub fn new(bonus_classes: usize) -> OriginRules {
+        OriginRules { bonus_classes }
+    }
+
+    pub fn get_bonus_classes(self) -> usize {
+        self.bonus_classes
+    }
+}
+
+/**
+ * The one action of each kind an origin gives, when it gives any
+ */
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OriginActions {
+    primary: PrimaryAction,
+    secondary: SecondaryAction,
+    special: SpecialAction,
+}
+
+impl OriginActions {
+    pub fn new(
+        primary: PrimaryAction,
+        secondary: SecondaryAction,
+        special: SpecialAction,
+    ) -> OriginActions {
+        OriginActions {
+            primary,
+            secondary,
+            special,
+        }
+    }
+}
+
+/**
+ * Where a character comes from, picked once alongside their classes. Unlike a class it has
+ * no level or prerequisites, and it may give no abilities at all.
+ */
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(from = "StoredOrigin")]
+pub struct Origin {
+    name: String,
+    utilities: Vec<ClassUtility>,
+    passives: Vec<ClassPassive>,
+    actions: Option<OriginActions>,
+    rules: OriginRules,
+}
+
+impl Origin {
+    pub fn new<N: Into<String>>(
+        name: N,
+        utilities: Vec<ClassUtility>,
+        passives: Vec<ClassPassive>,
+        actions: OriginActions,
+    ) -> Origin {
+        Origin {
+            name: name.into(),
+            utilities,
+            passives,
+            actions: Some(actions),
+            rules: OriginRules::default(),
+        }
+    }
+
+    /**
+     * An origin whose description gives rules, like extra classes, instead of abilities
+     */
+    pub fn without_abilities<N: Into<String>>(name: N, rules: OriginRules) -> Origin {
+        Origin {
+            name: name.into(),
+            utilities: vec![],
+            passives: vec![],
+            actions: None,
+            rules,
+        }
+    }
+
+    pub fn get_id(&self) -> OriginId {
+        OriginId::from_name(&self.name)
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_utilities(&self) -> &Vec<ClassUtility> {
+        &self.utilities
+    }
+
+    pub fn get_passives(&self) -> &Vec<ClassPassive> {
+        &self.passives
+    }
+
+    pub fn get_primary_action(&self) -> Option<PrimaryAction> {
+        self.actions.as_ref().map(|actions| actions.primary.clone())
+    }
+
+    pub fn get_secondary_action(&self) -> Option<SecondaryAction> {
+        self.actions
+            .as_ref()
+            .map(|actions| actions.secondary.clone())
+    }
+
+    pub fn get_special_action(&self) -> Option<SpecialAction> {
+        self.actions.as_ref().map(|actions| {
+            actions
+                .special
+                .clone()
+                .with_source(SourceId::Origin(self.get_id()))
+        })
+    }
+
+    pub fn has_abilities(&self) -> bool {
+        self.actions.is_some()
+    }
+
+    pub fn get_rules(&self) -> OriginRules {
+        self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: OriginRules) {
+        self.rules = rules;
+    }
+
+    /**
+     * Whether the other origin gives exactly the same, whatever either is called
+     */
+    pub fn has_same_abilities(&self, other: &Origin) -> bool {
+        self.utilities == other.utilities
+            && self.passives == other.passives
+            && self.actions == other.actions
+            && self.rules == other.rules
+    }
+
+    /**
+     * The origin's abilities shaped like a class without a level, for lists that gather
+     * abilities from the origin and classes together. None when it gives no abilities.
+     */
+    pub fn as_ability_source(&self) -> Option<Class> {
+        let actions = self.actions.clone()?;
+        Some(Class::new(
+            self.get_name(),
+            None,
+            self.utilities.clone(),
+            self.passives.clone(),
+            actions.primary,
+            actions.secondary,
+            actions.special.with_source(SourceId::Origin(self.get_id())),
+            None,
+        ))
+    }
+
+    /**
+     * Case insensitive search across the name and every ability name and description
+     */
+    pub fn matches_search(&self, query: &str) -> bool {
+        match self.as_ability_source() {
+            Some(source) => source.matches_search(query),
+            None => self
+                .name
+                .to_lowercase()
+                .contains(&query.trim().to_lowercase()),
+        }
+    }
+}
+
+/**
+ * An origin as found in a cache, which before origins had their own type was a class
+ * without a level. Both shapes are read so older caches keep loading.
+ */
+#[derive(Deserialize)]
+struct StoredOrigin {
+    name: String,
+    #[serde(default)]
+    utilities: Vec<ClassUtility>,
+    #[serde(default)]
+    passives: Vec<ClassPassive>,
+    #[serde(default)]
+    actions: Option<OriginActions>,
+    #[serde(default)]
+    rules: OriginRules,
+    #[serde(default, deserialize_with = "present")]
+    primary_action: Option<PrimaryAction>,
+    #[serde(default, deserialize_with = "present")]
+    secondary_action: Option<SecondaryAction>,
+    #[serde(default, deserialize_with = "present")]
+    special_action: Option<SpecialAction>,
+}
+
+/**
+ * Class fields were never wrapped in `Some`, so one being there at all is what counts
+ */
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+impl From<StoredOrigin> for Origin {
+    fn from(stored: StoredOrigin) -> Origin {
+        let StoredOrigin {
+            name,
+            utilities,
+            passives,
+            actions,
+            rules,
+            primary_action,
+            secondary_action,
+            special_action,
+        } = stored;
+        let (Some(primary), Some(secondary), Some(special)) =
+            (primary_action, secondary_action, special_action)
+        else {
+            return Origin {
+                name,
+                utilities,
+                passives,
+                actions,
+                rules,
+            };
+        };
+        // Older caches gave an origin without abilities an unnamed placeholder utility
+        // and a free class
+        let is_placeholder = !utilities.is_empty()
+            && utilities.iter().all(|utility| utility.name.is_empty())
+            && primary.get_name().is_empty();
+        if is_placeholder {
+            Origin::without_abilities(name, OriginRules::new(1))
+        } else {
+            Origin::new(
+                name,
+                utilities,
+                passives,
+                OriginActions::new(primary, secondary, special),
+            )
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct Class {
     name: String,
@@ -80,6 +334,9 @@ pub struct Class {
     secondary_action: SecondaryAction,
     special_action: SpecialAction,
     prerequisites: Option<Box<dyn ClassRequirement>>,
+    /// Classes the rules list as building on this one
+    #[serde(default)]
+    subclasses: Vec<String>,
 }
 
 impl Class {
@@ -103,9 +360,14 @@ impl Class {
             secondary_action,
             special_action,
             prerequisites,
+            subclasses: vec![],
         }
     }
 
+    pub fn get_id(&self) -> ClassId {
+        ClassId::from_name(&self.name)
+    }
+
     pub fn get_name(&self) -> String {
         self.name.clone()
     }
@@ -126,8 +388,26 @@ impl Class {
         self.secondary_action.clone()
     }
 
+    /**
+     * The class's special, noted as coming from it. An origin read as a class keeps its own.
+     */
     pub fn get_special_action(&self) -> SpecialAction {
-        self.special_action.clone()
+        self.special_action
+            .clone()
+            .with_source(SourceId::Class(self.get_id()))
+    }
+
+    /**
+     * Whether the other class has the same level and abilities, whatever either is called or
+     * requires
+     */
+    pub fn has_same_abilities(&self, other: &Class) -> bool {
+        self.level == other.level
+            && self.utilities == other.utilities
+            && self.passives == other.passives
+            && self.primary_action == other.primary_action
+            && self.secondary_action == other.secondary_action
+            && self.special_action == other.special_action
     }
 
     pub fn get_class_available(&self, current_classes: &[Class]) -> bool {
@@ -137,14 +417,93 @@ impl Class {
         }
     }
 
+    #[allow(clippy::ref_option, reason = "callers copy the level out")]
     pub fn get_level(&self) -> &Option<usize> {
         &self.level
     }
+
+    pub fn get_subclasses(&self) -> &[String] {
+        &self.subclasses
+    }
+
+    pub fn set_subclasses(&mut self, subclasses: Vec<String>) {
+        self.subclasses = subclasses;
+    }
+
+    /**
+     * Names of the classes this class builds on, empty when it has no prerequisites
+     */
+    pub fn get_parent_classes(&self, class_cache: &ClassCache) -> Vec<String> {
+        self.prerequisites
+            .as_ref()
+            .map(|requirements| requirements.get_parent_classes(class_cache))
+            .unwrap_or_default()
+    }
+
+    /**
+     * Names of the classes expected to list this class as a subclass. Where any of several
+     * classes meets a prerequisite, only the ones listing this class are expected to.
+     */
+    pub fn get_listing_parents(&self, class_cache: &ClassCache) -> Vec<String> {
+        self.prerequisites
+            .as_ref()
+            .map(|requirements| requirements.get_listing_parents(class_cache, self))
+            .unwrap_or_default()
+    }
+
+    /**
+     * Whether the Subclasses section names the given class
+     */
+    pub fn lists_subclass(&self, subclass: &Class) -> bool {
+        self.subclasses
+            .iter()
+            .any(|listed| ClassId::from_name(listed) == subclass.get_id())
+    }
+
+    /**
+     * Case insensitive search across the name and every ability name and description
+     */
+    pub fn matches_search(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let primary = &self.primary_action;
+        let secondary = &self.secondary_action;
+        let special = &self.special_action;
+        [
+            self.get_name(),
+            primary.get_name(),
+            primary.get_description(),
+            secondary.get_name(),
+            secondary.get_description(),
+            special.get_name(),
+            special.get_description(),
+        ]
+        .into_iter()
+        .chain(
+            self.utilities
+                .iter()
+                .flat_map(|utility| [utility.get_name(), utility.get_description()]),
+        )
+        .chain(
+            self.passives
+                .iter()
+                .flat_map(|passive| [passive.get_name(), passive.get_description()]),
+        )
+        .any(|text| text.to_lowercase().contains(&query))
+    }
 }
 
 #[typetag::serde]
-pub trait ClassRequirement: std::fmt::Debug + std::marker::Send {
+pub trait ClassRequirement: std::fmt::Debug + std::marker::Send + std::marker::Sync {
     fn meets_requirement(&self, current_classes: &[Class]) -> bool;
+    fn get_parent_classes(&self, _class_cache: &ClassCache) -> Vec<String> {
+        vec![]
+    }
+    fn get_listing_parents(&self, class_cache: &ClassCache, _subclass: &Class) -> Vec<String> {
+        self.get_parent_classes(class_cache)
+    }
     fn clone_dyn(&self) -> Box<dyn ClassRequirement>;
     #[allow(clippy::borrowed_box)]
     fn partial_eq_dyn(&self, other: &Box<dyn ClassRequirement>) -> bool;
@@ -172,8 +531,11 @@ impl ClassRequirement for SuperClassRequirement {
     fn meets_requirement(&self, current_classes: &[Class]) -> bool {
         current_classes
             .iter()
-            .map(Class::get_name)
-            .any(|current_class_name| current_class_name == self.class_name)
+            .any(|current_class| current_class.get_id() == ClassId::from_name(&self.class_name))
+    }
+
+    fn get_parent_classes(&self, _class_cache: &ClassCache) -> Vec<String> {
+        vec![self.class_name.clone()]
     }
 
     fn clone_dyn(&self) -> Box<dyn ClassRequirement> {
@@ -216,6 +578,18 @@ impl ClassRequirement for AndClassRequirement {
             && self.right.meets_requirement(current_classes)
     }
 
+    fn get_parent_classes(&self, class_cache: &ClassCache) -> Vec<String> {
+        let mut parents = self.left.get_parent_classes(class_cache);
+        parents.extend(self.right.get_parent_classes(class_cache));
+        parents
+    }
+
+    fn get_listing_parents(&self, class_cache: &ClassCache, subclass: &Class) -> Vec<String> {
+        let mut parents = self.left.get_listing_parents(class_cache, subclass);
+        parents.extend(self.right.get_listing_parents(class_cache, subclass));
+        parents
+    }
+
     fn clone_dyn(&self) -> Box<dyn ClassRequirement> {
         Box::new(self.clone())
     }
@@ -252,6 +626,37 @@ impl ClassRequirement for LevelPrefixRequirement {
         })
     }
 
+    fn get_parent_classes(&self, class_cache: &ClassCache) -> Vec<String> {
+        class_cache
+            .get_classes()
+            .into_iter()
+            .filter(|class| self.meets_requirement(std::slice::from_ref(*class)))
+            .map(Class::get_name)
+            .collect()
+    }
+
+    /**
+     * Any class with the prefix will do, so only the ones listing the subclass are expected to,
+     * or all of them when none do
+     */
+    fn get_listing_parents(&self, class_cache: &ClassCache, subclass: &Class) -> Vec<String> {
+        let candidates = class_cache
+            .get_classes()
+            .into_iter()
+            .filter(|class| self.meets_requirement(std::slice::from_ref(*class)))
+            .collect::<Vec<_>>();
+        let listing = candidates
+            .iter()
+            .filter(|class| class.lists_subclass(subclass))
+            .map(|class| class.get_name())
+            .collect::<Vec<_>>();
+        if listing.is_empty() {
+            candidates.into_iter().map(Class::get_name).collect()
+        } else {
+            listing
+        }
+    }
+
     fn clone_dyn(&self) -> Box<dyn ClassRequirement> {
         Box::new(self.clone())
     }
@@ -274,45 +679,159 @@ impl LevelPrefixRequirement {
     }
 }
 
-#[derive(Debug, PartialEq, Default, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Default, Clone, Deserialize, Serialize)]
 pub struct ClassCache {
-    origins: IndexMap<String, Class>,
-    classes: IndexMap<String, Class>,
+    origins: IndexMap<OriginId, Origin>,
+    classes: IndexMap<ClassId, Class>,
     #[serde(default)]
     cache_update_time: Option<i64>,
+    #[serde(default)]
+    glossary: Glossary,
+    #[serde(default)]
+    idea_bank: Vec<DraftClass>,
+    #[serde(default)]
+    renames: Renames,
+}
+
+/**
+ * A disagreement between a class's listed subclasses and the prerequisites of the classes
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SubclassMismatch {
+    #[error("{class} lists {subclass} as a subclass, but there is no such class")]
+    UnknownSubclass { class: String, subclass: String },
+    #[error("{class} lists {subclass} as a subclass, but {subclass} doesn't require {class}")]
+    MissingPrerequisite { class: String, subclass: String },
+    #[error("{subclass} requires {class}, but {class} doesn't list it as a subclass")]
+    Unlisted { class: String, subclass: String },
 }
 
 impl ClassCache {
     pub fn new(
-        origins: Vec<Class>,
+        origins: Vec<Origin>,
         classes: Vec<Class>,
         cache_update_time: Option<i64>,
     ) -> ClassCache {
         ClassCache {
             origins: origins
                 .into_iter()
-                .map(|origin| (origin.get_name(), origin))
+                .map(|origin| (origin.get_id(), origin))
                 .collect(),
             classes: classes
                 .into_iter()
-                .map(|class| (class.get_name(), class))
+                .map(|class| (class.get_id(), class))
                 .collect(),
             cache_update_time,
+            glossary: Glossary::default(),
+            idea_bank: vec![],
+            renames: Renames::default(),
         }
     }
 
-    pub fn get_origins(&self) -> Vec<&Class> {
+    pub fn get_glossary(&self) -> &Glossary {
+        &self.glossary
+    }
+
+    pub fn set_glossary(&mut self, glossary: Glossary) {
+        self.glossary = glossary;
+    }
+
+    pub fn get_idea_bank(&self) -> &[DraftClass] {
+        &self.idea_bank
+    }
+
+    pub fn set_idea_bank(&mut self, idea_bank: Vec<DraftClass>) {
+        self.idea_bank = idea_bank;
+    }
+
+    pub fn get_origins(&self) -> Vec<&Origin> {
         self.origins.values().collect()
     }
 
-    pub fn get_origin<'a, N: Into<&'a str>>(&self, origin_name: N) -> Option<&Class> {
-        self.origins.get(origin_name.into())
+    pub fn get_origin(&self, origin_id: &OriginId) -> Option<&Origin> {
+        self.origins.get(origin_id)
     }
 
     pub fn get_classes(&self) -> Vec<&Class> {
         self.classes.values().collect()
     }
 
+    pub fn get_class(&self, class_id: &ClassId) -> Option<&Class> {
+        self.classes.get(class_id)
+    }
+
+    /**
+     * Name of a class to show, the last one the rules used when they no longer have it
+     */
+    pub fn get_class_name(&self, class_id: &ClassId) -> String {
+        match self.get_class(class_id) {
+            Some(class) => class.get_name(),
+            None => self.renames.get_class(class_id).map_or_else(
+                || class_id.to_string(),
+                |retired| retired.get_name().to_owned(),
+            ),
+        }
+    }
+
+    /**
+     * Name of an origin to show, the last one the rules used when they no longer have it
+     */
+    pub fn get_origin_name(&self, origin_id: &OriginId) -> String {
+        match self.get_origin(origin_id) {
+            Some(origin) => origin.get_name(),
+            None => self.renames.get_origin(origin_id).map_or_else(
+                || origin_id.to_string(),
+                |retired| retired.get_name().to_owned(),
+            ),
+        }
+    }
+
+    /**
+     * Where a class the rules no longer have by this identifier went, if it was renamed
+     */
+    pub fn get_renamed_class(&self, class_id: &ClassId) -> Option<&ClassId> {
+        self.renames.get_class(class_id)?.get_renamed_to()
+    }
+
+    /**
+     * Where an origin the rules no longer have by this identifier went, if it was renamed
+     */
+    pub fn get_renamed_origin(&self, origin_id: &OriginId) -> Option<&OriginId> {
+        self.renames.get_origin(origin_id)?.get_renamed_to()
+    }
+
+    /**
+     * Note what the previous rules had that these don't, and what it was renamed to, so saves
+     * still find what they took. Called when newly fetched rules replace the previous ones.
+     */
+    pub fn carry_renames_from(&mut self, previous: &ClassCache) {
+        self.renames = previous.renames.carry(
+            (&previous.origins, &previous.classes),
+            (&self.origins, &self.classes),
+        );
+    }
+
+    /**
+     * Look a class up by how it is written, for imports and the rules' own references
+     */
+    pub fn find_class(&self, class_name: &str) -> Option<&Class> {
+        self.get_class(&ClassId::from_name(class_name))
+    }
+
+    /**
+     * Look an origin up by how it is written, for imports
+     */
+    pub fn find_origin(&self, origin_name: &str) -> Option<&Origin> {
+        self.get_origin(&OriginId::from_name(origin_name))
+    }
+
+    /**
+     * Whether no rules have been loaded yet
+     */
+    pub fn is_empty(&self) -> bool {
+        self.origins.is_empty() && self.classes.is_empty()
+    }
+
     pub fn get_class_cache_count(&self) -> usize {
         self.classes.len()
     }
@@ -321,11 +840,298 @@ impl ClassCache {
         self.cache_update_time
     }
 
-    pub fn map_to_concrete_classes(&self, class_names: &[String]) -> Vec<Class> {
-        class_names
+    pub fn map_to_concrete_classes(&self, class_ids: &[ClassId]) -> Vec<Class> {
+        class_ids
             .iter()
-            .filter_map(|class_name| self.classes.get(class_name))
+            .filter_map(|class_id| self.classes.get(class_id))
             .cloned()
             .collect()
     }
+
+    /**
+     * Classes without prerequisites that the given class ultimately builds on,
+     * a class without prerequisites is its own root
+     */
+    pub fn get_prerequisite_roots(&self, class: &Class) -> Vec<String> {
+        let mut roots = vec![];
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![class.get_name()];
+        while let Some(class_name) = to_visit.pop() {
+            if !visited.insert(class_name.clone()) {
+                continue;
+            }
+            let parents = self
+                .find_class(&class_name)
+                .map(|class| class.get_parent_classes(self))
+                .unwrap_or_default();
+            if parents.is_empty() {
+                roots.push(class_name);
+            } else {
+                to_visit.extend(parents);
+            }
+        }
+        roots.sort();
+        roots
+    }
+
+    /**
+     * Check each class's Subclasses section against the prerequisites of the classes it lists,
+     * and the other way around
+     */
+    pub fn get_subclass_report(&self) -> Vec<SubclassMismatch> {
+        let mut report = vec![];
+        for class in self.classes.values() {
+            for subclass_name in &class.subclasses {
+                let mismatch = match self.find_class(subclass_name) {
+                    None => SubclassMismatch::UnknownSubclass {
+                        class: class.get_name(),
+                        subclass: subclass_name.clone(),
+                    },
+                    Some(subclass)
+                        if !subclass
+                            .get_parent_classes(self)
+                            .iter()
+                            .any(|parent| ClassId::from_name(parent) == class.get_id()) =>
+                    {
+                        SubclassMismatch::MissingPrerequisite {
+                            class: class.get_name(),
+                            subclass: subclass_name.clone(),
+                        }
+                    }
+                    Some(_) => continue,
+                };
+                report.push(mismatch);
+            }
+        }
+        for subclass in self.classes.values() {
+            for parent_name in subclass.get_listing_parents(self) {
+                let listed = self
+                    .find_class(&parent_name)
+                    .is_none_or(|parent| parent.lists_subclass(subclass));
+                if !listed {
+                    report.push(SubclassMismatch::Unlisted {
+                        class: parent_name,
+                        subclass: subclass.get_name(),
+                    });
+                }
+            }
+        }
+        report
+    }
+
+    /**
+     * Stable FNV-1a hash of the origin and class names and levels, used to tell
+     * whether two sets of rules would resolve a build the same way
+     */
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        for origin in self.origins.values() {
+            feed(origin.get_name().as_bytes());
+            feed(&[0]);
+        }
+        feed(&[1]);
+        for class in self.classes.values() {
+            feed(class.get_name().as_bytes());
+            feed(&class.get_level().unwrap_or(0).to_le_bytes());
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::test_support::test_class;
+
+    #[test]
+    fn test_origins_from_older_caches() {
+        let placeholder = Class::new(
+            "Human",
+            None,
+            vec![ClassUtility::new("", "")],
+            vec![ClassPassive::new("", "")],
+            PrimaryAction::new("", ""),
+            SecondaryAction::new("", ""),
+            SpecialAction::new("", ""),
+            None,
+        );
+        let mut elf = test_class("Elf", None, None);
+        elf.primary_action = PrimaryAction::new("Volley", "Shoot twice");
+        let legacy = format!(
+            "(origins: {{\"Human\": {}, \"Elf\": {}}}, classes: {{}})",
+            ron::to_string(&placeholder).unwrap(),
+            ron::to_string(&elf).unwrap(),
+        );
+        let cache: ClassCache = ron::from_str(&legacy).unwrap();
+
+        let human = cache.get_origin(&OriginId::from_name("Human")).unwrap();
+        assert!(!human.has_abilities());
+        assert_eq!(human.get_rules(), OriginRules::new(1));
+        let elf = cache.get_origin(&OriginId::from_name("Elf")).unwrap();
+        assert!(elf.has_abilities());
+        assert_eq!(elf.get_rules(), OriginRules::default());
+        assert_eq!(
+            elf.get_primary_action(),
+            Some(PrimaryAction::new("Volley", "Shoot twice"))
+        );
+
+        let resaved: ClassCache = ron::from_str(&ron::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(resaved.get_origins(), cache.get_origins());
+    }
+
+    #[test]
+    fn test_fingerprint_stable() {
+        let cache = ClassCache::new(
+            vec![Origin::without_abilities("Elf", OriginRules::default())],
+            vec![test_class("Fighter", Some(1), None)],
+            Some(1),
+        );
+        let same_rules = ClassCache::new(
+            vec![Origin::without_abilities("Elf", OriginRules::default())],
+            vec![test_class("Fighter", Some(1), None)],
+            Some(2),
+        );
+
+        assert_eq!(cache.fingerprint(), same_rules.fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_rules() {
+        let cache = ClassCache::new(vec![], vec![test_class("Fighter", Some(1), None)], None);
+        let renamed = ClassCache::new(vec![], vec![test_class("Warrior", Some(1), None)], None);
+        let releveled = ClassCache::new(vec![], vec![test_class("Fighter", Some(2), None)], None);
+
+        assert_ne!(cache.fingerprint(), renamed.fingerprint());
+        assert_ne!(cache.fingerprint(), releveled.fingerprint());
+    }
+
+    #[test]
+    fn test_prerequisite_roots() {
+        let mut knight = test_class("Knight", Some(2), None);
+        knight.prerequisites = Some(Box::new(SuperClassRequirement::new("Fighter")));
+        let mut paladin = test_class("Paladin", Some(3), None);
+        paladin.prerequisites = Some(Box::new(AndClassRequirement::new(
+            Box::new(SuperClassRequirement::new("Knight")),
+            Box::new(LevelPrefixRequirement::new(1, "Cleric")),
+        )));
+        let cache = ClassCache::new(
+            vec![],
+            vec![
+                test_class("Fighter", Some(1), None),
+                test_class("Cleric", Some(1), None),
+                knight,
+                paladin.clone(),
+            ],
+            None,
+        );
+
+        assert_eq!(
+            cache.get_prerequisite_roots(&paladin),
+            vec!["Cleric".to_owned(), "Fighter".to_owned()]
+        );
+        assert_eq!(
+            cache.get_prerequisite_roots(&test_class("Fighter", Some(1), None)),
+            vec!["Fighter".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_subclass_report() {
+        let mut fighter = test_class("Fighter", Some(1), None);
+        fighter.set_subclasses(vec!["Knight".to_owned(), "Bard".to_owned()]);
+        let mut knight = test_class("Knight", Some(2), None);
+        knight.prerequisites = Some(Box::new(SuperClassRequirement::new("Fighter")));
+        let mut rogue = test_class("Rogue", Some(1), None);
+        rogue.set_subclasses(vec!["Knight".to_owned()]);
+        let mut duelist = test_class("Duelist", Some(2), None);
+        duelist.prerequisites = Some(Box::new(SuperClassRequirement::new("Fighter")));
+        let cache = ClassCache::new(vec![], vec![fighter, knight, rogue, duelist], None);
+
+        assert_eq!(
+            cache.get_subclass_report(),
+            [
+                SubclassMismatch::UnknownSubclass {
+                    class: "Fighter".to_owned(),
+                    subclass: "Bard".to_owned()
+                },
+                SubclassMismatch::MissingPrerequisite {
+                    class: "Rogue".to_owned(),
+                    subclass: "Knight".to_owned()
+                },
+                SubclassMismatch::Unlisted {
+                    class: "Fighter".to_owned(),
+                    subclass: "Duelist".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subclass_report_with_shared_prefix() {
+        let mut fighter = test_class("Fighter", Some(1), None);
+        fighter.set_subclasses(vec!["Knight".to_owned()]);
+        let fencer = test_class("Fencer", Some(1), None);
+        let mut knight = test_class("Knight", Some(2), None);
+        knight.prerequisites = Some(Box::new(LevelPrefixRequirement::new(1, "F")));
+        let mut duelist = test_class("Duelist", Some(2), None);
+        duelist.prerequisites = Some(Box::new(LevelPrefixRequirement::new(1, "F")));
+        let cache = ClassCache::new(vec![], vec![fighter, fencer, knight, duelist], None);
+
+        assert_eq!(
+            cache.get_subclass_report(),
+            [
+                SubclassMismatch::Unlisted {
+                    class: "Fighter".to_owned(),
+                    subclass: "Duelist".to_owned()
+                },
+                SubclassMismatch::Unlisted {
+                    class: "Fencer".to_owned(),
+                    subclass: "Duelist".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_search() {
+        let class = Class::new(
+            "Fighter",
+            Some(1),
+            vec![ClassUtility::new("Athletics", "Climb and jump")],
+            vec![],
+            PrimaryAction::new("Strike", ""),
+            SecondaryAction::new("Shove", ""),
+            SpecialAction::new("Surge", "Take two more primary actions"),
+            None,
+        );
+
+        assert!(class.matches_search(""));
+        assert!(class.matches_search("fight"));
+        assert!(class.matches_search("CLIMB"));
+        assert!(class.matches_search("more primary"));
+        assert!(!class.matches_search("fireball"));
+    }
+
+    #[test]
+    fn test_map_to_concrete_classes_drops_unknown() {
+        let cache = ClassCache::new(vec![], vec![test_class("Fighter", Some(1), None)], None);
+
+        let classes = cache.map_to_concrete_classes(&[
+            ClassId::from_name("Fighter"),
+            ClassId::from_name("Warrior"),
+        ]);
+
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].get_name(), "Fighter");
+    }
 }
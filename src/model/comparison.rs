@@ -0,0 +1,268 @@
+use super::classes::{Class, ClassCache};
+use super::sheets::Character;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbilityCategory {
+    Utilities,
+    Passives,
+    Primary,
+    Secondary,
+    Special,
+}
+
+impl AbilityCategory {
+    pub const ALL: [AbilityCategory; 5] = [
+        AbilityCategory::Utilities,
+        AbilityCategory::Passives,
+        AbilityCategory::Primary,
+        AbilityCategory::Secondary,
+        AbilityCategory::Special,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComparedAbility {
+    pub name: String,
+    pub description: String,
+}
+
+/**
+ * One column of a comparison, either a single class or a whole character build
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComparisonColumn {
+    pub title: String,
+    abilities: [Vec<ComparedAbility>; 5],
+    /// Classes this column opens up, what a class unlocks or what a build could take next
+    pub opens: Vec<String>,
+}
+
+/**
+ * How a column differs from the baseline column in one category or in what it opens
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnDiff {
+    pub gained: Vec<String>,
+    pub lost: Vec<String>,
+}
+
+impl ComparisonColumn {
+    pub fn from_class(class: &Class, class_cache: &ClassCache) -> ComparisonColumn {
+        ComparisonColumn::from_sources(
+            class.get_name(),
+            std::slice::from_ref(class),
+            class_cache
+                .get_classes()
+                .into_iter()
+                .filter(|other| {
+                    other
+                        .get_parent_classes(class_cache)
+                        .contains(&class.get_name())
+                })
+                .map(Class::get_name)
+                .collect(),
+        )
+    }
+
+    pub fn from_character<T: Into<String>>(
+        title: T,
+        character: &Character,
+        class_cache: &ClassCache,
+    ) -> ComparisonColumn {
+        let current_classes = class_cache.map_to_concrete_classes(character.get_classes());
+        ComparisonColumn::from_sources(
+            title.into(),
+            &character.get_ability_sources(class_cache),
+            class_cache
+                .get_classes()
+                .into_iter()
+                .filter(|class| {
+                    !character.get_classes().contains(&class.get_id())
+                        && class.get_class_available(&current_classes)
+                })
+                .map(Class::get_name)
+                .collect(),
+        )
+    }
+
+    fn from_sources(title: String, sources: &[Class], opens: Vec<String>) -> ComparisonColumn {
+        let ability = |name: String, description: String| ComparedAbility { name, description };
+        let mut abilities: [Vec<ComparedAbility>; 5] = Default::default();
+        for source in sources {
+            abilities[AbilityCategory::Utilities.index()].extend(
+                source
+                    .get_utilities()
+                    .iter()
+                    .map(|utility| ability(utility.get_name(), utility.get_description())),
+            );
+            abilities[AbilityCategory::Passives.index()].extend(
+                source
+                    .get_passives()
+                    .iter()
+                    .map(|passive| ability(passive.get_name(), passive.get_description())),
+            );
+            let primary = source.get_primary_action();
+            abilities[AbilityCategory::Primary.index()]
+                .push(ability(primary.get_name(), primary.get_description()));
+            let secondary = source.get_secondary_action();
+            abilities[AbilityCategory::Secondary.index()]
+                .push(ability(secondary.get_name(), secondary.get_description()));
+            let special = source.get_special_action();
+            abilities[AbilityCategory::Special.index()]
+                .push(ability(special.get_name(), special.get_description()));
+        }
+        for category in &mut abilities {
+            category.retain(|ability| !ability.name.is_empty());
+        }
+        ComparisonColumn {
+            title,
+            abilities,
+            opens,
+        }
+    }
+
+    pub fn get_abilities(&self, category: AbilityCategory) -> &[ComparedAbility] {
+        &self.abilities[category.index()]
+    }
+
+    /**
+     * Abilities in a category this column has that the baseline lacks, and the reverse
+     */
+    pub fn diff_abilities(
+        &self,
+        baseline: &ComparisonColumn,
+        category: AbilityCategory,
+    ) -> ColumnDiff {
+        let names = |column: &ComparisonColumn| {
+            column
+                .get_abilities(category)
+                .iter()
+                .map(|ability| ability.name.clone())
+                .collect::<Vec<_>>()
+        };
+        diff_names(&names(self), &names(baseline))
+    }
+
+    /**
+     * Classes opened or closed relative to the baseline column
+     */
+    pub fn diff_opens(&self, baseline: &ComparisonColumn) -> ColumnDiff {
+        diff_names(&self.opens, &baseline.opens)
+    }
+}
+
+fn diff_names(current: &[String], baseline: &[String]) -> ColumnDiff {
+    ColumnDiff {
+        gained: current
+            .iter()
+            .filter(|name| !baseline.contains(name))
+            .cloned()
+            .collect(),
+        lost: baseline
+            .iter()
+            .filter(|name| !current.contains(name))
+            .cloned()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::classes::ClassPassive;
+    use crate::model::ids::ClassId;
+    use crate::model::test_support::test_class_with_passives;
+
+    fn test_class(name: &str, passive: &str, prerequisite: Option<&str>) -> Class {
+        test_class_with_passives(
+            name,
+            Some(1),
+            prerequisite,
+            vec![ClassPassive::new(passive, "")],
+        )
+    }
+
+    fn test_cache() -> ClassCache {
+        ClassCache::new(
+            vec![],
+            vec![
+                test_class("Fighter", "Tough", None),
+                test_class("Wizard", "Studious", None),
+                test_class("Knight", "Tough", Some("Fighter")),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_class_columns() {
+        let cache = test_cache();
+        let fighter = ComparisonColumn::from_class(
+            cache.get_class(&ClassId::from_name("Fighter")).unwrap(),
+            &cache,
+        );
+        let wizard = ComparisonColumn::from_class(
+            cache.get_class(&ClassId::from_name("Wizard")).unwrap(),
+            &cache,
+        );
+
+        assert_eq!(fighter.opens, vec!["Knight".to_owned()]);
+        assert!(fighter.get_abilities(AbilityCategory::Utilities).is_empty());
+        assert_eq!(
+            wizard.diff_abilities(&fighter, AbilityCategory::Passives),
+            ColumnDiff {
+                gained: vec!["Studious".to_owned()],
+                lost: vec!["Tough".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_character_columns() {
+        let cache = test_cache();
+        let mut fighter = Character::default();
+        fighter.add_class(ClassId::from_name("Fighter"));
+        let mut wizard = Character::default();
+        wizard.add_class(ClassId::from_name("Wizard"));
+
+        let fighter = ComparisonColumn::from_character("A", &fighter, &cache);
+        let wizard = ComparisonColumn::from_character("B", &wizard, &cache);
+
+        assert_eq!(
+            wizard.diff_opens(&fighter),
+            ColumnDiff {
+                gained: vec!["Fighter".to_owned()],
+                lost: vec!["Wizard".to_owned(), "Knight".to_owned()],
+            }
+        );
+        assert_eq!(
+            wizard
+                .diff_abilities(&fighter, AbilityCategory::Primary)
+                .gained,
+            vec!["Wizard Primary".to_owned()]
+        );
+    }
+}
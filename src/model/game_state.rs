@@ -115,14 +115,14 @@ impl GameState {
     /**
      * Set `GameState` such that a special action was used
      */
-    pub fn use_special<'a, S: Into<&'a str>>(&mut self, name: S) {
-        let name_ref = name.into();
-        for action in &mut self.special_actions.iter_mut() {
-            if action.is_named(name_ref) {
-                action.use_action();
-                info!("Used action {}", name_ref);
-                break;
-            }
+    pub fn use_special(&mut self, special: &SpecialAction) {
+        if let Some(action) = self
+            .special_actions
+            .iter_mut()
+            .find(|action| *action == special)
+        {
+            action.use_action();
+            info!("Used action {}", action.get_name());
         }
         self.special_usable = false;
     }
@@ -367,7 +367,7 @@ mod tests {
         assert!(!state.get_secondary_usable());
 
         assert!(state.get_any_special_usable());
-        state.use_special("Test");
+        state.use_special(&SpecialAction::new("Test", "Lorem ipsum"));
         assert!(!state.get_any_special_usable());
 
         assert!(state.get_inspiration_usable());
@@ -382,7 +382,7 @@ mod tests {
 
         state.use_primary();
         state.use_secondary();
-        state.use_special("Test");
+        state.use_special(&SpecialAction::new("Test", "Lorem ipsum"));
         state.use_inspiration();
 
         assert!(!state.get_primary_usable());
@@ -411,7 +411,7 @@ mod tests {
 
         state.use_primary();
         state.use_secondary();
-        state.use_special("Test");
+        state.use_special(&SpecialAction::new("Test", "Lorem ipsum"));
         state.use_inspiration();
 
         assert!(!state.get_primary_usable());
@@ -497,7 +497,7 @@ mod tests {
         assert!(special.unwrap().is_usable());
         assert!(state.get_any_special_usable());
 
-        state.use_special("Test");
+        state.use_special(&SpecialAction::new("Test", "Lorem ipsum"));
 
         assert_eq!(state.get_special_actions().len(), 1);
         let special = state
@@ -531,7 +531,7 @@ mod tests {
         assert!(special.unwrap().is_usable());
         assert!(state.get_any_special_usable());
 
-        state.use_special("Test2");
+        state.use_special(&SpecialAction::new("Test2", "Lorem ipsum"));
 
         assert_eq!(state.get_special_actions().len(), 2);
         let special = state
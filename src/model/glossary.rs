@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+
+use super::classes::ClassCache;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Longest run of words treated as a term rather than a sentence
+const MAX_TERM_WORDS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    term: String,
+    definition: String,
+}
+
+impl GlossaryEntry {
+    pub fn new<T: Into<String>, D: Into<String>>(term: T, definition: D) -> GlossaryEntry {
+        GlossaryEntry {
+            term: term.into(),
+            definition: definition.into(),
+        }
+    }
+
+    pub fn get_term(&self) -> &str {
+        &self.term
+    }
+
+    pub fn get_definition(&self) -> &str {
+        &self.definition
+    }
+}
+
+/**
+ * Rules keywords and their definitions, taken from the general rules before the origins
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Glossary {
+    entries: Vec<GlossaryEntry>,
+}
+
+impl Glossary {
+    pub fn new(entries: Vec<GlossaryEntry>) -> Glossary {
+        Glossary { entries }
+    }
+
+    /**
+     * Pick out `Term: definition` lines, and short headings followed by a paragraph.
+     * The first definition of a term wins.
+     */
+    pub fn from_rules(lines: &[String]) -> Glossary {
+        let mut glossary = Glossary::default();
+        for (index, line) in lines.iter().enumerate() {
+            let line = clean_line(line);
+            if let Some((term, definition)) = definition_line(line) {
+                glossary.add(term, definition);
+                continue;
+            }
+            let heading = line.strip_suffix(':').unwrap_or(line);
+            if !is_term(heading) {
+                continue;
+            }
+            let paragraph = lines[index + 1..]
+                .iter()
+                .map(|line| clean_line(line))
+                .take_while(|line| !line.is_empty())
+                .collect::<Vec<_>>();
+            if paragraph
+                .first()
+                .is_some_and(|first| !is_term(first) && definition_line(first).is_none())
+            {
+                glossary.add(heading, &paragraph.join(" "));
+            }
+        }
+        glossary
+    }
+
+    /**
+     * Pick out definitions from a document with real headings, where a heading's term is
+     * defined by the paragraphs under it, along with any `Term: definition` paragraphs
+     */
+    pub fn from_sections<'a>(
+        sections: impl IntoIterator<Item = (&'a str, Vec<&'a str>)>,
+    ) -> Glossary {
+        let mut glossary = Glossary::default();
+        for (heading, paragraphs) in sections {
+            let (definitions, paragraphs): (Vec<_>, Vec<_>) = paragraphs
+                .into_iter()
+                .map(clean_line)
+                .filter(|paragraph| !paragraph.is_empty())
+                .partition(|paragraph| definition_line(paragraph).is_some());
+            let heading = clean_line(heading);
+            let heading = heading.strip_suffix(':').unwrap_or(heading);
+            if is_term(heading) && !paragraphs.is_empty() {
+                glossary.add(heading, &paragraphs.join(" "));
+            }
+            for (term, definition) in definitions.into_iter().filter_map(definition_line) {
+                glossary.add(term, definition);
+            }
+        }
+        glossary
+    }
+
+    fn add(&mut self, term: &str, definition: &str) {
+        if self.get_definition(term).is_none() {
+            self.entries.push(GlossaryEntry::new(term, definition));
+        }
+    }
+
+    pub fn get_entries(&self) -> &[GlossaryEntry] {
+        &self.entries
+    }
+
+    pub fn get_definition(&self, term: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.term.eq_ignore_ascii_case(term))
+            .map(GlossaryEntry::get_definition)
+    }
+}
+
+fn clean_line(line: &str) -> &str {
+    let line = line.trim().trim_start_matches('\u{feff}');
+    line.strip_prefix("* ")
+        .or_else(|| line.strip_prefix("- "))
+        .or_else(|| line.strip_prefix("• "))
+        .unwrap_or(line)
+        .trim()
+}
+
+fn definition_line(line: &str) -> Option<(&str, &str)> {
+    let (term, definition) = line.split_once(':')?;
+    let definition = definition.trim();
+    (is_term(term) && !definition.is_empty()).then_some((term.trim(), definition))
+}
+
+fn is_term(text: &str) -> bool {
+    let text = text.trim();
+    let words = text.split_whitespace().count();
+    (1..=MAX_TERM_WORDS).contains(&words)
+        && text.chars().next().is_some_and(char::is_uppercase)
+        && text
+            .chars()
+            .all(|character| character.is_alphabetic() || matches!(character, ' ' | '-' | '\''))
+}
+
+/**
+ * What a linked word in a description points at
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermLink {
+    Origin(String),
+    Class(String),
+    Keyword(GlossaryEntry),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptionPart {
+    Text(String),
+    Term(String, TermLink),
+}
+
+/**
+ * Every linkable name in the rules, longest first so "Fire Mage" wins over "Mage"
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TermIndex {
+    terms: Vec<(String, TermLink)>,
+}
+
+impl TermIndex {
+    pub fn new(class_cache: &ClassCache) -> TermIndex {
+        let mut terms = class_cache
+            .get_origins()
+            .into_iter()
+            .map(|origin| (origin.get_name(), TermLink::Origin(origin.get_name())))
+            .chain(
+                class_cache
+                    .get_classes()
+                    .into_iter()
+                    .map(|class| (class.get_name(), TermLink::Class(class.get_name()))),
+            )
+            .chain(
+                class_cache
+                    .get_glossary()
+                    .get_entries()
+                    .iter()
+                    .map(|entry| {
+                        (
+                            entry.get_term().to_owned(),
+                            TermLink::Keyword(entry.clone()),
+                        )
+                    }),
+            )
+            .filter(|(term, _)| !term.trim().is_empty())
+            .collect::<Vec<_>>();
+        terms.sort_by_key(|(term, _)| std::cmp::Reverse(term.len()));
+        TermIndex { terms }
+    }
+
+    /**
+     * Split a description into plain text and whole word, case insensitive term matches.
+     * A trailing "s" still counts, so "Inspirations" links to "Inspiration".
+     */
+    pub fn link(&self, text: &str) -> Vec<DescriptionPart> {
+        let mut parts = vec![];
+        let mut plain_start = 0;
+        let mut position = 0;
+        while position < text.len() {
+            let at_word_start = text[..position]
+                .chars()
+                .next_back()
+                .is_none_or(|previous| !previous.is_alphanumeric());
+            let found = at_word_start
+                .then(|| self.match_at(text, position))
+                .flatten();
+            if let Some((length, link)) = found {
+                if plain_start < position {
+                    parts.push(DescriptionPart::Text(
+                        text[plain_start..position].to_owned(),
+                    ));
+                }
+                parts.push(DescriptionPart::Term(
+                    text[position..position + length].to_owned(),
+                    link.clone(),
+                ));
+                position += length;
+                plain_start = position;
+            } else {
+                position += text[position..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        if plain_start < text.len() {
+            parts.push(DescriptionPart::Text(text[plain_start..].to_owned()));
+        }
+        parts
+    }
+
+    fn match_at(&self, text: &str, position: usize) -> Option<(usize, &TermLink)> {
+        self.terms.iter().find_map(|(term, link)| {
+            let candidate = text.get(position..position + term.len())?;
+            if !candidate.eq_ignore_ascii_case(term) {
+                return None;
+            }
+            let mut length = term.len();
+            if text[position + length..].starts_with('s') {
+                length += 1;
+            }
+            text[position + length..]
+                .chars()
+                .next()
+                .is_none_or(|next| !next.is_alphanumeric())
+                .then_some((length, link))
+        })
+    }
+}
+
+/**
+ * The description as it reads without any links
+ */
+pub fn plain_text(parts: &[DescriptionPart]) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            DescriptionPart::Text(text) | DescriptionPart::Term(text, _) => text.as_str(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::classes::{Origin, OriginRules};
+    use crate::model::test_support::test_class;
+
+    #[test]
+    fn test_glossary_from_rules() {
+        let lines = [
+            "Greed is a game about wanting more.",
+            "",
+            "Inspiration:",
+            "Spend it to take a second primary action.",
+            "It comes back every battle.",
+            "",
+            "* Power: Added to every attack roll.",
+            "Combat",
+            "Stats",
+            "Note: this rules document changes often and: has colons",
+        ]
+        .map(String::from);
+
+        let glossary = Glossary::from_rules(&lines);
+
+        assert_eq!(
+            glossary.get_entries(),
+            &[
+                GlossaryEntry::new(
+                    "Inspiration",
+                    "Spend it to take a second primary action. It comes back every battle."
+                ),
+                GlossaryEntry::new("Power", "Added to every attack roll."),
+                GlossaryEntry::new("Note", "this rules document changes often and: has colons"),
+            ]
+        );
+        assert_eq!(
+            glossary.get_definition("power"),
+            Some("Added to every attack roll.")
+        );
+    }
+
+    #[test]
+    fn test_link_terms() {
+        let mut class_cache = ClassCache::new(
+            vec![Origin::without_abilities("Human", OriginRules::new(1))],
+            vec![
+                test_class("Mage", Some(1), None),
+                test_class("Fire Mage", Some(1), None),
+            ],
+            None,
+        );
+        class_cache.set_glossary(Glossary::new(vec![GlossaryEntry::new(
+            "Inspiration",
+            "A second wind",
+        )]));
+        let index = TermIndex::new(&class_cache);
+
+        let parts = index.link("Gain inspirations like a Fire Mage, not a Magenta human.");
+
+        assert_eq!(
+            parts,
+            vec![
+                DescriptionPart::Text("Gain ".to_owned()),
+                DescriptionPart::Term(
+                    "inspirations".to_owned(),
+                    TermLink::Keyword(GlossaryEntry::new("Inspiration", "A second wind"))
+                ),
+                DescriptionPart::Text(" like a ".to_owned()),
+                DescriptionPart::Term(
+                    "Fire Mage".to_owned(),
+                    TermLink::Class("Fire Mage".to_owned())
+                ),
+                DescriptionPart::Text(", not a Magenta ".to_owned()),
+                DescriptionPart::Term("human".to_owned(), TermLink::Origin("Human".to_owned())),
+                DescriptionPart::Text(".".to_owned()),
+            ]
+        );
+        assert_eq!(
+            plain_text(&parts),
+            "Gain inspirations like a Fire Mage, not a Magenta human."
+        );
+    }
+}
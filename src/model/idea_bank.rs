@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * An unfinished class from the rules' Idea Bank, kept as written rather than made playable
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DraftClass {
+    name: String,
+    level: Option<usize>,
+    /// Prerequisites as written after `Req:`
+    requirements: Option<String>,
+    notes: String,
+}
+
+impl DraftClass {
+    pub fn new<N: Into<String>, D: Into<String>>(
+        name: N,
+        level: Option<usize>,
+        requirements: Option<String>,
+        notes: D,
+    ) -> DraftClass {
+        DraftClass {
+            name: name.into(),
+            level,
+            requirements,
+            notes: notes.into(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_level(&self) -> Option<usize> {
+        self.level
+    }
+
+    pub fn get_requirements(&self) -> Option<&str> {
+        self.requirements.as_deref()
+    }
+
+    pub fn get_notes(&self) -> &str {
+        &self.notes
+    }
+
+    /**
+     * Case insensitive search across the name, requirements and notes
+     */
+    pub fn matches_search(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        [
+            self.name.as_str(),
+            self.requirements.as_deref().unwrap_or_default(),
+            self.notes.as_str(),
+        ]
+        .into_iter()
+        .any(|text| text.to_lowercase().contains(&query))
+    }
+}
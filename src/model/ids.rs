@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * Lowercase a name and join its words with dashes, so "Fire Mage", "fire mage" and
+ * "Fire-Mage" all give "fire-mage". Slugging a slug gives it back unchanged.
+ */
+fn slug(name: &str) -> String {
+    name.split(|character: char| !character.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/**
+ * Identifier of an origin, derived from its name in the rules so it survives changes to
+ * case, spacing and punctuation. Saves from before identifiers stored the name, which reads
+ * back as the same identifier. Real renames are followed through the cache's `Renames`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct OriginId(String);
+
+impl OriginId {
+    pub fn from_name(name: &str) -> OriginId {
+        OriginId(slug(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for OriginId {
+    fn from(name: String) -> OriginId {
+        OriginId::from_name(&name)
+    }
+}
+
+impl From<OriginId> for String {
+    fn from(id: OriginId) -> String {
+        id.0
+    }
+}
+
+impl fmt::Display for OriginId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/**
+ * Identifier of a class, derived from its name in the rules the same way as `OriginId`
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct ClassId(String);
+
+impl ClassId {
+    pub fn from_name(name: &str) -> ClassId {
+        ClassId(slug(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for ClassId {
+    fn from(name: String) -> ClassId {
+        ClassId::from_name(&name)
+    }
+}
+
+impl From<ClassId> for String {
+    fn from(id: ClassId) -> String {
+        id.0
+    }
+}
+
+impl fmt::Display for ClassId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/**
+ * The origin or class an ability comes from
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SourceId {
+    Origin(OriginId),
+    Class(ClassId),
+}
+
+impl fmt::Display for SourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceId::Origin(origin) => write!(f, "origin:{origin}"),
+            SourceId::Class(class) => write!(f, "class:{class}"),
+        }
+    }
+}
+
+/**
+ * Identifier of a special action. Each origin or class gives exactly one, so it is known by
+ * where it comes from rather than its own name, which other specials may share. Specials are
+ * the only abilities a save keeps track of, the rest are looked up from the character's
+ * origin and classes whenever they are shown, so they need no identifier of their own.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SpecialId(SourceId);
+
+impl SpecialId {
+    pub fn new(source: SourceId) -> SpecialId {
+        SpecialId(source)
+    }
+
+    pub fn get_source(&self) -> &SourceId {
+        &self.0
+    }
+}
+
+impl fmt::Display for SpecialId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/special", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_ignore_case_and_punctuation() {
+        assert_eq!(ClassId::from_name("Fire Mage").as_str(), "fire-mage");
+        assert_eq!(
+            ClassId::from_name("  fire-MAGE "),
+            ClassId::from_name("Fire Mage")
+        );
+        assert_eq!(
+            OriginId::from_name("Half-Elf's Kin").as_str(),
+            "half-elf-s-kin"
+        );
+        let id = ClassId::from_name("Fire Mage");
+        assert_eq!(ClassId::from_name(id.as_str()), id);
+    }
+
+    #[test]
+    fn test_names_from_older_saves_read_as_ids() {
+        let ids: Vec<ClassId> = ron::from_str("[\"Fighter\", \"Fire Mage\"]").unwrap();
+        assert_eq!(
+            ids,
+            [
+                ClassId::from_name("fighter"),
+                ClassId::from_name("fire-mage")
+            ]
+        );
+        assert_eq!(ron::to_string(&ids).unwrap(), "[\"fighter\",\"fire-mage\"]");
+    }
+}
@@ -1,5 +1,15 @@
 pub mod actions;
 pub mod classes;
+pub mod comparison;
 pub mod game_state;
+pub mod glossary;
+pub mod idea_bank;
+pub mod ids;
+pub mod notes;
+pub mod planner;
+pub mod renames;
 pub mod save;
 pub mod sheets;
+#[cfg(test)]
+pub mod test_support;
+pub mod validation;
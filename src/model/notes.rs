@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+const DEFAULT_PAGE_TITLE: &str = "Notes";
+
+/**
+ * One named page of Markdown notes, session pages remember the battle they were written for
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotePage {
+    title: String,
+    body: String,
+    #[serde(default)]
+    battle: Option<u16>,
+}
+
+impl NotePage {
+    pub fn new<T: Into<String>>(title: T) -> NotePage {
+        NotePage {
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn title_mut(&mut self) -> &mut String {
+        &mut self.title
+    }
+
+    pub fn get_body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn body_mut(&mut self) -> &mut String {
+        &mut self.body
+    }
+
+    pub fn get_battle(&self) -> Option<u16> {
+        self.battle
+    }
+}
+
+/**
+ * A line of a page that matched a search
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteMatch {
+    pub page: usize,
+    pub line: String,
+}
+
+/**
+ * A campaign's notes, kept as a list of pages
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notebook {
+    pages: Vec<NotePage>,
+}
+
+impl Default for Notebook {
+    fn default() -> Self {
+        Notebook {
+            pages: vec![NotePage::new(DEFAULT_PAGE_TITLE)],
+        }
+    }
+}
+
+impl Notebook {
+    /**
+     * A notebook holding notes from before pages existed
+     */
+    pub fn from_legacy(notes: String) -> Notebook {
+        let mut notebook = Notebook::default();
+        notebook.pages[0].body = notes;
+        notebook
+    }
+
+    pub fn get_pages(&self) -> &[NotePage] {
+        &self.pages
+    }
+
+    pub fn get_page(&self, index: usize) -> Option<&NotePage> {
+        self.pages.get(index)
+    }
+
+    pub fn page_mut(&mut self, index: usize) -> Option<&mut NotePage> {
+        self.pages.get_mut(index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.iter().all(|page| page.body.trim().is_empty())
+    }
+
+    /**
+     * Add a page to the end, returning its index
+     */
+    pub fn add_page<T: Into<String>>(&mut self, title: T) -> usize {
+        self.pages.push(NotePage::new(title));
+        self.pages.len() - 1
+    }
+
+    pub fn remove_page(&mut self, index: usize) -> Option<NotePage> {
+        (index < self.pages.len()).then(|| self.pages.remove(index))
+    }
+
+    /**
+     * Index of the session page for a battle, adding one if there isn't one yet
+     */
+    pub fn session_page(&mut self, battle: u16) -> usize {
+        if let Some(index) = self
+            .pages
+            .iter()
+            .position(|page| page.battle == Some(battle))
+        {
+            return index;
+        }
+        self.pages.push(NotePage {
+            battle: Some(battle),
+            ..NotePage::new(format!("Battle {battle}"))
+        });
+        self.pages.len() - 1
+    }
+
+    /**
+     * Every line containing the query, ignoring case, including page titles
+     */
+    pub fn search(&self, query: &str) -> Vec<NoteMatch> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return vec![];
+        }
+        self.pages
+            .iter()
+            .enumerate()
+            .flat_map(|(index, page)| {
+                std::iter::once(page.title.as_str())
+                    .chain(page.body.lines())
+                    .filter(|line| line.to_lowercase().contains(&query))
+                    .map(move |line| NoteMatch {
+                        page: index,
+                        line: line.trim().to_owned(),
+                    })
+            })
+            .collect()
+    }
+
+    /**
+     * All pages as one piece of text for exports, single pages are left untitled
+     */
+    pub fn to_text(&self) -> String {
+        let pages = self
+            .pages
+            .iter()
+            .filter(|page| !page.body.trim().is_empty())
+            .collect::<Vec<_>>();
+        match pages.as_slice() {
+            [] => String::new(),
+            [page] => page.body.clone(),
+            pages => pages
+                .iter()
+                .map(|page| format!("{}\n{}", page.title, page.body.trim_end()))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_page_is_reused() {
+        let mut notebook = Notebook::from_legacy("Old notes".to_owned());
+
+        let first = notebook.session_page(3);
+        let second = notebook.session_page(3);
+
+        assert_eq!(first, second);
+        assert_eq!(notebook.get_pages().len(), 2);
+        assert_eq!(notebook.get_page(first).unwrap().get_title(), "Battle 3");
+        assert_eq!(notebook.get_page(first).unwrap().get_battle(), Some(3));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut notebook = Notebook::from_legacy("Owes the innkeeper\nMet a dragon".to_owned());
+        let page = notebook.add_page("Dragons");
+        *notebook.page_mut(page).unwrap().body_mut() = "Red DRAGON in the hills".to_owned();
+
+        let matches = notebook.search("dragon");
+
+        assert_eq!(
+            matches,
+            vec![
+                NoteMatch {
+                    page: 0,
+                    line: "Met a dragon".to_owned()
+                },
+                NoteMatch {
+                    page: 1,
+                    line: "Dragons".to_owned()
+                },
+                NoteMatch {
+                    page: 1,
+                    line: "Red DRAGON in the hills".to_owned()
+                },
+            ]
+        );
+        assert!(notebook.search("  ").is_empty());
+    }
+}
@@ -0,0 +1,367 @@
+use super::classes::{Class, ClassCache};
+use super::ids::ClassId;
+use super::sheets::Character;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use std::collections::HashSet;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Upper bound on how many future builds are listed, the search grows quickly with each level
+pub const MAX_PLANNED_BUILDS: usize = 200;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    #[error("\"{0}\" is not in the current rules")]
+    UnknownClass(ClassId),
+    #[error("\"{class}\" can never be taken, it needs {}", requires.join(" or "))]
+    UnmetRequirement {
+        class: String,
+        requires: Vec<String>,
+    },
+}
+
+/**
+ * Target build pinned by the player along with the order to take its classes in
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelPlan {
+    target: Vec<ClassId>,
+    steps: Vec<ClassId>,
+}
+
+impl LevelPlan {
+    /**
+     * Pin a target made of extra classes on top of the current character
+     */
+    pub fn new(
+        character: &Character,
+        class_cache: &ClassCache,
+        target: Vec<ClassId>,
+    ) -> Result<LevelPlan, PlanError> {
+        let steps = plan_steps(character, class_cache, &target)?;
+        Ok(LevelPlan { target, steps })
+    }
+
+    pub fn get_steps(&self) -> &[ClassId] {
+        &self.steps
+    }
+
+    /**
+     * Steps paired with whether the character has already taken them
+     */
+    pub fn checklist(&self, character: &Character) -> Vec<(ClassId, bool)> {
+        self.steps
+            .iter()
+            .map(|step| (step.clone(), character.get_classes().contains(step)))
+            .collect()
+    }
+
+    pub fn next_step(&self, character: &Character) -> Option<&ClassId> {
+        self.steps
+            .iter()
+            .find(|step| !character.get_classes().contains(step))
+    }
+
+    /**
+     * Check the plan still works, for when the character or the rules changed
+     */
+    pub fn check(&self, character: &Character, class_cache: &ClassCache) -> Result<(), PlanError> {
+        plan_steps(character, class_cache, &self.target).map(|_| ())
+    }
+}
+
+/**
+ * Future builds found by `reachable_builds`
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReachableBuilds {
+    builds: Vec<Vec<ClassId>>,
+    truncated: bool,
+}
+
+impl ReachableBuilds {
+    pub fn get_builds(&self) -> &[Vec<ClassId>] {
+        &self.builds
+    }
+
+    /**
+     * Whether more builds exist than `MAX_PLANNED_BUILDS` allowed to be listed
+     */
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/**
+ * Every distinct set of classes the character could add over the next levels, including
+ * any bonus classes their origin still gives them. Each build reaches the full number of
+ * levels, once there are more than `MAX_PLANNED_BUILDS` only that many are kept.
+ */
+pub fn reachable_builds(
+    character: &Character,
+    class_cache: &ClassCache,
+    levels_ahead: usize,
+) -> ReachableBuilds {
+    let classes_taken = class_cache
+        .map_to_concrete_classes(character.get_classes())
+        .len();
+    let unused_bonus_classes = character
+        .get_bonus_classes(class_cache)
+        .saturating_sub(classes_taken);
+    let mut frontier: Vec<Vec<ClassId>> = vec![vec![]];
+    let mut truncated = false;
+    for _ in 0..levels_ahead + unused_bonus_classes {
+        let mut seen = HashSet::new();
+        let mut next_frontier = vec![];
+        'frontier: for additions in &frontier {
+            let taken = character
+                .get_classes()
+                .iter()
+                .chain(additions)
+                .cloned()
+                .collect::<Vec<_>>();
+            let current_classes = class_cache.map_to_concrete_classes(&taken);
+            for class in class_cache.get_classes() {
+                if taken.contains(&class.get_id()) || !class.get_class_available(&current_classes) {
+                    continue;
+                }
+                let mut next = additions.clone();
+                next.push(class.get_id());
+                let mut key = next.clone();
+                key.sort();
+                if !seen.insert(key) {
+                    continue;
+                }
+                if next_frontier.len() >= MAX_PLANNED_BUILDS {
+                    truncated = true;
+                    break 'frontier;
+                }
+                next_frontier.push(next);
+            }
+        }
+        frontier = next_frontier;
+    }
+    ReachableBuilds {
+        builds: frontier,
+        truncated,
+    }
+}
+
+/**
+ * Order the target classes so each one's prerequisites are met when it is taken
+ */
+pub fn plan_steps(
+    character: &Character,
+    class_cache: &ClassCache,
+    target: &[ClassId],
+) -> Result<Vec<ClassId>, PlanError> {
+    let mut remaining = target
+        .iter()
+        .filter(|class_id| !character.get_classes().contains(class_id))
+        .map(|class_id| {
+            class_cache
+                .get_class(class_id)
+                .cloned()
+                .ok_or_else(|| PlanError::UnknownClass(class_id.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut current_classes = class_cache.map_to_concrete_classes(character.get_classes());
+    let mut steps = vec![];
+
+    while !remaining.is_empty() {
+        let Some(position) = remaining
+            .iter()
+            .position(|class| class.get_class_available(&current_classes))
+        else {
+            let blocked = &remaining[0];
+            let taken = current_classes
+                .iter()
+                .chain(&remaining)
+                .map(Class::get_name)
+                .collect::<Vec<_>>();
+            return Err(PlanError::UnmetRequirement {
+                class: blocked.get_name(),
+                requires: blocked
+                    .get_parent_classes(class_cache)
+                    .into_iter()
+                    .filter(|parent| !taken.contains(parent))
+                    .collect(),
+            });
+        };
+        let class = remaining.remove(position);
+        steps.push(class.get_id());
+        current_classes.push(class);
+    }
+
+    // Steps taken before pinning stay in the checklist so progress reads naturally
+    Ok(target
+        .iter()
+        .filter(|class_id| character.get_classes().contains(class_id))
+        .cloned()
+        .chain(steps)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::classes::{Origin, OriginRules};
+    use crate::model::ids::OriginId;
+    use crate::model::test_support::test_class;
+
+    fn id(name: &str) -> ClassId {
+        ClassId::from_name(name)
+    }
+
+    fn test_cache() -> ClassCache {
+        ClassCache::new(
+            vec![],
+            vec![
+                test_class("Fighter", Some(1), None),
+                test_class("Wizard", Some(1), None),
+                test_class("Knight", Some(1), Some("Fighter")),
+                test_class("Paladin", Some(1), Some("Knight")),
+                test_class("Lich", Some(1), Some("Necromancer")),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_reachable_builds() {
+        let cache = test_cache();
+        let mut character = Character::default();
+        character.add_class(id("Fighter"));
+
+        let one_level = reachable_builds(&character, &cache, 1);
+        assert!(!one_level.is_truncated());
+        let mut one_level = one_level.get_builds().to_vec();
+        one_level.sort();
+        assert_eq!(one_level, vec![vec![id("Knight")], vec![id("Wizard")]]);
+
+        let two_levels = reachable_builds(&character, &cache, 2)
+            .get_builds()
+            .iter()
+            .map(|build| {
+                let mut build = build.clone();
+                build.sort();
+                build
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(two_levels.len(), 2);
+        assert!(two_levels.contains(&vec![id("Knight"), id("Paladin")]));
+        assert!(two_levels.contains(&vec![id("Knight"), id("Wizard")]));
+    }
+
+    #[test]
+    fn test_reachable_builds_take_unused_bonus_classes() {
+        let cache = ClassCache::new(
+            vec![Origin::without_abilities("Human", OriginRules::new(1))],
+            vec![
+                test_class("Fighter", Some(1), None),
+                test_class("Wizard", Some(1), None),
+            ],
+            None,
+        );
+        let mut character = Character::default();
+        character.replace_origin(Some(OriginId::from_name("Human")));
+
+        let builds = reachable_builds(&character, &cache, 1);
+        assert_eq!(builds.get_builds().len(), 1);
+        assert_eq!(builds.get_builds()[0].len(), 2);
+
+        character.add_class(id("Fighter"));
+        let builds = reachable_builds(&character, &cache, 1);
+        assert_eq!(builds.get_builds(), &[vec![id("Wizard")]]);
+    }
+
+    #[test]
+    fn test_reachable_builds_truncate_whole_levels() {
+        let classes = (0..=MAX_PLANNED_BUILDS)
+            .map(|index| test_class(&format!("Class {index}"), Some(1), None))
+            .collect();
+        let cache = ClassCache::new(vec![], classes, None);
+
+        let builds = reachable_builds(&Character::default(), &cache, 2);
+        assert!(builds.is_truncated());
+        assert_eq!(builds.get_builds().len(), MAX_PLANNED_BUILDS);
+        assert!(builds.get_builds().iter().all(|build| build.len() == 2));
+    }
+
+    #[test]
+    fn test_plan_orders_steps() {
+        let cache = test_cache();
+        let character = Character::default();
+
+        let plan = LevelPlan::new(
+            &character,
+            &cache,
+            vec![id("Paladin"), id("Knight"), id("Fighter")],
+        )
+        .unwrap();
+
+        assert_eq!(
+            plan.get_steps(),
+            &[id("Fighter"), id("Knight"), id("Paladin")]
+        );
+        assert_eq!(plan.next_step(&character), Some(&id("Fighter")));
+    }
+
+    #[test]
+    fn test_plan_checklist() {
+        let cache = test_cache();
+        let mut character = Character::default();
+        character.add_class(id("Fighter"));
+        let plan = LevelPlan::new(&character, &cache, vec![id("Fighter"), id("Knight")]).unwrap();
+
+        assert_eq!(
+            plan.checklist(&character),
+            vec![(id("Fighter"), true), (id("Knight"), false)]
+        );
+    }
+
+    #[test]
+    fn test_impossible_plans() {
+        let cache = test_cache();
+        let character = Character::default();
+
+        assert_eq!(
+            LevelPlan::new(&character, &cache, vec![id("Paladin")]),
+            Err(PlanError::UnmetRequirement {
+                class: "Paladin".to_owned(),
+                requires: vec!["Knight".to_owned()],
+            })
+        );
+        assert_eq!(
+            LevelPlan::new(&character, &cache, vec![id("Lich")]),
+            Err(PlanError::UnmetRequirement {
+                class: "Lich".to_owned(),
+                requires: vec!["Necromancer".to_owned()],
+            })
+        );
+        assert_eq!(
+            LevelPlan::new(&character, &cache, vec![id("Bard")]),
+            Err(PlanError::UnknownClass(id("Bard")))
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use indexmap::IndexMap;
+
+use super::classes::{Class, Origin};
+use super::ids::{ClassId, OriginId};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * An origin or class that earlier rules had and the current ones don't
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Retired<Id> {
+    /// Name as the rules last wrote it
+    name: String,
+    /// What it is called now, when the rules kept its abilities under a new name
+    renamed_to: Option<Id>,
+}
+
+impl<Id> Retired<Id> {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_renamed_to(&self) -> Option<&Id> {
+        self.renamed_to.as_ref()
+    }
+}
+
+/**
+ * Every origin and class dropped from the rules since the cache was first filled, and where
+ * the renamed ones went. Identifiers come from names, so this is what lets saves follow a
+ * rename instead of losing the class.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Renames {
+    origins: BTreeMap<OriginId, Retired<OriginId>>,
+    classes: BTreeMap<ClassId, Retired<ClassId>>,
+}
+
+impl Renames {
+    /**
+     * Renames as of the current rules, given the ones known under the previous rules. An
+     * entry that disappeared counts as renamed when exactly one new entry has the same
+     * abilities and no other disappeared entry matches it too.
+     */
+    pub fn carry(
+        &self,
+        previous: (&IndexMap<OriginId, Origin>, &IndexMap<ClassId, Class>),
+        current: (&IndexMap<OriginId, Origin>, &IndexMap<ClassId, Class>),
+    ) -> Renames {
+        Renames {
+            origins: carry(
+                &self.origins,
+                previous.0,
+                current.0,
+                Origin::get_name,
+                Origin::has_same_abilities,
+            ),
+            classes: carry(
+                &self.classes,
+                previous.1,
+                current.1,
+                Class::get_name,
+                Class::has_same_abilities,
+            ),
+        }
+    }
+
+    pub fn get_origin(&self, origin_id: &OriginId) -> Option<&Retired<OriginId>> {
+        self.origins.get(origin_id)
+    }
+
+    pub fn get_class(&self, class_id: &ClassId) -> Option<&Retired<ClassId>> {
+        self.classes.get(class_id)
+    }
+}
+
+fn carry<Id: Clone + Ord + Hash, T>(
+    retired: &BTreeMap<Id, Retired<Id>>,
+    previous: &IndexMap<Id, T>,
+    current: &IndexMap<Id, T>,
+    name: impl Fn(&T) -> String,
+    same: impl Fn(&T, &T) -> bool,
+) -> BTreeMap<Id, Retired<Id>> {
+    let dropped = previous
+        .iter()
+        .filter(|(id, _)| !current.contains_key(*id))
+        .collect::<Vec<_>>();
+    let added = current
+        .iter()
+        .filter(|(id, _)| !previous.contains_key(*id))
+        .collect::<Vec<_>>();
+    let only_match = |entry: &T, candidates: &[(&Id, &T)]| {
+        let mut matches = candidates
+            .iter()
+            .filter(|(_, candidate)| same(entry, candidate));
+        match (matches.next(), matches.next()) {
+            (Some((id, _)), None) => Some((*id).clone()),
+            _ => None,
+        }
+    };
+
+    // Names that came back are no longer retired
+    let mut carried = retired
+        .iter()
+        .filter(|(id, _)| !current.contains_key(*id))
+        .map(|(id, entry)| (id.clone(), entry.clone()))
+        .collect::<BTreeMap<_, _>>();
+    for (id, entry) in &dropped {
+        let renamed_to = only_match(entry, &added)
+            .filter(|new_id| only_match(&current[new_id], &dropped).as_ref() == Some(*id));
+        carried.insert(
+            (*id).clone(),
+            Retired {
+                name: name(entry),
+                renamed_to,
+            },
+        );
+    }
+
+    // Earlier renames follow their target through any rename since
+    let resolved = carried
+        .keys()
+        .map(|id| {
+            let mut target = carried[id].renamed_to.clone();
+            for _ in 0..carried.len() {
+                match &target {
+                    Some(next) if !current.contains_key(next) => {
+                        target = carried.get(next).and_then(|entry| entry.renamed_to.clone());
+                    }
+                    _ => break,
+                }
+            }
+            (id.clone(), target.filter(|id| current.contains_key(id)))
+        })
+        .collect::<Vec<_>>();
+    for (id, target) in resolved {
+        if let Some(entry) = carried.get_mut(&id) {
+            entry.renamed_to = target;
+        }
+    }
+    carried
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
+    use crate::model::classes::ClassCache;
+    use crate::model::ids::{SourceId, SpecialId};
+    use crate::model::save::Save;
+    use crate::model::validation::validate_save;
+
+    use std::collections::HashSet;
+
+    fn class(name: &str, special: &str) -> Class {
+        Class::new(
+            name,
+            Some(1),
+            vec![],
+            vec![],
+            PrimaryAction::new("Strike", "Hit something"),
+            SecondaryAction::new("Shove", "Push something"),
+            SpecialAction::new(special, "Does a big thing"),
+            None,
+        )
+    }
+
+    fn rules(classes: Vec<Class>, previous: Option<&ClassCache>) -> ClassCache {
+        let mut class_cache = ClassCache::new(vec![], classes, None);
+        if let Some(previous) = previous {
+            class_cache.carry_renames_from(previous);
+        }
+        class_cache
+    }
+
+    #[test]
+    fn test_saves_follow_renames_across_refreshes() {
+        let first = rules(
+            vec![
+                class("Fire Mage", "Fireball"),
+                class("Rogue", "Vanish"),
+                class("Bard", "Encore"),
+            ],
+            None,
+        );
+        let second = rules(
+            vec![class("Pyromancer", "Fireball"), class("Rogue", "Vanish")],
+            Some(&first),
+        );
+        let third = rules(
+            vec![class("Flame Lord", "Fireball"), class("Rogue", "Vanish")],
+            Some(&second),
+        );
+
+        let fire_mage = ClassId::from_name("Fire Mage");
+        let flame_lord = ClassId::from_name("Flame Lord");
+        assert_eq!(third.get_renamed_class(&fire_mage), Some(&flame_lord));
+        assert_eq!(third.get_renamed_class(&ClassId::from_name("Bard")), None);
+
+        let mut save = Save::new("Renamed");
+        save.get_character_mut().add_class(fire_mage.clone());
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Bard"));
+        save.use_special(SpecialId::new(SourceId::Class(fire_mage)));
+        save.follow_renames(&third);
+
+        assert_eq!(
+            save.get_character().get_classes(),
+            [flame_lord.clone(), ClassId::from_name("Bard")]
+        );
+        assert_eq!(
+            save.get_used_specials(),
+            HashSet::from([SpecialId::new(SourceId::Class(flame_lord))])
+        );
+        let issues = validate_save(&save, &third)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(issues, ["Class \"Bard\" is not in the current rules"]);
+
+        let resaved: ClassCache = ron::from_str(&ron::to_string(&third).unwrap()).unwrap();
+        assert_eq!(resaved, third);
+    }
+
+    #[test]
+    fn test_ambiguous_renames_are_not_guessed() {
+        let first = rules(
+            vec![class("Fighter", "Surge"), class("Knight", "Surge")],
+            None,
+        );
+        let merged = rules(vec![class("Warrior", "Surge")], Some(&first));
+        assert_eq!(
+            merged.get_renamed_class(&ClassId::from_name("Fighter")),
+            None
+        );
+        assert_eq!(
+            merged.get_renamed_class(&ClassId::from_name("Knight")),
+            None
+        );
+
+        let split = rules(
+            vec![class("Warrior", "Surge"), class("Soldier", "Surge")],
+            Some(&merged),
+        );
+        assert_eq!(
+            split.get_renamed_class(&ClassId::from_name("Warrior")),
+            None
+        );
+
+        let returned = rules(vec![class("Fighter", "Surge")], Some(&merged));
+        assert_eq!(
+            returned.get_class_name(&ClassId::from_name("Fighter")),
+            "Fighter"
+        );
+        assert_eq!(
+            returned.get_class_name(&ClassId::from_name("warrior")),
+            "Warrior"
+        );
+    }
+}
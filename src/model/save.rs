@@ -1,5 +1,5 @@
 use ron::{from_str, to_string};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashSet;
 use std::ffi::OsString;
 use std::fs::File;
@@ -9,6 +9,10 @@ use std::path::Path;
 use thiserror::Error;
 use tracing::error;
 
+use super::classes::ClassCache;
+use super::ids::{SourceId, SpecialId};
+use super::notes::Notebook;
+use super::planner::LevelPlan;
 use super::sheets::Character;
 
 #[derive(Error, Debug)]
@@ -33,13 +37,46 @@ pub struct Save {
     battle_number: Wrapping<u16>,
     round_number: u8,
     character: Character,
+    #[serde(default)]
+    spent_specials: HashSet<SpecialId>,
+    /// Names of spent specials in saves made before identifiers, see `upgrade_legacy_specials`.
+    /// Kept until the rules have a special by that name.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     used_specials: HashSet<String>,
     #[serde(default)]
     battle_power: i8,
     #[serde(default)]
     battle_defense: i8,
+    /// Only read from saves made before the notebook, see `upgrade_legacy_notes`
+    #[serde(
+        default,
+        skip_serializing,
+        deserialize_with = "deserialize_legacy_notes"
+    )]
+    notes: Option<String>,
+    #[serde(default)]
+    notebook: Notebook,
     #[serde(default)]
-    notes: String,
+    plan: Option<LevelPlan>,
+}
+
+/**
+ * The parts of a save that playing changes, leaving out the notebook, plan and name
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayState {
+    battle_number: Wrapping<u16>,
+    round_number: u8,
+    character: Character,
+    spent_specials: HashSet<SpecialId>,
+    battle_power: i8,
+    battle_defense: i8,
+}
+
+fn deserialize_legacy_notes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    String::deserialize(deserializer).map(Some)
 }
 
 impl Save {
@@ -54,7 +91,20 @@ impl Save {
         let mut file = File::open(path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
-        Ok(from_str(&contents)?)
+        let mut save: Save = from_str(&contents)?;
+        save.upgrade_legacy_notes();
+        Ok(save)
+    }
+
+    /**
+     * Move notes from before the notebook into its first page
+     */
+    fn upgrade_legacy_notes(&mut self) {
+        if let Some(notes) = self.notes.take() {
+            if self.notebook.is_empty() {
+                self.notebook = Notebook::from_legacy(notes);
+            }
+        }
     }
 
     pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), SaveToFileError> {
@@ -112,20 +162,105 @@ impl Save {
         self.battle_defense = defense;
     }
 
-    pub fn use_special<N: Into<String>>(&mut self, name: N) {
-        self.used_specials.insert(name.into());
+    /**
+     * Swap the names of specials spent in saves from before identifiers for the specials of
+     * the character's origin and classes going by those names. Names matching none are kept
+     * for when the rules have them, and nothing happens before any rules are loaded.
+     */
+    pub fn upgrade_legacy_specials(&mut self, class_cache: &ClassCache) {
+        if self.used_specials.is_empty() || class_cache.is_empty() {
+            return;
+        }
+        let mut resolved = HashSet::new();
+        for source in self.character.get_ability_sources(class_cache) {
+            let special = source.get_special_action();
+            if self.used_specials.contains(&special.get_name()) {
+                self.spent_specials.extend(special.get_id());
+                resolved.insert(special.get_name());
+            }
+        }
+        self.used_specials.retain(|name| !resolved.contains(name));
+    }
+
+    /**
+     * Move the character, and the specials they spent, over to the new names of anything the
+     * rules renamed
+     */
+    pub fn follow_renames(&mut self, class_cache: &ClassCache) {
+        self.character.follow_renames(class_cache);
+        self.spent_specials = self
+            .spent_specials
+            .drain()
+            .map(|special| {
+                let renamed = match special.get_source() {
+                    SourceId::Origin(origin_id) => class_cache
+                        .get_renamed_origin(origin_id)
+                        .cloned()
+                        .map(SourceId::Origin),
+                    SourceId::Class(class_id) => class_cache
+                        .get_renamed_class(class_id)
+                        .cloned()
+                        .map(SourceId::Class),
+                };
+                renamed.map_or(special, SpecialId::new)
+            })
+            .collect();
+    }
+
+    pub fn get_play_state(&self) -> PlayState {
+        PlayState {
+            battle_number: self.battle_number,
+            round_number: self.round_number,
+            character: self.character.clone(),
+            spent_specials: self.spent_specials.clone(),
+            battle_power: self.battle_power,
+            battle_defense: self.battle_defense,
+        }
+    }
+
+    /**
+     * Go back to an earlier play state, keeping any notes and plan made since
+     */
+    pub fn restore_play_state(&mut self, play_state: PlayState) {
+        self.battle_number = play_state.battle_number;
+        self.round_number = play_state.round_number;
+        self.character = play_state.character;
+        self.spent_specials = play_state.spent_specials;
+        self.battle_power = play_state.battle_power;
+        self.battle_defense = play_state.battle_defense;
+    }
+
+    pub fn use_special(&mut self, special_id: SpecialId) {
+        self.spent_specials.insert(special_id);
+    }
+
+    pub fn forget_special(&mut self, special_id: &SpecialId) {
+        self.spent_specials.remove(special_id);
     }
 
     pub fn refresh_specials(&mut self) {
+        self.spent_specials.clear();
         self.used_specials.clear();
     }
 
-    pub fn get_used_specials(&self) -> HashSet<String> {
-        self.used_specials.clone()
+    pub fn get_used_specials(&self) -> HashSet<SpecialId> {
+        self.spent_specials.clone()
+    }
+
+    pub fn get_notebook(&self) -> &Notebook {
+        &self.notebook
+    }
+
+    pub fn get_notebook_mut(&mut self) -> &mut Notebook {
+        &mut self.notebook
+    }
+
+    pub fn get_plan(&self) -> Option<&LevelPlan> {
+        self.plan.as_ref()
     }
 
-    pub fn get_notes_mut(&mut self) -> &mut String {
-        &mut self.notes
+    pub fn set_plan(&mut self, plan: Option<LevelPlan>) {
+        self.plan = plan;
     }
 }
 
@@ -136,10 +271,13 @@ impl Default for Save {
             battle_number: Wrapping(1),
             round_number: 1,
             character: Character::default(),
+            spent_specials: HashSet::default(),
             used_specials: HashSet::default(),
             battle_power: 0,
             battle_defense: 0,
-            notes: String::default(),
+            notes: None,
+            notebook: Notebook::default(),
+            plan: None,
         }
     }
 }
@@ -177,6 +315,7 @@ impl SaveWithPath {
         out
     }
 
+    #[allow(clippy::ref_option, reason = "callers clone the stored path")]
     pub fn get_path(&self) -> &Option<OsString> {
         &self.path
     }
@@ -206,3 +345,134 @@ impl SaveWithPath {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
+    use crate::model::classes::{Class, ClassPassive, ClassUtility};
+    use crate::model::ids::ClassId;
+
+    #[test]
+    fn test_legacy_notes_move_to_first_page() {
+        let mut save = Save::new("Notes Test");
+        let legacy = to_string(&save)
+            .unwrap()
+            .replace("notebook:", "notes: \"Owes the innkeeper\", notebook:");
+        let path = std::env::temp_dir().join("greed_console_legacy_notes.ron");
+        std::fs::write(&path, legacy).unwrap();
+
+        let loaded = Save::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        save.get_notebook_mut()
+            .page_mut(0)
+            .unwrap()
+            .body_mut()
+            .push_str("Owes the innkeeper");
+        assert_eq!(loaded, save);
+        assert!(!to_string(&loaded).unwrap().contains("notes:"));
+    }
+
+    #[test]
+    fn test_restoring_play_state_keeps_notes_and_plan() {
+        let mut save = Save::new("Undo Test");
+        let play_state = save.get_play_state();
+        let battle = save.get_battle();
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Fighter"));
+        save.inc_battle();
+        save.get_notebook_mut()
+            .page_mut(0)
+            .unwrap()
+            .body_mut()
+            .push_str("Met the baron");
+        save.set_plan(Some(LevelPlan::default()));
+
+        save.restore_play_state(play_state);
+
+        assert!(save.get_character().get_classes().is_empty());
+        assert_eq!(save.get_battle(), battle);
+        assert!(save
+            .get_notebook()
+            .get_page(0)
+            .unwrap()
+            .get_body()
+            .contains("Met the baron"));
+        assert_eq!(save.get_plan(), Some(&LevelPlan::default()));
+    }
+
+    #[test]
+    fn test_legacy_specials_resolve_against_the_rules() {
+        let class = |name: &str, special: &str| {
+            Class::new(
+                name,
+                Some(1),
+                vec![],
+                vec![],
+                PrimaryAction::new("", ""),
+                SecondaryAction::new("", ""),
+                SpecialAction::new(special, ""),
+                None,
+            )
+        };
+        let class_cache = ClassCache::new(
+            vec![],
+            vec![
+                class("Fighter", "Surge"),
+                class("Rogue", "Surge"),
+                class("Wizard", "Blink"),
+            ],
+            None,
+        );
+        let legacy = "(campaign_name: \"Old\", battle_number: 1, round_number: 1, \
+            character: (origin: None, classes: [\"Fighter\", \"Wizard\"]), \
+            used_specials: [\"Surge\", \"Gone\"])";
+        let mut save: Save = from_str(legacy).unwrap();
+        let unmigrated = save.clone();
+        save.upgrade_legacy_specials(&ClassCache::default());
+        assert_eq!(save, unmigrated);
+
+        save.upgrade_legacy_specials(&class_cache);
+
+        let fighter = SpecialId::new(SourceId::Class(ClassId::from_name("Fighter")));
+        assert_eq!(save.get_used_specials(), HashSet::from([fighter.clone()]));
+        assert_eq!(save.used_specials, HashSet::from(["Gone".to_owned()]));
+
+        let resaved: Save = from_str(&to_string(&save).unwrap()).unwrap();
+        assert_eq!(resaved, save);
+
+        save.refresh_specials();
+        assert!(!to_string(&save).unwrap().contains("used_specials"));
+    }
+
+    #[test]
+    fn test_saves_only_refer_to_abilities_by_source() {
+        let class = Class::new(
+            "Fighter",
+            Some(1),
+            vec![ClassUtility::new("Climbing Gear", "")],
+            vec![ClassPassive::new("Iron Hide", "")],
+            PrimaryAction::new("Hammer Blow", ""),
+            SecondaryAction::new("Shield Bash", ""),
+            SpecialAction::new("Last Stand", ""),
+            None,
+        );
+        let mut save = Save::new("Ability Test");
+        save.get_character_mut().add_class(class.get_id());
+        save.use_special(SpecialId::new(SourceId::Class(class.get_id())));
+        save.set_plan(Some(LevelPlan::default()));
+
+        let saved = to_string(&save).unwrap();
+        for name in [
+            "Climbing Gear",
+            "Iron Hide",
+            "Hammer Blow",
+            "Shield Bash",
+            "Last Stand",
+        ] {
+            assert!(!saved.contains(name), "{name} is saved by name");
+        }
+    }
+}
@@ -2,7 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use super::{
     actions::{PrimaryAction, SecondaryAction, SpecialAction},
-    classes::{Class, ClassCache, ClassPassive, ClassUtility},
+    classes::{Class, ClassCache, ClassPassive, ClassUtility, Origin},
+    ids::{ClassId, OriginId},
 };
 
 /*
@@ -25,8 +26,8 @@ use super::{
 
 #[derive(Serialize, Deserialize, Default, Debug, Eq, PartialEq, Clone)]
 pub struct Character {
-    origin: Option<String>,
-    classes: Vec<String>,
+    origin: Option<OriginId>,
+    classes: Vec<ClassId>,
 }
 
 type ActionOutput = (
@@ -38,80 +39,35 @@ type ActionOutput = (
 );
 
 impl Character {
-    pub fn get_all_actions(&self, class_cache: &ClassCache) -> ActionOutput {
-        let character_origin = self
-            .get_origin()
-            .and_then(|origin_name| class_cache.get_origin(origin_name.as_str()));
-
-        let character_classes = class_cache.map_to_concrete_classes(self.get_classes());
-
-        let utilities = character_origin
-            .map_or_else(Vec::new, |origin| {
-                if origin.get_name() == "Human" {
-                    vec![]
-                } else {
-                    origin.get_utilities().clone()
-                }
-            })
+    /**
+     * Concrete origin and classes that grant this character abilities, origin first
+     */
+    pub fn get_ability_sources(&self, class_cache: &ClassCache) -> Vec<Class> {
+        self.resolve_origin(class_cache)
+            .and_then(Origin::as_ability_source)
             .into_iter()
-            .chain(
-                character_classes
-                    .iter()
-                    .flat_map(|class| class.get_utilities().clone()),
-            )
-            .collect();
+            .chain(class_cache.map_to_concrete_classes(self.get_classes()))
+            .collect()
+    }
 
-        let passives = character_origin
-            .map_or_else(Vec::new, |origin| {
-                if origin.get_name() == "Human" {
-                    vec![]
-                } else {
-                    origin.get_passives().clone()
-                }
-            })
-            .into_iter()
-            .chain(
-                character_classes
-                    .iter()
-                    .flat_map(|class| class.get_passives().clone()),
-            )
-            .collect();
+    pub fn get_all_actions(&self, class_cache: &ClassCache) -> ActionOutput {
+        let sources = self.get_ability_sources(class_cache);
 
-        let primary_actions = character_origin
-            .map_or_else(Vec::new, |origin| {
-                if origin.get_name() == "Human" {
-                    vec![]
-                } else {
-                    vec![origin.get_primary_action()]
-                }
-            })
-            .into_iter()
-            .chain(character_classes.iter().map(Class::get_primary_action))
+        let utilities = sources
+            .iter()
+            .flat_map(|class| class.get_utilities().clone())
             .collect();
 
-        let secondary_actions = character_origin
-            .map_or_else(Vec::new, |origin| {
-                if origin.get_name() == "Human" {
-                    vec![]
-                } else {
-                    vec![origin.get_secondary_action()]
-                }
-            })
-            .into_iter()
-            .chain(character_classes.iter().map(Class::get_secondary_action))
+        let passives = sources
+            .iter()
+            .flat_map(|class| class.get_passives().clone())
             .collect();
 
-        let special_actions = character_origin
-            .map_or_else(Vec::new, |origin| {
-                if origin.get_name() == "Human" {
-                    vec![]
-                } else {
-                    vec![origin.get_special_action()]
-                }
-            })
-            .into_iter()
-            .chain(character_classes.iter().map(Class::get_special_action))
-            .collect();
+        let primary_actions = sources.iter().map(Class::get_primary_action).collect();
+
+        let secondary_actions = sources.iter().map(Class::get_secondary_action).collect();
+
+        let special_actions = sources.iter().map(Class::get_special_action).collect();
 
         (
             utilities,
@@ -122,34 +78,98 @@ impl Character {
         )
     }
 
-    pub fn get_origin(&self) -> Option<String> {
-        self.origin.clone()
+    /**
+     * Level of the character, not counting classes their origin gives for free
+     */
+    pub fn get_level(&self, class_cache: &ClassCache) -> usize {
+        let class_count = class_cache
+            .map_to_concrete_classes(self.get_classes())
+            .len();
+        class_count.saturating_sub(self.get_bonus_classes(class_cache))
+    }
+
+    /**
+     * Classes the character's origin lets them take without gaining a level
+     */
+    pub fn get_bonus_classes(&self, class_cache: &ClassCache) -> usize {
+        self.resolve_origin(class_cache)
+            .map_or(0, |origin| origin.get_rules().get_bonus_classes())
     }
 
-    pub fn replace_origin(&mut self, new_origin: Option<String>) {
+    /**
+     * The character's origin as the rules describe it, None if they have none or the
+     * rules no longer have it
+     */
+    pub fn resolve_origin<'a>(&self, class_cache: &'a ClassCache) -> Option<&'a Origin> {
+        self.origin
+            .as_ref()
+            .and_then(|origin_id| class_cache.get_origin(origin_id))
+    }
+
+    pub fn set_origin(&mut self, origin: Option<&Origin>) {
+        self.origin = origin.map(Origin::get_id);
+    }
+
+    pub fn get_origin(&self) -> Option<&OriginId> {
+        self.origin.as_ref()
+    }
+
+    /**
+     * Set the origin by identifier, for imports and repairs where it may not be in the rules
+     */
+    pub fn replace_origin(&mut self, new_origin: Option<OriginId>) {
         self.origin = new_origin;
     }
 
-    pub fn get_classes(&self) -> &Vec<String> {
+    pub fn get_classes(&self) -> &[ClassId] {
         &self.classes
     }
 
-    pub fn add_class<N: Into<String>>(&mut self, class_name: N) {
-        self.classes.push(class_name.into());
+    pub fn add_class(&mut self, class_id: ClassId) {
+        self.classes.push(class_id);
+    }
+
+    pub fn remove_class(&mut self, class_id: &ClassId) {
+        if let Some(class_position) = self.classes.iter().position(|class| class == class_id) {
+            self.classes.remove(class_position);
+        }
     }
 
-    pub fn remove_class<N: Into<String>>(&mut self, class_name: N) {
-        let class_name_string = class_name.into();
+    /**
+     * Swap a class for another in place, keeping the order classes were taken in
+     */
+    pub fn replace_class(&mut self, old_id: &ClassId, new_id: ClassId) {
+        if let Some(class) = self.classes.iter_mut().find(|class| *class == old_id) {
+            *class = new_id;
+        }
+    }
 
-        if let Some(class_position) = self
-            .classes
-            .iter()
-            .position(|class| class_name_string == class.clone())
+    /**
+     * Swap an origin or classes the rules have since renamed for what they are called now
+     */
+    pub fn follow_renames(&mut self, class_cache: &ClassCache) {
+        if let Some(renamed) = self
+            .origin
+            .as_ref()
+            .and_then(|origin_id| class_cache.get_renamed_origin(origin_id))
         {
-            self.classes.remove(class_position);
+            self.origin = Some(renamed.clone());
+        }
+        for class in &mut self.classes {
+            if let Some(renamed) = class_cache.get_renamed_class(class) {
+                *class = renamed.clone();
+            }
         }
     }
 
+    /**
+     * Keep only the first of any repeated class
+     */
+    pub fn dedup_classes(&mut self) {
+        let mut seen = std::collections::HashSet::new();
+        self.classes.retain(|class| seen.insert(class.clone()));
+    }
+
     pub fn clear(&mut self) {
         self.origin = None;
         self.classes.clear();
@@ -0,0 +1,51 @@
+use crate::model::actions::{PrimaryAction, SecondaryAction, SpecialAction};
+use crate::model::classes::{Class, ClassPassive, ClassRequirement, SuperClassRequirement};
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/**
+ * Class for tests, with actions named after it (e.g. "Fighter Special") and optionally
+ * requiring another class
+ */
+pub fn test_class(name: &str, level: Option<usize>, prerequisite: Option<&str>) -> Class {
+    test_class_with_passives(name, level, prerequisite, vec![])
+}
+
+/**
+ * Like `test_class`, with the given passives
+ */
+pub fn test_class_with_passives(
+    name: &str,
+    level: Option<usize>,
+    prerequisite: Option<&str>,
+    passives: Vec<ClassPassive>,
+) -> Class {
+    Class::new(
+        name,
+        level,
+        vec![],
+        passives,
+        PrimaryAction::new(format!("{name} Primary"), "Does a thing"),
+        SecondaryAction::new(format!("{name} Secondary"), "Does another thing"),
+        SpecialAction::new(format!("{name} Special"), "Does a big thing"),
+        prerequisite.map(|class_name| {
+            Box::new(SuperClassRequirement::new(class_name)) as Box<dyn ClassRequirement>
+        }),
+    )
+}
@@ -0,0 +1,470 @@
+use super::classes::{Class, ClassCache, Origin};
+use super::ids::{ClassId, OriginId, SourceId, SpecialId};
+use super::save::Save;
+
+use std::collections::HashSet;
+use std::fmt;
+
+/*
+ * A console and digital character sheet for campaigns under the greed ruleset.
+ * Copyright (C) 2023 Claire Moore
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Lowest normalized edit similarity that still counts as a likely rename
+const SUGGESTION_THRESHOLD: f64 = 0.5;
+const MAX_SUGGESTIONS: usize = 3;
+
+/**
+ * Something in a save that does not line up with the current rules. Names are as the rules
+ * write them, or last wrote them for anything they no longer have.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveIssue {
+    UnknownOrigin {
+        origin: OriginId,
+        name: String,
+        suggestions: Vec<String>,
+    },
+    UnknownClass {
+        class: ClassId,
+        name: String,
+        suggestions: Vec<String>,
+    },
+    UnmetPrerequisites {
+        class: ClassId,
+        name: String,
+    },
+    DuplicateClass {
+        class: ClassId,
+        name: String,
+        count: usize,
+    },
+    StaleSpecial {
+        special: SpecialId,
+        source: String,
+    },
+    IllegalLevel {
+        class: ClassId,
+        name: String,
+        level: usize,
+        classes_taken: usize,
+    },
+}
+
+impl fmt::Display for SaveIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveIssue::UnknownOrigin { name, .. } => {
+                write!(f, "Origin \"{name}\" is not in the current rules")
+            }
+            SaveIssue::UnknownClass { name, .. } => {
+                write!(f, "Class \"{name}\" is not in the current rules")
+            }
+            SaveIssue::UnmetPrerequisites { name, .. } => {
+                write!(f, "Class \"{name}\" is missing its prerequisites")
+            }
+            SaveIssue::DuplicateClass { name, count, .. } => {
+                write!(f, "Class \"{name}\" was taken {count} times")
+            }
+            SaveIssue::StaleSpecial { source, .. } => {
+                write!(
+                    f,
+                    "Used special of \"{source}\" no longer belongs to the character"
+                )
+            }
+            SaveIssue::IllegalLevel {
+                name,
+                level,
+                classes_taken,
+                ..
+            } => write!(
+                f,
+                "Class \"{name}\" is level {level} but the character has only taken {classes_taken} classes"
+            ),
+        }
+    }
+}
+
+impl SaveIssue {
+    /**
+     * Fixes that would resolve the issue, best guess first
+     */
+    pub fn repairs(&self) -> Vec<SaveRepair> {
+        match self {
+            SaveIssue::UnknownOrigin { suggestions, .. } => suggestions
+                .iter()
+                .map(|suggestion| SaveRepair::ReplaceOrigin(Some(suggestion.clone())))
+                .chain(std::iter::once(SaveRepair::ReplaceOrigin(None)))
+                .collect(),
+            SaveIssue::UnknownClass {
+                class,
+                name,
+                suggestions,
+            } => suggestions
+                .iter()
+                .map(|suggestion| SaveRepair::ReplaceClass {
+                    from: class.clone(),
+                    to: suggestion.clone(),
+                })
+                .chain(std::iter::once(SaveRepair::RemoveClass {
+                    class: class.clone(),
+                    name: name.clone(),
+                }))
+                .collect(),
+            SaveIssue::UnmetPrerequisites { class, name }
+            | SaveIssue::IllegalLevel { class, name, .. } => {
+                vec![SaveRepair::RemoveClass {
+                    class: class.clone(),
+                    name: name.clone(),
+                }]
+            }
+            SaveIssue::DuplicateClass { .. } => vec![SaveRepair::RemoveDuplicateClasses],
+            SaveIssue::StaleSpecial { special, .. } => {
+                vec![SaveRepair::ForgetSpecial(special.clone())]
+            }
+        }
+    }
+}
+
+/**
+ * A fix for a save, replacements are named as the rules write them
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveRepair {
+    ReplaceOrigin(Option<String>),
+    ReplaceClass { from: ClassId, to: String },
+    RemoveClass { class: ClassId, name: String },
+    RemoveDuplicateClasses,
+    ForgetSpecial(SpecialId),
+}
+
+impl fmt::Display for SaveRepair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveRepair::ReplaceOrigin(Some(origin)) => write!(f, "Change origin to \"{origin}\""),
+            SaveRepair::ReplaceOrigin(None) => write!(f, "Clear origin"),
+            SaveRepair::ReplaceClass { to, .. } => write!(f, "Replace with \"{to}\""),
+            SaveRepair::RemoveClass { name, .. } => write!(f, "Remove \"{name}\""),
+            SaveRepair::RemoveDuplicateClasses => write!(f, "Remove duplicates"),
+            SaveRepair::ForgetSpecial(_) => write!(f, "Forget special"),
+        }
+    }
+}
+
+impl SaveRepair {
+    pub fn apply(&self, save: &mut Save) {
+        match self {
+            SaveRepair::ReplaceOrigin(origin) => {
+                save.get_character_mut()
+                    .replace_origin(origin.as_deref().map(OriginId::from_name));
+            }
+            SaveRepair::ReplaceClass { from, to } => {
+                save.get_character_mut()
+                    .replace_class(from, ClassId::from_name(to));
+            }
+            SaveRepair::RemoveClass { class, .. } => {
+                save.get_character_mut().remove_class(class);
+            }
+            SaveRepair::RemoveDuplicateClasses => save.get_character_mut().dedup_classes(),
+            SaveRepair::ForgetSpecial(special) => save.forget_special(special),
+        }
+    }
+}
+
+/**
+ * Report every problem with a save when read against the given rules
+ */
+pub fn validate_save(save: &Save, class_cache: &ClassCache) -> Vec<SaveIssue> {
+    let character = save.get_character();
+    let mut issues = vec![];
+
+    if let Some(origin) = character.get_origin() {
+        if class_cache.get_origin(origin).is_none() {
+            let origin_names = class_cache
+                .get_origins()
+                .into_iter()
+                .map(Origin::get_name)
+                .collect::<Vec<_>>();
+            let name = class_cache.get_origin_name(origin);
+            issues.push(SaveIssue::UnknownOrigin {
+                suggestions: suggest_names(&name, &origin_names),
+                origin: origin.clone(),
+                name,
+            });
+        }
+    }
+
+    let taken = character.get_classes().iter().collect::<HashSet<_>>();
+    let untaken_names = class_cache
+        .get_classes()
+        .into_iter()
+        .filter(|class| !taken.contains(&class.get_id()))
+        .map(Class::get_name)
+        .collect::<Vec<_>>();
+    let mut seen = HashSet::new();
+    for class_id in character.get_classes() {
+        if !seen.insert(class_id) {
+            continue;
+        }
+        let count = character
+            .get_classes()
+            .iter()
+            .filter(|other| *other == class_id)
+            .count();
+        if count > 1 {
+            issues.push(SaveIssue::DuplicateClass {
+                class: class_id.clone(),
+                name: class_cache.get_class_name(class_id),
+                count,
+            });
+        }
+        if class_cache.get_class(class_id).is_none() {
+            let name = class_cache.get_class_name(class_id);
+            issues.push(SaveIssue::UnknownClass {
+                class: class_id.clone(),
+                suggestions: suggest_names(&name, &untaken_names),
+                name,
+            });
+        }
+    }
+
+    let concrete_classes = class_cache.map_to_concrete_classes(character.get_classes());
+    // Bonus classes from an origin still count towards the level a class can be taken at
+    let classes_taken = concrete_classes.len();
+    let mut checked = HashSet::new();
+    for class in &concrete_classes {
+        if !checked.insert(class.get_id()) {
+            continue;
+        }
+        let others = concrete_classes
+            .iter()
+            .filter(|other| other.get_id() != class.get_id())
+            .cloned()
+            .collect::<Vec<_>>();
+        if !class.get_class_available(&others) {
+            issues.push(SaveIssue::UnmetPrerequisites {
+                class: class.get_id(),
+                name: class.get_name(),
+            });
+        }
+        if let Some(level) = class.get_level().filter(|level| *level > classes_taken) {
+            issues.push(SaveIssue::IllegalLevel {
+                class: class.get_id(),
+                name: class.get_name(),
+                level,
+                classes_taken,
+            });
+        }
+    }
+
+    let special_ids = character
+        .get_ability_sources(class_cache)
+        .iter()
+        .filter_map(|source| source.get_special_action().get_id())
+        .collect::<HashSet<_>>();
+    let mut stale_specials = save
+        .get_used_specials()
+        .into_iter()
+        .filter(|special| !special_ids.contains(special))
+        .collect::<Vec<_>>();
+    stale_specials.sort();
+    issues.extend(stale_specials.into_iter().map(|special| {
+        let source = match special.get_source() {
+            SourceId::Origin(origin_id) => class_cache.get_origin_name(origin_id),
+            SourceId::Class(class_id) => class_cache.get_class_name(class_id),
+        };
+        SaveIssue::StaleSpecial { special, source }
+    }));
+
+    issues
+}
+
+/**
+ * Candidates that look like a rename of the given name, most similar first
+ */
+pub fn suggest_names(name: &str, candidates: &[String]) -> Vec<String> {
+    let name = name.to_lowercase();
+    let mut scored = candidates
+        .iter()
+        .map(|candidate| {
+            (
+                strsim::normalized_damerau_levenshtein(&name, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+        .collect::<Vec<_>>();
+    scored.sort_by(|(left, _), (right, _)| right.total_cmp(left));
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::classes::OriginRules;
+    use crate::model::test_support::test_class;
+
+    fn class_special(name: &str) -> SpecialId {
+        SpecialId::new(SourceId::Class(ClassId::from_name(name)))
+    }
+
+    fn test_cache() -> ClassCache {
+        ClassCache::new(
+            vec![Origin::without_abilities("Elf", OriginRules::default())],
+            vec![
+                test_class("Fighter", Some(1), None),
+                test_class("Knight", Some(2), Some("Fighter")),
+                test_class("Wizard", Some(1), None),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_valid_save_has_no_issues() {
+        let mut save = Save::new("Valid");
+        save.get_character_mut()
+            .replace_origin(Some(OriginId::from_name("Elf")));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Fighter"));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Knight"));
+        save.use_special(class_special("Knight"));
+
+        assert!(validate_save(&save, &test_cache()).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_issue() {
+        let mut save = Save::new("Broken");
+        save.get_character_mut()
+            .replace_origin(Some(OriginId::from_name("Elfe")));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Knight"));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Wizzard"));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Knight"));
+        save.use_special(class_special("Old"));
+
+        assert_eq!(
+            validate_save(&save, &test_cache()),
+            vec![
+                SaveIssue::UnknownOrigin {
+                    origin: OriginId::from_name("Elfe"),
+                    name: "elfe".to_owned(),
+                    suggestions: vec!["Elf".to_owned()],
+                },
+                SaveIssue::DuplicateClass {
+                    class: ClassId::from_name("Knight"),
+                    name: "Knight".to_owned(),
+                    count: 2,
+                },
+                SaveIssue::UnknownClass {
+                    class: ClassId::from_name("Wizzard"),
+                    name: "wizzard".to_owned(),
+                    suggestions: vec!["Wizard".to_owned()],
+                },
+                SaveIssue::UnmetPrerequisites {
+                    class: ClassId::from_name("Knight"),
+                    name: "Knight".to_owned(),
+                },
+                SaveIssue::StaleSpecial {
+                    special: class_special("Old"),
+                    source: "old".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_illegal_level() {
+        let cache = ClassCache::new(vec![], vec![test_class("Knight", Some(2), None)], None);
+        let mut save = Save::new("Too Fast");
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Knight"));
+
+        assert_eq!(
+            validate_save(&save, &cache),
+            vec![SaveIssue::IllegalLevel {
+                class: ClassId::from_name("Knight"),
+                name: "Knight".to_owned(),
+                level: 2,
+                classes_taken: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_bonus_classes_count_towards_class_levels() {
+        let cache = ClassCache::new(
+            vec![Origin::without_abilities("Human", OriginRules::new(1))],
+            vec![
+                test_class("Fighter", Some(1), None),
+                test_class("Knight", Some(2), Some("Fighter")),
+            ],
+            None,
+        );
+        let mut save = Save::new("Human");
+        save.get_character_mut()
+            .replace_origin(Some(OriginId::from_name("Human")));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Fighter"));
+        assert!(validate_save(&save, &cache).is_empty());
+
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Knight"));
+        assert!(validate_save(&save, &cache).is_empty());
+    }
+
+    #[test]
+    fn test_repairs_resolve_issues() {
+        let cache = test_cache();
+        let mut save = Save::new("Repair");
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Figter"));
+        save.get_character_mut()
+            .add_class(ClassId::from_name("Figter"));
+        save.use_special(class_special("Old"));
+
+        while let Some(issue) = validate_save(&save, &cache).first() {
+            issue.repairs()[0].apply(&mut save);
+        }
+
+        assert_eq!(
+            save.get_character().get_classes(),
+            [ClassId::from_name("Fighter")]
+        );
+        assert!(save.get_used_specials().is_empty());
+    }
+
+    #[test]
+    fn test_suggest_names() {
+        let candidates = vec![
+            "Fighter".to_owned(),
+            "Fire Mage".to_owned(),
+            "Rogue".to_owned(),
+        ];
+
+        assert_eq!(suggest_names("fightr", &candidates), vec!["Fighter"]);
+        assert!(suggest_names("Necromancer", &candidates).is_empty());
+    }
+}
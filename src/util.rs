@@ -1,13 +1,99 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
-/// Dumb conversion from Roman String to optional usize
+/// Largest number written with standard Roman numerals
+pub const MAX_ROMAN: usize = 3999;
+
+static ROMAN_NUMERALS: [(usize, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// Parse a Roman numeral, only accepting its standard spelling so "IIII" or "VX" are `None`
 pub fn from_roman(roman_text: &str) -> Option<usize> {
-    let mut roman_map: HashMap<&str, usize> = HashMap::new();
-    roman_map.insert("I", 1);
-    roman_map.insert("II", 2);
-    roman_map.insert("III", 3);
-    roman_map.insert("IV", 4);
-    roman_map.insert("V", 5);
-
-    roman_map.get(roman_text).copied()
+    let roman_text = roman_text.trim();
+    let mut rest = roman_text;
+    let mut number = 0;
+    for (value, numeral) in ROMAN_NUMERALS {
+        while let Some(remaining) = rest.strip_prefix(numeral) {
+            number += value;
+            rest = remaining;
+        }
+    }
+    (rest.is_empty() && to_roman(number).as_deref() == Some(roman_text)).then_some(number)
+}
+
+/// Write a number from 1 to `MAX_ROMAN` as a Roman numeral
+pub fn to_roman(mut number: usize) -> Option<String> {
+    if !(1..=MAX_ROMAN).contains(&number) {
+        return None;
+    }
+    let mut roman = String::new();
+    for (value, numeral) in ROMAN_NUMERALS {
+        while number >= value {
+            roman.push_str(numeral);
+            number -= value;
+        }
+    }
+    Some(roman)
+}
+
+/**
+ * How class levels are written, the rules use Roman numerals
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelNumerals {
+    Roman,
+    #[default]
+    Arabic,
+}
+
+impl LevelNumerals {
+    /**
+     * Write a level, falling back to Arabic numerals where Roman ones can't be used
+     */
+    pub fn format(self, level: usize) -> String {
+        match self {
+            LevelNumerals::Roman => to_roman(level).unwrap_or_else(|| level.to_string()),
+            LevelNumerals::Arabic => level.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roman_round_trip() {
+        for number in 1..=MAX_ROMAN {
+            let roman = to_roman(number).unwrap();
+            assert_eq!(from_roman(&roman), Some(number), "{roman}");
+        }
+        assert_eq!(from_roman("VI"), Some(6));
+        assert_eq!(from_roman(" XIV "), Some(14));
+        assert_eq!(to_roman(1994).as_deref(), Some("MCMXCIV"));
+    }
+
+    #[test]
+    fn test_invalid_roman() {
+        for invalid in ["", "IIII", "VX", "IC", "VV", "iv", "XIIV", "6", "Fighter"] {
+            assert_eq!(from_roman(invalid), None, "{invalid}");
+        }
+        assert_eq!(to_roman(0), None);
+        assert_eq!(to_roman(MAX_ROMAN + 1), None);
+        assert_eq!(LevelNumerals::Roman.format(0), "0");
+        assert_eq!(LevelNumerals::Roman.format(6), "VI");
+        assert_eq!(LevelNumerals::Arabic.format(6), "6");
+    }
 }